@@ -0,0 +1,12 @@
+use bevy::prelude::*;
+
+// spawned only to be seen, like projectiles
+// and the sprites of units that died
+#[derive(Component)]
+pub struct Effect;
+
+// damage numbers rising from where they were dealt
+#[derive(Component)]
+pub struct FloatingText {
+    pub timer: Timer,
+}
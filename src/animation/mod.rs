@@ -0,0 +1,43 @@
+use bevy::prelude::*;
+
+use crate::{
+    command::CommandSet,
+    state::{game_running, AppState, GameSetupSet},
+    util::despawn_all,
+};
+
+use self::{
+    components::Effect,
+    resources::AnimationQueue,
+    systems::{float_texts, play_animations, queue_animations, reset_animations},
+};
+
+pub mod components;
+pub mod resources;
+pub mod systems;
+
+// plays back what the rules did in an instant,
+// local input and the ai wait until it's done
+pub struct AnimationPlugin;
+
+impl Plugin for AnimationPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<AnimationQueue>()
+            .add_systems(
+                OnEnter(AppState::InGame),
+                reset_animations.in_set(GameSetupSet::Resources),
+            )
+            .add_systems(OnExit(AppState::InGame), despawn_all::<Effect>)
+            .add_systems(
+                Update,
+                (
+                    queue_animations
+                        .after(CommandSet::Apply)
+                        .run_if(in_state(AppState::InGame)),
+                    (play_animations, float_texts)
+                        .after(queue_animations)
+                        .run_if(game_running),
+                ),
+            );
+    }
+}
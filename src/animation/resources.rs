@@ -0,0 +1,103 @@
+use std::collections::{HashMap, VecDeque};
+
+use bevy::prelude::*;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Motion {
+    // world positions, one per hex walked through
+    Path(Vec<Vec3>),
+    // into the target and back
+    Lunge { from: Vec3, toward: Vec3 },
+    // a projectile flying from the attacker to the target
+    Shot { from: Vec3, to: Vec3 },
+    Fade,
+}
+
+impl Motion {
+    // where the entity is after `progress` (0 to 1)
+    // of the motion, None when it doesn't move
+    pub fn position(&self, progress: f32) -> Option<Vec3> {
+        let progress = progress.clamp(0., 1.);
+
+        match self {
+            Motion::Path(path) => {
+                let segments = path.len().saturating_sub(1);
+                if segments == 0 {
+                    return path.first().copied();
+                }
+
+                let along = progress * segments as f32;
+                let segment = (along as usize).min(segments - 1);
+                Some(path[segment].lerp(path[segment + 1], along - segment as f32))
+            }
+            // half way into the target tile at the peak
+            Motion::Lunge { from, toward } => {
+                let reach = 1. - (progress * 2. - 1.).abs();
+                Some(from.lerp(*toward, reach * 0.5))
+            }
+            Motion::Shot { from, to } => Some(from.lerp(*to, progress)),
+            Motion::Fade => None,
+        }
+    }
+
+    pub fn end(&self) -> Option<Vec3> {
+        self.position(1.)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Animation {
+    pub entity: Entity,
+    pub motion: Motion,
+    pub seconds: f32,
+    // a floating text shown where it ends
+    pub label: Option<(String, Color, Vec3)>,
+}
+
+// played one after the other, units waiting for their turn
+// are held where the last animation left them, since the
+// rules already put them where they end up
+#[derive(Resource, Default)]
+pub struct AnimationQueue {
+    pub animations: VecDeque<Animation>,
+    pub elapsed: f32,
+    pub held: HashMap<Entity, Vec3>,
+}
+
+impl AnimationQueue {
+    pub fn is_idle(&self) -> bool {
+        self.animations.is_empty()
+    }
+
+    // `at` is where the entity is shown until
+    // its animation comes up, if it isn't held yet
+    pub fn push(&mut self, animation: Animation, at: Option<Vec3>) {
+        if let Some(at) = at {
+            self.held.entry(animation.entity).or_insert(at);
+        }
+        self.animations.push_back(animation);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn motions_end_where_they_should() {
+        let path = Motion::Path(vec![Vec3::ZERO, Vec3::X, Vec3::new(1., 1., 0.)]);
+        assert_eq!(path.position(0.), Some(Vec3::ZERO));
+        assert_eq!(path.position(0.5), Some(Vec3::X));
+        assert_eq!(path.position(0.75), Some(Vec3::new(1., 0.5, 0.)));
+        assert_eq!(path.end(), Some(Vec3::new(1., 1., 0.)));
+
+        let lunge = Motion::Lunge {
+            from: Vec3::ZERO,
+            toward: Vec3::X,
+        };
+        assert_eq!(lunge.position(0.5), Some(Vec3::X * 0.5));
+        assert_eq!(lunge.end(), Some(Vec3::ZERO));
+
+        assert_eq!(Motion::Fade.end(), None);
+    }
+}
@@ -0,0 +1,313 @@
+use bevy::prelude::*;
+use hexx::Hex;
+
+use crate::{
+    board::{
+        resources::{BoardIndex, HexColors},
+        HEX_LAYOUT, HEX_SIZE,
+    },
+    buildings::components::Building,
+    command::{
+        events::{GameEvent, GameEventKind},
+        rules::Strike,
+    },
+    menu::systems::FONT,
+    units::{
+        components::Unit,
+        systems::{walkable, UNIT_SPRITE_SIZE},
+    },
+};
+
+use super::{
+    components::{Effect, FloatingText},
+    resources::{Animation, AnimationQueue, Motion},
+};
+
+const STEP_SECONDS: f32 = 0.15;
+const LUNGE_SECONDS: f32 = 0.25;
+const SHOT_SECONDS: f32 = 0.1;
+const FADE_SECONDS: f32 = 0.4;
+const FLOAT_SECONDS: f32 = 0.8;
+
+fn world_position(hex: Hex) -> Vec3 {
+    HEX_LAYOUT.hex_to_world_pos(hex).extend(1.)
+}
+
+pub fn reset_animations(mut commands: Commands) {
+    commands.insert_resource(AnimationQueue::default());
+}
+
+pub fn animations_finished(queue: Option<Res<AnimationQueue>>) -> bool {
+    queue.is_none_or(|queue| queue.is_idle())
+}
+
+// turns what the rules did into animations, the rules
+// have already moved every unit to where it ends up
+pub fn queue_animations(
+    mut commands: Commands,
+    mut game_events: EventReader<GameEvent>,
+    mut queue: ResMut<AnimationQueue>,
+    asset_server: Res<AssetServer>,
+    colors: Res<HexColors>,
+    board: (Query<&Unit>, Res<BoardIndex>, Query<&Building>),
+) {
+    let (units, board_index, buildings) = board;
+
+    for event in game_events.iter() {
+        match event.kind {
+            GameEventKind::Moved { unit, from, to } => {
+                // the unit's own moves, straight
+                // there if it has none that get there
+                let route = units
+                    .get(unit)
+                    .ok()
+                    .and_then(|moved| {
+                        let start = Unit {
+                            position: from,
+                            ..moved.clone()
+                        };
+                        start.path_to(to, |hex| {
+                            hex == to || walkable(&board_index, &buildings, hex)
+                        })
+                    })
+                    .unwrap_or_else(|| vec![to]);
+                let hexes = [from].into_iter().chain(route).collect::<Vec<_>>();
+
+                let distance = hexes
+                    .windows(2)
+                    .map(|step| step[0].unsigned_distance_to(step[1]))
+                    .sum::<u32>();
+                let animation = Animation {
+                    entity: unit,
+                    motion: Motion::Path(hexes.into_iter().map(world_position).collect()),
+                    seconds: STEP_SECONDS * distance as f32,
+                    label: None,
+                };
+                queue.push(animation, Some(world_position(from)));
+            }
+            GameEventKind::Attacked {
+                attacker,
+                from,
+                target,
+                strike,
+                damage,
+            } => {
+                let distance = from.unsigned_distance_to(target);
+                let label = Some(damage_label(strike, damage, world_position(target)));
+                let (from, to) = (world_position(from), world_position(target));
+
+                // neighbors are hit up close,
+                // everything further away shoots
+                if distance <= 1 {
+                    let animation = Animation {
+                        entity: attacker,
+                        motion: Motion::Lunge { from, toward: to },
+                        seconds: LUNGE_SECONDS,
+                        label,
+                    };
+                    queue.push(animation, Some(from));
+                    continue;
+                }
+
+                queue.held.entry(attacker).or_insert(from);
+
+                let shot = commands
+                    .spawn(SpriteBundle {
+                        sprite: Sprite {
+                            color: Color::WHITE,
+                            custom_size: Some(Vec2::splat(HEX_SIZE / 8.)),
+                            ..default()
+                        },
+                        transform: Transform::from_translation(from + Vec3::Z),
+                        visibility: Visibility::Hidden,
+                        ..default()
+                    })
+                    .insert(Effect)
+                    .id();
+                let animation = Animation {
+                    entity: shot,
+                    motion: Motion::Shot {
+                        from: from + Vec3::Z,
+                        to: to + Vec3::Z,
+                    },
+                    seconds: SHOT_SECONDS * distance as f32,
+                    label,
+                };
+                queue.push(animation, None);
+            }
+            // the unit is despawned right away, the rules are
+            // done with it, a copy of its sprite fades in its place
+            GameEventKind::Died { at, kind, .. } => {
+                let color = colors.team(event.team).map_or(Color::WHITE, |c| c.sprite);
+                let ghost = commands
+                    .spawn(SpriteBundle {
+                        sprite: Sprite { color, ..default() },
+                        texture: asset_server.load(kind.sprite_location()),
+                        transform: Transform::from_translation(world_position(at))
+                            .with_scale(Vec3::splat(UNIT_SPRITE_SIZE)),
+                        ..default()
+                    })
+                    .insert(Effect)
+                    .id();
+                let animation = Animation {
+                    entity: ghost,
+                    motion: Motion::Fade,
+                    seconds: FADE_SECONDS,
+                    label: None,
+                };
+                queue.push(animation, None);
+            }
+            _ => {}
+        }
+    }
+}
+
+fn damage_label(strike: Strike, damage: i32, at: Vec3) -> (String, Color, Vec3) {
+    let (text, color) = match strike {
+        Strike::Miss => ("Miss".to_string(), Color::WHITE),
+        Strike::Dodged => ("Dodged".to_string(), Color::WHITE),
+        Strike::Hit(_) => (format!("-{damage}"), Color::RED),
+        Strike::Critical(_) => (format!("-{damage}!"), Color::GOLD),
+    };
+
+    (text, color, at)
+}
+
+pub fn play_animations(
+    mut commands: Commands,
+    time: Res<Time>,
+    asset_server: Res<AssetServer>,
+    mut queue: ResMut<AnimationQueue>,
+    mut animated: Query<(&mut Transform, Option<&mut Sprite>, &mut Visibility)>,
+) {
+    let AnimationQueue {
+        animations,
+        elapsed,
+        held,
+    } = queue.as_mut();
+
+    // units despawned since, by dying or a
+    // reloaded game, have nothing left to show
+    while animations
+        .front()
+        .is_some_and(|animation| !animated.contains(animation.entity))
+    {
+        animations.pop_front();
+        *elapsed = 0.;
+    }
+
+    if let Some(animation) = animations.front() {
+        *elapsed += time.delta_seconds();
+        let progress = match animation.seconds > 0. {
+            true => *elapsed / animation.seconds,
+            false => 1.,
+        };
+
+        if let Ok((mut transform, sprite, mut visibility)) = animated.get_mut(animation.entity) {
+            visibility.set_if_neq(Visibility::Inherited);
+
+            match animation.motion.position(progress) {
+                Some(position) => transform.translation = position,
+                None => {
+                    if let Some(mut sprite) = sprite {
+                        sprite.color.set_a(1. - progress.min(1.));
+                    }
+                }
+            }
+        }
+
+        if progress >= 1. {
+            let animation = animations
+                .pop_front()
+                .expect("there is a current animation");
+            *elapsed = 0.;
+
+            if let Some((text, color, at)) = animation.label {
+                spawn_floating_text(&mut commands, &asset_server, text, color, at);
+            }
+
+            match animation.motion {
+                // shots and faded units are done
+                Motion::Shot { .. } | Motion::Fade => {
+                    commands.entity(animation.entity).despawn_recursive()
+                }
+                _ => match animation.motion.end() {
+                    Some(end) if animations.iter().any(|a| a.entity == animation.entity) => {
+                        held.insert(animation.entity, end);
+                    }
+                    _ => {
+                        held.remove(&animation.entity);
+                    }
+                },
+            }
+        }
+    }
+
+    // everyone else waits for their turn
+    let current = animations.front().map(|animation| animation.entity);
+    held.retain(|entity, position| {
+        if Some(*entity) == current {
+            return true;
+        }
+
+        // ranged attackers are held without an animation of their own
+        if !animations.iter().any(|a| a.entity == *entity) && current.is_none() {
+            return false;
+        }
+
+        match animated.get_mut(*entity) {
+            Ok((mut transform, _, _)) => {
+                transform.translation = *position;
+                true
+            }
+            Err(_) => false,
+        }
+    });
+}
+
+fn spawn_floating_text(
+    commands: &mut Commands,
+    asset_server: &AssetServer,
+    text: String,
+    color: Color,
+    at: Vec3,
+) {
+    commands.spawn((
+        Text2dBundle {
+            text: Text::from_section(
+                text,
+                TextStyle {
+                    font: asset_server.load(FONT),
+                    font_size: HEX_SIZE / 2.5,
+                    color,
+                },
+            ),
+            transform: Transform::from_translation(at + Vec3::Z * 2.),
+            ..default()
+        },
+        FloatingText {
+            timer: Timer::from_seconds(FLOAT_SECONDS, TimerMode::Once),
+        },
+        Effect,
+    ));
+}
+
+pub fn float_texts(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut texts: Query<(Entity, &mut FloatingText, &mut Transform, &mut Text)>,
+) {
+    for (entity, mut floating, mut transform, mut text) in &mut texts {
+        floating.timer.tick(time.delta());
+        transform.translation.y += HEX_SIZE * time.delta_seconds();
+
+        let left = floating.timer.percent_left();
+        for section in &mut text.sections {
+            section.style.color.set_a(left);
+        }
+
+        if floating.timer.finished() {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
@@ -0,0 +1,101 @@
+// frame times of the board rendering systems on
+// large boards, run them with
+// `cargo test --release benches -- --ignored --nocapture`
+
+use std::time::{Duration, Instant};
+
+use bevy::prelude::*;
+use hexx::Hex;
+
+use crate::{
+    board::{
+        components::{HexTile, Team, TileVariant},
+        resources::{BoardIndex, TeamSettings, Themes},
+        systems::{draw_borders, load_colors},
+    },
+    tile_highlighting::systems::{update_tile_highlights, update_tile_purpose_sprites},
+    units::resources::SelectedUnit,
+};
+
+const FRAMES: u32 = 50;
+
+fn board_app(radius: u32) -> App {
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, AssetPlugin::default()))
+        .add_asset::<Mesh>()
+        .add_asset::<ColorMaterial>()
+        .add_asset::<Image>()
+        .init_resource::<TeamSettings>()
+        .init_resource::<Themes>()
+        .init_resource::<BoardIndex>()
+        .insert_resource(SelectedUnit(None))
+        .add_systems(Startup, load_colors)
+        .add_systems(
+            Update,
+            (draw_borders, update_tile_highlights, update_tile_purpose_sprites),
+        );
+
+    // a few ragged territories, so borders
+    // have concave corners and holes
+    for coordinate in Hex::ZERO.range(radius) {
+        let team = match (coordinate.x.rem_euclid(7), coordinate.y.rem_euclid(5)) {
+            (0, _) | (_, 0) => Team::Neutral,
+            _ if coordinate.x < 0 => Team::Player(0),
+            _ => Team::Player(1),
+        };
+
+        app.world.spawn((
+            HexTile {
+                coordinate,
+                variant: TileVariant::Land,
+                capture_progress: 0,
+                team,
+                resource: None,
+            },
+            Handle::<ColorMaterial>::default(),
+        ));
+    }
+
+    app.update();
+    app
+}
+
+fn average_frame(app: &mut App, capture_tiles: bool) -> Duration {
+    let start = Instant::now();
+
+    for frame in 0..FRAMES {
+        // one tile changing hands, the most a
+        // round end usually changes at once
+        if capture_tiles {
+            let mut hexes = app.world.query::<&mut HexTile>();
+            if let Some(mut hex) = hexes.iter_mut(&mut app.world).next() {
+                hex.team = Team::Player(frame as u8 % 2);
+            }
+        }
+
+        app.update();
+    }
+
+    start.elapsed() / FRAMES
+}
+
+fn compare(radius: u32) {
+    let mut app = board_app(radius);
+
+    let capturing = average_frame(&mut app, true);
+    let idle = average_frame(&mut app, false);
+
+    println!("radius {radius}: {capturing:?} with a tile captured every frame, {idle:?} idle");
+}
+
+#[test]
+#[ignore]
+fn board_rendering_radius_30() {
+    compare(30);
+}
+
+#[test]
+#[ignore]
+fn board_rendering_radius_45() {
+    compare(45);
+}
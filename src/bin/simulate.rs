@@ -0,0 +1,44 @@
+// plays ai against ai without a window, like
+// `cargo run --bin simulate -- --games 20 --seeds 1,2
+// --sizes 4,6 --ai balanced,rush --out results.csv`
+// writes csv for a .csv path and json otherwise
+
+use std::{fs, process};
+
+use bevy_hexagons::simulation::{report::Report, run, SimulationConfig};
+
+fn main() {
+    let args = std::env::args().collect::<Vec<_>>();
+    let config = SimulationConfig::from_args(&args).unwrap_or_else(|error| {
+        eprintln!("{error}");
+        process::exit(1);
+    });
+    let out = args
+        .windows(2)
+        .find(|pair| pair[0] == "--out")
+        .map_or("simulation.json", |pair| pair[1].as_str());
+
+    let report = Report::new(run(&config));
+
+    for summary in &report.summaries {
+        println!(
+            "{}: {} wins, {} losses, {} draws ({:.0}%)",
+            summary.name,
+            summary.wins,
+            summary.losses,
+            summary.draws,
+            summary.win_rate * 100.
+        );
+    }
+    println!("average game length: {:.1} turns", report.average_turns);
+
+    let contents = if out.ends_with(".csv") {
+        report.to_csv()
+    } else {
+        serde_json::to_string_pretty(&report).expect("the report is always valid json")
+    };
+    if let Err(error) = fs::write(out, contents) {
+        eprintln!("could not write {out}: {error}");
+        process::exit(1);
+    }
+}
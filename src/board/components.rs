@@ -1,64 +1,151 @@
 use bevy::prelude::*;
 use hexx::Hex;
+use serde::{Deserialize, Serialize};
 
-use super::resources::HexColors;
+use super::resources::{HexColors, TeamColors};
 
 #[derive(Component)]
-pub struct Border;
+pub struct Border(pub Team);
 
-#[derive(Reflect, PartialEq, Debug, Clone, Copy)]
+#[derive(Component)]
+pub struct BoardBackground;
+
+#[derive(Reflect, PartialEq, Eq, Hash, Debug, Clone, Copy, Serialize, Deserialize)]
 pub enum Team {
     Neutral,
-    Ally,
-    Enemy,
+    // index into TeamSettings
+    Player(u8),
+}
+
+impl Team {
+    pub fn id(&self) -> Option<usize> {
+        match self {
+            Team::Neutral => None,
+            Team::Player(id) => Some(*id as usize),
+        }
+    }
+
+    // (sides, rotation) of the shape marking the
+    // team, so teams differ by more than color
+    pub fn emblem(&self) -> Option<(usize, f32)> {
+        let emblems = [
+            (24, 0.),
+            (3, 0.),
+            (4, 45_f32.to_radians()),
+            (4, 0.),
+            (3, 180_f32.to_radians()),
+            (5, 0.),
+        ];
+
+        emblems.get(self.id()?).copied()
+    }
 }
 
-#[derive(Reflect, PartialEq, Debug)]
+// marks a team's emblem, on top
+// of a unit or a capital
+#[derive(Component)]
+pub struct Emblem(pub Team);
+
+#[derive(Reflect, PartialEq, Eq, Debug, Clone, Copy, Serialize, Deserialize)]
 pub enum TileVariant {
     Land,
     Capital,
 }
 
-#[derive(Component, PartialEq)]
+#[derive(Reflect, PartialEq, Eq, Hash, Debug, Clone, Copy, Serialize, Deserialize)]
+pub enum TileResource {
+    GoldMine,
+    Farm,
+    Shrine,
+}
+
+impl TileResource {
+    // coins per round for the owning team,
+    // instead of what a plain tile pays
+    pub fn income(&self) -> i32 {
+        match self {
+            TileResource::GoldMine => 3,
+            TileResource::Farm => 2,
+            TileResource::Shrine => 1,
+        }
+    }
+
+    // health restored every round to a unit
+    // of the owning team standing on it
+    pub fn healing(&self) -> i32 {
+        match self {
+            TileResource::Shrine => 1,
+            _ => 0,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            TileResource::GoldMine => "Gold mine",
+            TileResource::Farm => "Farm",
+            TileResource::Shrine => "Shrine",
+        }
+    }
+
+    pub fn sprite_location(&self) -> String {
+        match self {
+            TileResource::GoldMine => "sprites/gold_mine.png".to_string(),
+            TileResource::Farm => "sprites/farm.png".to_string(),
+            TileResource::Shrine => "sprites/shrine.png".to_string(),
+        }
+    }
+
+    pub fn icon_color(&self) -> Color {
+        match self {
+            TileResource::GoldMine => Color::rgb_u8(250, 200, 60),
+            TileResource::Farm => Color::rgb_u8(150, 220, 90),
+            TileResource::Shrine => Color::rgb_u8(235, 235, 255),
+        }
+    }
+}
+
+#[derive(Component, PartialEq, Clone, Debug, Serialize, Deserialize)]
 pub struct HexTile {
     pub coordinate: Hex,
     pub variant: TileVariant,
     pub capture_progress: i32,
     pub team: Team,
+    #[serde(default)]
+    pub resource: Option<TileResource>,
 }
 
+// the icon of a tile's resource
+#[derive(Component)]
+pub struct ResourceIcon;
+
 impl HexTile {
-    pub fn strong_highlight(&self, colors: &Res<HexColors>) -> Handle<ColorMaterial> {
-        if self.team == Team::Ally && self.variant == TileVariant::Capital {
-            return colors.ally_capital_strong_highlight.clone();
+    fn capital_colors<'a>(&self, colors: &'a HexColors) -> Option<&'a TeamColors> {
+        if self.variant != TileVariant::Capital {
+            return None;
         }
 
-        if self.team == Team::Enemy && self.variant == TileVariant::Capital {
-            return colors.enemy_capital_strong_highlight.clone();
+        colors.team(self.team)
+    }
+
+    pub fn strong_highlight(&self, colors: &Res<HexColors>) -> Handle<ColorMaterial> {
+        if let Some(team_colors) = self.capital_colors(colors) {
+            return team_colors.capital_strong_highlight.clone();
         }
 
         colors.neutral_strong_highlight.clone()
     }
 
     pub fn weak_highlight(&self, colors: &Res<HexColors>) -> Handle<ColorMaterial> {
-        if self.team == Team::Ally && self.variant == TileVariant::Capital {
-            return colors.ally_capital_weak_highlight.clone();
-        }
-
-        if self.team == Team::Enemy && self.variant == TileVariant::Capital {
-            return colors.enemy_capital_weak_highlight.clone();
+        if let Some(team_colors) = self.capital_colors(colors) {
+            return team_colors.capital_weak_highlight.clone();
         }
 
         colors.neutral_weak_highlight.clone()
     }
 
     pub fn base_color(&self, colors: &Res<HexColors>) -> Handle<ColorMaterial> {
-        if self.team == Team::Ally && self.variant == TileVariant::Capital {
-            return colors.ally_capital.clone();
-        }
-
-        if self.team == Team::Enemy && self.variant == TileVariant::Capital {
-            return colors.enemy_capital.clone();
+        if let Some(team_colors) = self.capital_colors(colors) {
+            return team_colors.capital.clone();
         }
 
         colors.neutral.clone()
@@ -0,0 +1,68 @@
+use std::collections::HashMap;
+
+use hexx::Hex;
+
+use super::components::TileResource;
+
+// about one tile in this many gets a resource
+const RESOURCE_RARITY: usize = 10;
+
+// resources scattered over the board, the same seed
+// always gives the same map. tiles next to a capital
+// are left plain so no team starts with a resource
+pub fn generate_resources(radius: u32, seed: u64, capitals: &[Hex]) -> HashMap<Hex, TileResource> {
+    let mut rng = fastrand::Rng::with_seed(seed);
+
+    let mut candidates = Hex::ZERO
+        .range(radius)
+        .filter(|hex| capitals.iter().all(|capital| capital.unsigned_distance_to(*hex) > 1))
+        .collect::<Vec<_>>();
+    candidates.sort_by_key(|hex| (hex.x, hex.y));
+
+    let count = Hex::range_count(radius) as usize / RESOURCE_RARITY;
+    let mut resources = HashMap::new();
+
+    while resources.len() < count && !candidates.is_empty() {
+        let hex = candidates.swap_remove(rng.usize(..candidates.len()));
+
+        // farms are twice as common as the others
+        let resource = match rng.u8(..4) {
+            0 => TileResource::GoldMine,
+            1 => TileResource::Shrine,
+            _ => TileResource::Farm,
+        };
+
+        resources.insert(hex, resource);
+    }
+
+    resources
+}
+
+#[cfg(test)]
+mod tests {
+    use hexx::hex;
+
+    use super::*;
+
+    #[test]
+    fn same_seed_same_resources() {
+        let capitals = [hex(0, 0)];
+
+        assert_eq!(generate_resources(5, 7, &capitals), generate_resources(5, 7, &capitals));
+        assert_ne!(generate_resources(5, 7, &capitals), generate_resources(5, 8, &capitals));
+    }
+
+    #[test]
+    fn capitals_start_without_resources() {
+        let capitals = [hex(0, 0), hex(3, 0)];
+
+        for seed in 0..20 {
+            let resources = generate_resources(5, seed, &capitals);
+
+            assert_eq!(resources.len(), 9);
+            assert!(resources
+                .keys()
+                .all(|hex| capitals.iter().all(|capital| capital.unsigned_distance_to(*hex) > 1)));
+        }
+    }
+}
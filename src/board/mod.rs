@@ -1,11 +1,30 @@
 use bevy::prelude::*;
 use hexx::HexLayout;
 
-use self::systems::{build_board, draw_borders, load_colors};
+use crate::{
+    state::{game_running, AppState, GameSetupSet},
+    util::despawn_all,
+};
+
+use self::{
+    components::{BoardBackground, Border, HexTile, ResourceIcon},
+    resources::{TeamSettings, Themes},
+    systems::{
+        apply_match_settings, build_board, draw_borders, draw_resource_icons, fit_camera,
+        load_colors,
+        place_team_capitals, recolor_background, reset_board_index, switch_theme,
+    },
+};
 
 pub mod components;
+pub mod generator;
+pub mod outline;
 pub mod resources;
 pub mod systems;
+pub mod theme;
+
+// the default map radius
+pub const HEX_RADIUS: i32 = 5;
 
 // how many hex_rads larger the
 // background hex should be
@@ -13,7 +32,6 @@ pub const BACKGROUND_HEX_SIZE: f32 = 1.8;
 
 pub const HEX_SIZE: f32 = 40.;
 pub const HEX_GAP: f32 = 2.5;
-pub const HEX_RADIUS: i32 = 5;
 
 pub const HEX_LAYOUT: HexLayout = HexLayout {
     orientation: hexx::HexOrientation::Pointy,
@@ -27,8 +45,38 @@ pub struct BoardPlugin;
 
 impl Plugin for BoardPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(PreStartup, load_colors)
-        .add_systems(Startup, build_board)
-        .add_systems(Update, draw_borders);
+        app.init_resource::<TeamSettings>()
+            .init_resource::<Themes>()
+            .add_systems(
+                OnEnter(AppState::InGame),
+                (
+                    (apply_match_settings, load_colors)
+                        .chain()
+                        .in_set(GameSetupSet::Resources),
+                    build_board.in_set(GameSetupSet::Spawn),
+                    (place_team_capitals, fit_camera).in_set(GameSetupSet::Place),
+                ),
+            )
+            .add_systems(
+                OnExit(AppState::InGame),
+                (
+                    despawn_all::<HexTile>,
+                    despawn_all::<Border>,
+                    despawn_all::<BoardBackground>,
+                    despawn_all::<ResourceIcon>,
+                    reset_board_index,
+                ),
+            )
+            // themes can also be switched from the menus
+            .add_systems(
+                Update,
+                (
+                    switch_theme.run_if(game_running),
+                    load_colors.run_if(resource_changed::<Themes>()),
+                    apply_deferred,
+                    (draw_borders, draw_resource_icons, recolor_background).run_if(game_running),
+                )
+                    .chain(),
+            );
     }
 }
@@ -0,0 +1,211 @@
+use std::collections::{HashMap, HashSet};
+
+use bevy::{
+    prelude::*,
+    render::{mesh::Indices, render_resource::PrimitiveTopology},
+};
+use hexx::Hex;
+
+use super::HEX_LAYOUT;
+
+// goes around a hex, each direction
+// is next to the ones beside it
+const DIRECTIONS: [Hex; 6] = Hex::NEIGHBORS_COORDS;
+
+// groups of tiles connected through
+// a shared edge
+pub fn connected_regions(tiles: &HashSet<Hex>) -> Vec<HashSet<Hex>> {
+    let mut unvisited = tiles.clone();
+    let mut regions = Vec::new();
+
+    while let Some(&start) = unvisited.iter().next() {
+        unvisited.remove(&start);
+
+        let mut region = HashSet::from([start]);
+        let mut frontier = vec![start];
+
+        while let Some(hex) = frontier.pop() {
+            for neighbor in hex.all_neighbors() {
+                if unvisited.remove(&neighbor) {
+                    region.insert(neighbor);
+                    frontier.push(neighbor);
+                }
+            }
+        }
+
+        regions.push(region);
+    }
+
+    regions
+}
+
+// the closed outlines of a region, its outer
+// boundary and one for every hole in it.
+//
+// corners are kept as three times their (fractional)
+// axial coordinate, so a corner shared by three hexes
+// always has the same key. only three edges meet at a
+// corner, so a corner starts at most one boundary edge
+// and the edges chain into loops without guessing
+pub fn region_outlines(region: &HashSet<Hex>) -> Vec<Vec<Vec2>> {
+    let mut edges = HashMap::new();
+
+    for hex in region {
+        for (i, direction) in DIRECTIONS.iter().enumerate() {
+            if region.contains(&(*hex + *direction)) {
+                continue;
+            }
+
+            let previous = DIRECTIONS[(i + 5) % 6];
+            let next = DIRECTIONS[(i + 1) % 6];
+
+            edges.insert(
+                *hex * 3 + *direction + previous,
+                *hex * 3 + *direction + next,
+            );
+        }
+    }
+
+    let mut outlines = Vec::new();
+    while let Some(&start) = edges.keys().next() {
+        let mut points = Vec::new();
+        let mut corner = start;
+
+        while let Some(next) = edges.remove(&corner) {
+            points.push(corner_position(corner));
+            corner = next;
+        }
+
+        outlines.push(points);
+    }
+
+    outlines
+}
+
+fn corner_position(corner: Hex) -> Vec2 {
+    (HEX_LAYOUT.hex_to_world_pos(corner) - HEX_LAYOUT.origin) / 3. + HEX_LAYOUT.origin
+}
+
+// a single mesh drawing every closed outline
+// as a strip of the given width, corners are
+// mitered so the strip has no gaps
+pub fn outline_mesh(outlines: &[Vec<Vec2>], width: f32) -> Mesh {
+    let mut positions = Vec::new();
+    let mut indices = Vec::new();
+
+    for points in outlines {
+        let first = positions.len() as u32;
+        let count = points.len();
+
+        for (i, point) in points.iter().enumerate() {
+            let previous = points[(i + count - 1) % count];
+            let next = points[(i + 1) % count];
+
+            let in_normal = (*point - previous).normalize().perp();
+            let out_normal = (next - *point).normalize().perp();
+            let miter = (in_normal + out_normal).normalize();
+            let offset = miter * (width / 2.) / miter.dot(in_normal);
+
+            positions.push((*point + offset).extend(0.).to_array());
+            positions.push((*point - offset).extend(0.).to_array());
+        }
+
+        for i in 0..count as u32 {
+            let outer = first + i * 2;
+            let inner = outer + 1;
+            let next_outer = first + ((i + 1) % count as u32) * 2;
+            let next_inner = next_outer + 1;
+
+            indices.extend([outer, inner, next_outer, next_outer, inner, next_inner]);
+        }
+    }
+
+    let vertex_count = positions.len();
+
+    let mut mesh = Mesh::new(PrimitiveTopology::TriangleList);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, vec![[0., 0., 1.]; vertex_count]);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, vec![[0., 0.]; vertex_count]);
+    mesh.set_indices(Some(Indices::U32(indices)));
+    mesh
+}
+
+#[cfg(test)]
+mod tests {
+    use hexx::hex;
+
+    use super::*;
+
+    fn outlines(tiles: &[Hex]) -> Vec<Vec<Vec2>> {
+        region_outlines(&tiles.iter().copied().collect())
+    }
+
+    // every corner is one hex edge away from the next,
+    // the last one included, and none is visited twice
+    fn assert_closed(outline: &[Vec2]) {
+        let edge = HEX_LAYOUT.hex_size.x;
+
+        for (i, point) in outline.iter().enumerate() {
+            let next = outline[(i + 1) % outline.len()];
+            assert!((point.distance(next) - edge).abs() < 1e-3);
+            assert!(outline[..i].iter().all(|other| other.distance(*point) > 1e-3));
+        }
+    }
+
+    #[test]
+    fn a_single_hex_is_one_loop_of_six_corners() {
+        let outlines = outlines(&[hex(0, 0)]);
+
+        assert_eq!(outlines.len(), 1);
+        assert_eq!(outlines[0].len(), 6);
+        assert_closed(&outlines[0]);
+
+        let mesh = outline_mesh(&outlines, 1.);
+        assert_eq!(mesh.count_vertices(), 12);
+        assert_eq!(mesh.indices().unwrap().len(), 36);
+    }
+
+    #[test]
+    fn a_ring_has_an_outer_loop_and_one_around_its_hole() {
+        let ring = hex(0, 0).all_neighbors();
+        let regions = connected_regions(&ring.into_iter().collect());
+        assert_eq!(regions.len(), 1);
+
+        let mut outlines = outlines(&ring);
+        outlines.sort_by_key(Vec::len);
+
+        assert_eq!(outlines.iter().map(Vec::len).collect::<Vec<_>>(), vec![6, 18]);
+        outlines.iter().for_each(|outline| assert_closed(outline));
+    }
+
+    #[test]
+    fn hexes_meeting_across_a_gap_stay_separate() {
+        // both border the same two tiles, which
+        // share an edge, but not each other
+        let tiles = HashSet::from([hex(0, 0), hex(1, 1)]);
+        let regions = connected_regions(&tiles);
+        assert_eq!(regions.len(), 2);
+
+        let outlines = regions.iter().flat_map(region_outlines).collect::<Vec<_>>();
+        assert_eq!(outlines.len(), 2);
+        outlines.iter().for_each(|outline| {
+            assert_eq!(outline.len(), 6);
+            assert_closed(outline);
+        });
+        assert!(outlines[0]
+            .iter()
+            .all(|corner| outlines[1].iter().all(|other| other.distance(*corner) > 1e-3)));
+    }
+
+    #[test]
+    fn a_concave_shape_is_one_loop() {
+        let mut ring = hex(0, 0).all_neighbors().to_vec();
+        ring.remove(0);
+
+        let outlines = outlines(&ring);
+
+        assert_eq!(outlines.len(), 1);
+        assert_eq!(outlines[0].len(), 22);
+        assert_closed(&outlines[0]);
+    }
+}
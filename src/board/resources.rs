@@ -1,4 +1,15 @@
+use std::collections::HashMap;
+
 use bevy::prelude::*;
+use hexx::{hex, Hex};
+
+use super::{
+    components::Team,
+    theme::{load_themes, Theme, THEME_DIRECTORY},
+};
+
+pub const MIN_TEAMS: usize = 2;
+pub const MAX_TEAMS: usize = 6;
 
 #[derive(Resource)]
 pub struct HexColors {
@@ -8,20 +19,264 @@ pub struct HexColors {
     pub neutral_weak_highlight: Handle<ColorMaterial>,
     pub neutral_strong_highlight: Handle<ColorMaterial>,
 
-    
-    pub ally_sprite: Color,
-    pub ally_unused_action_color: Color,
-    pub ally_used_action_color: Color,
-    pub ally_border_color: Handle<ColorMaterial>,
-    pub ally_capital: Handle<ColorMaterial>,
-    pub ally_capital_weak_highlight: Handle<ColorMaterial>,
-    pub ally_capital_strong_highlight: Handle<ColorMaterial>,
-
-    pub enemy_sprite: Color,
-    pub enemy_unused_action_color: Color,
-    pub enemy_used_action_color: Color,
-    pub enemy_border_color: Handle<ColorMaterial>,
-    pub enemy_capital: Handle<ColorMaterial>,
-    pub enemy_capital_weak_highlight: Handle<ColorMaterial>,
-    pub enemy_capital_strong_highlight: Handle<ColorMaterial>,
-}
\ No newline at end of file
+    // indexed by team id
+    pub teams: Vec<TeamColors>,
+}
+
+impl HexColors {
+    pub fn team(&self, team: Team) -> Option<&TeamColors> {
+        self.teams.get(team.id()?)
+    }
+}
+
+pub struct TeamColors {
+    pub sprite: Color,
+    pub unused_action_color: Color,
+    pub used_action_color: Color,
+    pub border_color: Handle<ColorMaterial>,
+    // the team's emblem on units and capitals
+    pub emblem: Handle<ColorMaterial>,
+    pub capital: Handle<ColorMaterial>,
+    pub capital_weak_highlight: Handle<ColorMaterial>,
+    pub capital_strong_highlight: Handle<ColorMaterial>,
+}
+
+// which tile, living unit and building is on a hex,
+// kept up to date by the command systems and
+// reconciled every frame by sync_board_index
+#[derive(Resource, Default)]
+pub struct BoardIndex {
+    tiles: HashMap<Hex, Entity>,
+    units: HashMap<Hex, Entity>,
+    unit_positions: HashMap<Entity, Hex>,
+    buildings: HashMap<Hex, Entity>,
+}
+
+impl BoardIndex {
+    pub fn tile(&self, hex: Hex) -> Option<Entity> {
+        self.tiles.get(&hex).copied()
+    }
+
+    pub fn unit(&self, hex: Hex) -> Option<Entity> {
+        self.units.get(&hex).copied()
+    }
+
+    pub fn building(&self, hex: Hex) -> Option<Entity> {
+        self.buildings.get(&hex).copied()
+    }
+
+    pub fn contains(&self, hex: Hex) -> bool {
+        self.tiles.contains_key(&hex)
+    }
+
+    pub fn insert_tile(&mut self, hex: Hex, entity: Entity) {
+        self.tiles.insert(hex, entity);
+    }
+
+    // also forgets where the unit was before
+    pub fn place_unit(&mut self, entity: Entity, hex: Hex) {
+        self.remove_unit(entity);
+        self.unit_positions.insert(entity, hex);
+        self.units.insert(hex, entity);
+    }
+
+    pub fn remove_unit(&mut self, entity: Entity) {
+        let Some(position) = self.unit_positions.remove(&entity) else {
+            return;
+        };
+
+        if self.units.get(&position) == Some(&entity) {
+            self.units.remove(&position);
+        }
+    }
+
+    // buildings never move
+    pub fn place_building(&mut self, entity: Entity, hex: Hex) {
+        self.buildings.insert(hex, entity);
+    }
+
+    pub fn remove_building(&mut self, entity: Entity) {
+        self.buildings.retain(|_, building| *building != entity);
+    }
+}
+
+// every loaded theme, HexColors is
+// rebuilt whenever this changes
+#[derive(Resource)]
+pub struct Themes {
+    pub themes: Vec<Theme>,
+    pub current: usize,
+}
+
+impl Default for Themes {
+    fn default() -> Self {
+        Themes {
+            themes: vec![Theme::default()],
+            current: 0,
+        }
+    }
+}
+
+impl Themes {
+    // the files in assets/themes, `--theme <name>`
+    // picks the one to start with
+    pub fn from_args() -> Self {
+        let mut themes = load_themes(THEME_DIRECTORY);
+        if themes.is_empty() {
+            themes.push(Theme::default());
+        }
+
+        let args = std::env::args().collect::<Vec<_>>();
+        let current = args
+            .windows(2)
+            .find(|pair| pair[0] == "--theme")
+            .and_then(|pair| themes.iter().position(|theme| theme.name == pair[1]))
+            .or_else(|| themes.iter().position(|theme| theme.name == "default"))
+            .unwrap_or(0);
+
+        Themes { themes, current }
+    }
+
+    pub fn current(&self) -> &Theme {
+        &self.themes[self.current]
+    }
+
+    pub fn next(&mut self) {
+        self.current = (self.current + 1) % self.themes.len();
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Controller {
+    // played on this machine
+    Local,
+    // played by a peer over the network
+    Remote,
+    Ai,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct TeamConfig {
+    // None lets whoever controls
+    // the team pick it at the start
+    pub capital: Option<Hex>,
+    pub controller: Controller,
+}
+
+#[derive(Resource, Clone, PartialEq, Debug)]
+pub struct TeamSettings {
+    // indexed by team id, also the turn order
+    pub teams: Vec<TeamConfig>,
+}
+
+impl Default for TeamSettings {
+    fn default() -> Self {
+        TeamSettings::new(MIN_TEAMS)
+    }
+}
+
+impl TeamSettings {
+    // the first team is played locally and picks
+    // its capital, every other team is an ai with
+    // its capital spread around the board
+    pub fn new(count: usize) -> Self {
+        let corners = [0, 3, 1, 4, 2, 5];
+
+        let teams = (0..count.clamp(MIN_TEAMS, MAX_TEAMS))
+            .map(|id| TeamConfig {
+                capital: match id {
+                    0 => None,
+                    1 => Some(hex(0, 0)),
+                    _ => Some(Hex::NEIGHBORS_COORDS[corners[id - 2]] * 3),
+                },
+                controller: if id == 0 {
+                    Controller::Local
+                } else {
+                    Controller::Ai
+                },
+            })
+            .collect();
+
+        TeamSettings { teams }
+    }
+
+    // `--teams <count>`, defaults to two
+    pub fn from_args() -> Self {
+        let args = std::env::args().collect::<Vec<_>>();
+
+        let count = args
+            .windows(2)
+            .find(|pair| pair[0] == "--teams")
+            .and_then(|pair| pair[1].parse().ok())
+            .unwrap_or(MIN_TEAMS);
+
+        TeamSettings::new(count)
+    }
+
+    pub fn teams(&self) -> Vec<Team> {
+        (0..self.teams.len()).map(|id| Team::Player(id as u8)).collect()
+    }
+
+    pub fn get(&self, team: Team) -> Option<&TeamConfig> {
+        self.teams.get(team.id()?)
+    }
+
+    pub fn get_mut(&mut self, team: Team) -> Option<&mut TeamConfig> {
+        self.teams.get_mut(team.id()?)
+    }
+
+    pub fn controller(&self, team: Team) -> Option<Controller> {
+        self.get(team).map(|config| config.controller)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use hexx::hex;
+
+    use super::*;
+
+    #[test]
+    fn a_moved_unit_leaves_its_old_hex() {
+        let mut index = BoardIndex::default();
+        let unit = Entity::from_raw(1);
+
+        index.place_unit(unit, hex(0, 0));
+        index.place_unit(unit, hex(1, 0));
+        assert_eq!(index.unit(hex(0, 0)), None);
+        assert_eq!(index.unit(hex(1, 0)), Some(unit));
+
+        index.remove_unit(unit);
+        assert_eq!(index.unit(hex(1, 0)), None);
+    }
+
+    #[test]
+    fn removing_a_unit_keeps_whoever_took_its_hex() {
+        let mut index = BoardIndex::default();
+        let (attacker, defender) = (Entity::from_raw(1), Entity::from_raw(2));
+
+        index.place_unit(attacker, hex(0, 0));
+        index.place_unit(defender, hex(1, 0));
+
+        // a Nimble attacker moves in before
+        // the defender is cleaned up
+        index.place_unit(attacker, hex(1, 0));
+        index.remove_unit(defender);
+
+        assert_eq!(index.unit(hex(0, 0)), None);
+        assert_eq!(index.unit(hex(1, 0)), Some(attacker));
+    }
+
+    #[test]
+    fn removed_buildings_leave_their_hex() {
+        let mut index = BoardIndex::default();
+        let (wall, tower) = (Entity::from_raw(1), Entity::from_raw(2));
+
+        index.place_building(wall, hex(0, 0));
+        index.place_building(tower, hex(1, 0));
+        index.remove_building(wall);
+
+        assert_eq!(index.building(hex(0, 0)), None);
+        assert_eq!(index.building(hex(1, 0)), Some(tower));
+    }
+}
@@ -1,47 +1,158 @@
+use std::collections::{HashMap, HashSet};
+
 use bevy::{prelude::*, sprite::MaterialMesh2dBundle};
 use hexx::Hex;
 
+use crate::{
+    buildings::components::Building,
+    scenario::resources::Scenario,
+    state::{MatchSettings, Opponent},
+    units::components::Unit,
+};
+
 use super::{
-    components::{Border, HexTile, Team, TileVariant},
-    resources::HexColors,
-    BACKGROUND_HEX_SIZE, HEX_GAP, HEX_RADIUS, HEX_SIZE, HEX_LAYOUT,
+    components::{
+        BoardBackground, Border, Emblem, HexTile, ResourceIcon, Team, TileResource, TileVariant,
+    },
+    generator::generate_resources,
+    outline::{connected_regions, outline_mesh, region_outlines},
+    resources::{BoardIndex, Controller, HexColors, TeamColors, TeamSettings, Themes},
+    theme::color,
+    BACKGROUND_HEX_SIZE, HEX_GAP, HEX_SIZE, HEX_LAYOUT,
 };
 
-pub fn load_colors(mut commands: Commands, mut materials: ResMut<Assets<ColorMaterial>>) {
+// the first team is always played locally,
+// the others by the chosen opponent
+pub fn apply_match_settings(
+    match_settings: Res<MatchSettings>,
+    mut team_settings: ResMut<TeamSettings>,
+) {
+    for (id, config) in team_settings.teams.iter_mut().enumerate() {
+        config.controller = match match_settings.opponent {
+            _ if id == 0 => Controller::Local,
+            Opponent::Hotseat => Controller::Local,
+            Opponent::Ai(_) => Controller::Ai,
+        };
+    }
+}
+
+// also runs whenever the theme changes, everything
+// drawn with HexColors redraws once it's replaced
+pub fn load_colors(
+    mut commands: Commands,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    themes: Res<Themes>,
+    team_settings: Res<TeamSettings>,
+) {
+    let theme = themes.current();
+
+    let teams = theme
+        .teams
+        .iter()
+        .take(team_settings.teams.len())
+        .map(|team| {
+            let sprite = color(team.sprite);
+            let capital = color(team.capital);
+            let unused_action_color = lighten(sprite, 30);
+
+            TeamColors {
+                sprite,
+                unused_action_color,
+                used_action_color: unused_action_color.with_a(50. / 255.),
+                border_color: materials.add(ColorMaterial::from(sprite.with_a(100. / 255.))),
+                emblem: materials.add(ColorMaterial::from(lighten(sprite, 50))),
+                capital: materials.add(ColorMaterial::from(capital)),
+                capital_weak_highlight: materials.add(ColorMaterial::from(lighten(capital, 35))),
+                capital_strong_highlight: materials
+                    .add(ColorMaterial::from(lighten(capital, 80))),
+            }
+        })
+        .collect();
+
+    commands.insert_resource(ClearColor(color(theme.clear)));
     commands.insert_resource(HexColors {
-        backround_hex: materials.add(ColorMaterial::from(Color::rgb_u8(25, 25, 25))),
-
-        neutral: materials.add(ColorMaterial::from(Color::rgb_u8(40, 40, 40))),
-        neutral_weak_highlight: materials.add(ColorMaterial::from(Color::rgb_u8(60, 60, 60))),
-        neutral_strong_highlight: materials.add(ColorMaterial::from(Color::rgb_u8(90, 90, 90))),
-
-        ally_sprite: Color::rgb_u8(70, 130, 250),
-        ally_unused_action_color: Color::rgb_u8(100, 150, 250),
-        ally_used_action_color: Color::rgba_u8(100, 150, 250, 50),
-        ally_border_color: materials.add(ColorMaterial::from(Color::rgba_u8(70, 130, 250, 100))),
-        ally_capital: materials.add(ColorMaterial::from(Color::rgb_u8(70, 70, 200))),
-        ally_capital_weak_highlight: materials
-            .add(ColorMaterial::from(Color::rgb_u8(100, 100, 240))),
-        ally_capital_strong_highlight: materials
-            .add(ColorMaterial::from(Color::rgb_u8(150, 150, 255))),
-
-        enemy_sprite: Color::rgb_u8(250, 130, 70),
-        enemy_unused_action_color: Color::rgb_u8(250, 150, 100),
-        enemy_used_action_color: Color::rgba_u8(250, 150, 100, 50),
-        enemy_border_color: materials.add(ColorMaterial::from(Color::rgba_u8(200, 70, 70, 100))),
-        enemy_capital: materials.add(ColorMaterial::from(Color::rgb_u8(200, 70, 70))),
-        enemy_capital_weak_highlight: materials
-            .add(ColorMaterial::from(Color::rgb_u8(240, 100, 100))),
-        enemy_capital_strong_highlight: materials
-            .add(ColorMaterial::from(Color::rgb_u8(255, 150, 150))),
+        backround_hex: materials.add(ColorMaterial::from(color(theme.background))),
+
+        neutral: materials.add(ColorMaterial::from(color(theme.neutral))),
+        neutral_weak_highlight: materials
+            .add(ColorMaterial::from(color(theme.neutral_weak_highlight))),
+        neutral_strong_highlight: materials
+            .add(ColorMaterial::from(color(theme.neutral_strong_highlight))),
+
+        teams,
     });
 }
 
+pub fn switch_theme(keys: Res<Input<KeyCode>>, mut themes: ResMut<Themes>) {
+    if keys.just_released(KeyCode::T) {
+        themes.next();
+    }
+}
+
+pub fn recolor_background(
+    colors: Res<HexColors>,
+    mut background: Query<&mut Handle<ColorMaterial>, With<BoardBackground>>,
+) {
+    if !colors.is_changed() {
+        return;
+    }
+
+    for mut material in &mut background {
+        material.set_if_neq(colors.backround_hex.clone());
+    }
+}
+
+// the team's emblem shape, sized to radius
+pub fn emblem_bundle(
+    team: Team,
+    radius: f32,
+    translation: Vec3,
+    meshes: &mut Assets<Mesh>,
+    colors: &HexColors,
+) -> Option<MaterialMesh2dBundle<ColorMaterial>> {
+    let (sides, rotation) = team.emblem()?;
+
+    Some(MaterialMesh2dBundle {
+        mesh: meshes
+            .add(shape::RegularPolygon::new(radius, sides).into())
+            .into(),
+        material: colors.team(team)?.emblem.clone(),
+        transform: Transform::from_translation(translation)
+            .with_rotation(Quat::from_rotation_z(rotation)),
+        ..default()
+    })
+}
+
+fn lighten(color: Color, amount: u8) -> Color {
+    let [r, g, b, a] = color.as_rgba_u8();
+    Color::rgba_u8(
+        r.saturating_add(amount),
+        g.saturating_add(amount),
+        b.saturating_add(amount),
+        a,
+    )
+}
+
 pub fn build_board(
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
     colors: Res<HexColors>,
+    match_settings: Res<MatchSettings>,
+    team_settings: Res<TeamSettings>,
+    scenario: Option<Res<Scenario>>,
 ) {
+    let radius = match_settings.map_radius;
+
+    let capitals = team_settings
+        .teams
+        .iter()
+        .filter_map(|config| config.capital)
+        .collect::<Vec<_>>();
+    let resources = match scenario.as_ref().and_then(|scenario| scenario.resources.as_ref()) {
+        Some(placed) => placed.iter().map(|p| (p.at, p.resource)).collect(),
+        None => generate_resources(radius, match_settings.seed, &capitals),
+    };
+
     let mut pointy_top_hex_mesh = MaterialMesh2dBundle {
         mesh: meshes
             .add(shape::RegularPolygon::new(HEX_SIZE, 6).into())
@@ -50,7 +161,7 @@ pub fn build_board(
         ..default()
     };
 
-    let hex_coords = Hex::ZERO.range(HEX_RADIUS as u32);
+    let hex_coords = Hex::ZERO.range(radius);
     for coord in hex_coords {
         // https://www.redblobgames.com/grids/hexagons/#hex-to-pixel
         pointy_top_hex_mesh.transform.translation = HEX_LAYOUT.hex_to_world_pos(coord).extend(1.);
@@ -60,12 +171,12 @@ pub fn build_board(
             variant: TileVariant::Land,
             capture_progress: 0,
             team: Team::Neutral,
+            resource: resources.get(&coord).copied(),
         });
     }
 
     let scale = 3_f32.sqrt() / 2.
-        * (2. * HEX_RADIUS as f32 * HEX_GAP
-            + HEX_SIZE * (2. * HEX_RADIUS as f32 + BACKGROUND_HEX_SIZE));
+        * (2. * radius as f32 * HEX_GAP + HEX_SIZE * (2. * radius as f32 + BACKGROUND_HEX_SIZE));
 
     let flat_top_hex_mesh = MaterialMesh2dBundle {
         mesh: meshes
@@ -76,174 +187,305 @@ pub fn build_board(
         ..default()
     };
 
-    commands.spawn(flat_top_hex_mesh);
+    commands.spawn(flat_top_hex_mesh).insert(BoardBackground);
 }
 
-pub fn draw_borders(
-    mut commands: Commands,
-    hexes: Query<&HexTile>,
-    mut meshes: ResMut<Assets<Mesh>>,
-    colors: Res<HexColors>,
-    borders: Query<Entity, With<Border>>,
+// zooms out until the whole board fits the window
+pub fn fit_camera(
+    match_settings: Res<MatchSettings>,
+    windows: Query<&Window>,
+    mut projections: Query<&mut OrthographicProjection>,
 ) {
-    let ally_point_group = tile_border(&hexes, Team::Ally);
-    let enemy_point_group = tile_border(&hexes, Team::Enemy);
-
-    let Some(ally_point_group) = ally_point_group else {
+    let Ok(window) = windows.get_single() else {
         return;
     };
 
-    let Some(enemy_point_group) = enemy_point_group else {
-        return;
-    };
+    let radius = match_settings.map_radius as f32;
+    let hex_size = HEX_SIZE + HEX_GAP;
+    let width = (2. * radius + 1.) * 3_f32.sqrt() * hex_size;
+    let height = (3. * radius + 2.) * hex_size;
+
+    let scale = (width / window.width())
+        .max(height / window.height())
+        .max(1.);
 
-    for border in &borders {
-        commands.entity(border).despawn();
+    for mut projection in &mut projections {
+        projection.scale = scale;
     }
+}
 
-    let first = ally_point_group[0][0];
-    let second = ally_point_group[0][1];
+pub fn reset_board_index(mut commands: Commands) {
+    commands.insert_resource(BoardIndex::default());
+}
 
-    let mut border = MaterialMesh2dBundle {
-        mesh: meshes
-            .add(shape::Quad::new(Vec2::new(first.distance(second), HEX_GAP * 2.)).into())
-            .into(),
-        material: colors.ally_border_color.clone(),
-        ..default()
-    };
+// picks up spawned tiles, spawned or despawned
+// units and buildings and any whose position or
+// health changed outside of the command systems
+pub fn sync_board_index(
+    mut board_index: ResMut<BoardIndex>,
+    tiles: Query<(Entity, &HexTile), Added<HexTile>>,
+    units: Query<(Entity, &Unit), Changed<Unit>>,
+    mut removed_units: RemovedComponents<Unit>,
+    buildings: Query<(Entity, &Building), Changed<Building>>,
+    mut removed_buildings: RemovedComponents<Building>,
+) {
+    for (entity, tile) in &tiles {
+        board_index.insert_tile(tile.coordinate, entity);
+    }
 
-    for points in ally_point_group {
-        for positions in points.windows(2) {
-            border.transform = Transform {
-                translation: Vec3::new(
-                    (positions[0].x + positions[1].x) / 2.,
-                    (positions[0].y + positions[1].y) / 2.,
-                    2.,
-                ),
-                rotation: Quat::from_axis_angle(
-                    Vec3::Z,
-                    Vec2::new(
-                        positions[0].x - positions[1].x,
-                        positions[0].y - positions[1].y,
-                    )
-                    .angle_between(Vec2::X)
-                        * -1.,
-                ),
-                ..Default::default()
-            };
-
-            commands.spawn(border.clone()).insert(Border);
+    for entity in removed_units.iter() {
+        board_index.remove_unit(entity);
+    }
+
+    for (entity, unit) in &units {
+        if unit.health > 0 {
+            board_index.place_unit(entity, unit.position);
+        } else {
+            board_index.remove_unit(entity);
+        }
+    }
+
+    for entity in removed_buildings.iter() {
+        board_index.remove_building(entity);
+    }
+
+    for (entity, building) in &buildings {
+        if building.health > 0 {
+            board_index.place_building(entity, building.position);
+        } else {
+            board_index.remove_building(entity);
         }
     }
+}
+
+pub fn place_team_capitals(mut hexes: Query<&mut HexTile>, team_settings: Res<TeamSettings>) {
+    for (team, config) in team_settings.teams().into_iter().zip(&team_settings.teams) {
+        let Some(capital) = config.capital else {
+            continue;
+        };
 
-    border.material = colors.enemy_border_color.clone();
-    for points in enemy_point_group {
-        for positions in points.windows(2) {
-            border.transform = Transform {
-                translation: Vec3::new(
-                    (positions[0].x + positions[1].x) / 2.,
-                    (positions[0].y + positions[1].y) / 2.,
-                    2.,
-                ),
-                rotation: Quat::from_axis_angle(
-                    Vec3::Z,
-                    Vec2::new(
-                        positions[0].x - positions[1].x,
-                        positions[0].y - positions[1].y,
-                    )
-                    .angle_between(Vec2::X)
-                        * -1.,
-                ),
-                ..Default::default()
-            };
-
-            commands.spawn(border.clone()).insert(Border);
+        let team_tiles = capital.all_neighbors();
+
+        for mut hex in &mut hexes {
+            if team_tiles.contains(&hex.coordinate) {
+                hex.team = team;
+            }
+
+            if hex.coordinate == capital {
+                hex.team = team;
+                hex.variant = TileVariant::Capital;
+            }
         }
     }
 }
 
-fn tile_border(hexes: &Query<&HexTile>, team: Team) -> Option<Vec<Vec<Vec2>>> {
-    let valid_tiles = Hex::ZERO.range(HEX_RADIUS as u32).collect::<Vec<Hex>>();
+// icons are only rebuilt when a
+// tile's resource changed
+pub fn draw_resource_icons(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    hexes: Query<&HexTile>,
+    changed_hexes: Query<(), Changed<HexTile>>,
+    mut drawn_resources: Local<HashMap<Hex, TileResource>>,
+    icons: Query<Entity, With<ResourceIcon>>,
+) {
+    if changed_hexes.is_empty() {
+        return;
+    }
 
-    let mut unsorted_points = hexes
+    let resources = hexes
         .iter()
-        .filter(|h| h.team == team)
-        .flat_map(|h| {
-            let neighbor_coords = h.coordinate.all_neighbors();
-            let hex_pixel_pos = HEX_LAYOUT.hex_to_world_pos(h.coordinate);
-
-            [
-                neighbor_coords
-                    .iter()
-                    .filter(|c| !valid_tiles.contains(c))
-                    .map(|c| {
-                        let pixel_pos = HEX_LAYOUT.hex_to_world_pos(*c);
-                        Vec2::new(
-                            (pixel_pos.x + hex_pixel_pos.x) / 2.,
-                            (pixel_pos.y + hex_pixel_pos.y) / 2.,
-                        )
-                    })
-                    .collect::<Vec<_>>(),
-                hexes
-                    .iter()
-                    .filter(|h| neighbor_coords.contains(&h.coordinate))
-                    .filter_map(|n| {
-                        if n.team != team {
-                            let neighbor_pixel_pos = HEX_LAYOUT.hex_to_world_pos(n.coordinate);
-                            return Some(Vec2::new(
-                                (neighbor_pixel_pos.x + hex_pixel_pos.x) / 2.,
-                                (neighbor_pixel_pos.y + hex_pixel_pos.y) / 2.,
-                            ));
-                        }
-
-                        None
-                    })
-                    .collect::<Vec<_>>(),
-            ]
-            .concat()
-        })
-        .collect::<Vec<_>>();
-    unsorted_points.dedup();
+        .filter_map(|h| Some((h.coordinate, h.resource?)))
+        .collect::<HashMap<_, _>>();
 
-    if unsorted_points.is_empty() {
-        return None;
+    if *drawn_resources == resources && icons.iter().len() == resources.len() {
+        return;
     }
 
-    let first = unsorted_points.remove(0);
-    let mut point_groups = vec![vec![first]];
-    let mut current_point = first;
+    for entity in &icons {
+        commands.entity(entity).despawn();
+    }
 
-    while !unsorted_points.is_empty() {
-        let mut points = Vec::new();
+    // below the unit, so both stay visible
+    for (coordinate, resource) in &resources {
+        let position = HEX_LAYOUT.hex_to_world_pos(*coordinate) - Vec2::Y * HEX_SIZE * 0.6;
+
+        commands
+            .spawn(SpriteBundle {
+                transform: Transform {
+                    translation: position.extend(1.5),
+                    scale: Vec3::splat(HEX_SIZE / 400.),
+                    ..default()
+                },
+                sprite: Sprite {
+                    color: resource.icon_color(),
+                    ..default()
+                },
+                texture: asset_server.load(resource.sprite_location()),
+                ..default()
+            })
+            .insert(ResourceIcon);
+    }
 
-        loop {
-            let mut distance = i32::MAX;
-            let mut index = 0;
+    *drawn_resources = resources;
+}
 
-            for (i, point) in unsorted_points.iter().enumerate() {
-                let dist = current_point.distance(*point) as i32;
-                if dist < distance {
-                    index = i;
-                    distance = dist;
-                }
-            }
+// borders are only rebuilt for
+// teams whose tiles changed
+pub fn draw_borders(
+    mut commands: Commands,
+    hexes: (Query<&HexTile>, Query<(), Changed<HexTile>>),
+    mut drawn_tiles: Local<HashMap<Team, HashSet<Hex>>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    colors: Res<HexColors>,
+    team_settings: Res<TeamSettings>,
+    borders: Query<(Entity, &Border)>,
+) {
+    let (hexes, changed_hexes) = hexes;
+    if !colors.is_changed() && changed_hexes.is_empty() {
+        return;
+    }
 
-            points.push(current_point);
-            current_point = unsorted_points.remove(index);
+    for team in team_settings.teams() {
+        let team_tiles = hexes
+            .iter()
+            .filter(|h| h.team == team)
+            .map(|h| h.coordinate)
+            .collect::<HashSet<_>>();
 
-            if unsorted_points.is_empty() {
-                points.push(current_point);
+        if !colors.is_changed() && drawn_tiles.get(&team) == Some(&team_tiles) {
+            continue;
+        }
+
+        for (entity, border) in &borders {
+            if border.0 == team {
+                commands.entity(entity).despawn();
             }
+        }
 
-            if distance >= HEX_SIZE as i32 || unsorted_points.is_empty() {
-                break;
+        let Some(team_colors) = colors.team(team) else {
+            continue;
+        };
+
+        // capitals show the emblem, so
+        // they don't rely on color alone
+        for capital in hexes
+            .iter()
+            .filter(|h| h.team == team && h.variant == TileVariant::Capital)
+        {
+            let translation = HEX_LAYOUT.hex_to_world_pos(capital.coordinate).extend(3.);
+            if let Some(emblem) =
+                emblem_bundle(team, HEX_SIZE / 3., translation, &mut meshes, &colors)
+            {
+                commands.spawn(emblem).insert((Border(team), Emblem(team)));
             }
         }
 
-        points.push(*points.first()?);
-        point_groups.push(points);
+        for region in connected_regions(&team_tiles) {
+            let outlines = region_outlines(&region);
+
+            commands
+                .spawn(MaterialMesh2dBundle {
+                    mesh: meshes.add(outline_mesh(&outlines, HEX_GAP * 2.)).into(),
+                    material: team_colors.border_color.clone(),
+                    transform: Transform::from_xyz(0., 0., 2.),
+                    ..default()
+                })
+                .insert(Border(team));
+        }
+
+        drawn_tiles.insert(team, team_tiles);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use hexx::hex;
+
+    use crate::{
+        buildings::components::BuildingKind,
+        testing::{TestBoard, FIRST},
+    };
+
+    use super::*;
+
+    fn indexed_unit(board: &TestBoard, at: Hex) -> Option<Entity> {
+        board.app.world.resource::<BoardIndex>().unit(at)
+    }
+
+    fn indexed_building(board: &TestBoard, at: Hex) -> Option<Entity> {
+        board.app.world.resource::<BoardIndex>().building(at)
+    }
+
+    // only the index sync, so nothing
+    // is despawned behind its back
+    fn sync_only(board: &mut TestBoard) {
+        let mut schedule = Schedule::new();
+        schedule.add_systems(sync_board_index);
+        schedule.run(&mut board.app.world);
+    }
+
+    #[test]
+    fn moves_and_nimble_kills_keep_the_index_in_step() {
+        let mut board = TestBoard::parse(
+            "
+            . B . .
+            . . s .
+            . . . S
+            ",
+        );
+        let dancer = indexed_unit(&board, hex(1, 0)).unwrap();
+        let scout = indexed_unit(&board, hex(3, 2)).unwrap();
+
+        board.move_unit(hex(3, 2), hex(3, 1));
+        assert_eq!(indexed_unit(&board, hex(3, 2)), None);
+        assert_eq!(indexed_unit(&board, hex(3, 1)), Some(scout));
+
+        board.attack(hex(1, 0), hex(2, 1));
+        assert_eq!(indexed_unit(&board, hex(1, 0)), None);
+        assert_eq!(indexed_unit(&board, hex(2, 1)), Some(dancer));
+
+        // the next frame's sync must not undo it
+        board.app.update();
+        assert_eq!(indexed_unit(&board, hex(2, 1)), Some(dancer));
     }
 
-    point_groups.remove(0);
-    Some(point_groups)
+    #[test]
+    fn dead_units_leave_the_index_before_they_are_despawned() {
+        let mut board = TestBoard::parse(". S s .");
+        let scout = indexed_unit(&board, hex(2, 0)).unwrap();
+
+        board.app.world.get_mut::<Unit>(scout).unwrap().health = 0;
+        sync_only(&mut board);
+
+        assert!(board.app.world.get_entity(scout).is_some());
+        assert_eq!(indexed_unit(&board, hex(2, 0)), None);
+
+        board.app.update();
+        assert!(board.app.world.get_entity(scout).is_none());
+        assert_eq!(indexed_unit(&board, hex(2, 0)), None);
+    }
+
+    #[test]
+    fn destroyed_and_despawned_buildings_leave_the_index() {
+        let mut board = TestBoard::parse(". . .");
+        let wall = board.app.world.spawn(Building::new(BuildingKind::Wall, hex(0, 0), FIRST)).id();
+        let tower = board
+            .app
+            .world
+            .spawn(Building::new(BuildingKind::Watchtower, hex(1, 0), FIRST))
+            .id();
+        board.app.update();
+        assert_eq!(indexed_building(&board, hex(0, 0)), Some(wall));
+        assert_eq!(indexed_building(&board, hex(1, 0)), Some(tower));
+
+        board.app.world.get_mut::<Building>(wall).unwrap().health = 0;
+        sync_only(&mut board);
+        assert_eq!(indexed_building(&board, hex(0, 0)), None);
+
+        board.app.world.despawn(tower);
+        board.app.update();
+        assert_eq!(indexed_building(&board, hex(1, 0)), None);
+        assert_eq!(indexed_building(&board, hex(0, 0)), None);
+    }
 }
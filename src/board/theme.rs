@@ -0,0 +1,114 @@
+use std::{fs, path::Path};
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::resources::MAX_TEAMS;
+
+pub const THEME_DIRECTORY: &str = "assets/themes";
+
+// every color is an rgb triple so
+// theme files stay easy to edit by hand
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Theme {
+    pub name: String,
+    pub clear: [u8; 3],
+    pub background: [u8; 3],
+    pub neutral: [u8; 3],
+    pub neutral_weak_highlight: [u8; 3],
+    pub neutral_strong_highlight: [u8; 3],
+    // indexed by team id, needs
+    // one entry for every team
+    pub teams: Vec<ThemeTeam>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ThemeTeam {
+    pub sprite: [u8; 3],
+    pub capital: [u8; 3],
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        let team = |sprite, capital| ThemeTeam { sprite, capital };
+
+        Theme {
+            name: "default".to_string(),
+            clear: [20, 20, 20],
+            background: [25, 25, 25],
+            neutral: [40, 40, 40],
+            neutral_weak_highlight: [60, 60, 60],
+            neutral_strong_highlight: [90, 90, 90],
+            teams: vec![
+                team([70, 130, 250], [70, 70, 200]),
+                team([250, 130, 70], [200, 70, 70]),
+                team([90, 210, 110], [50, 150, 70]),
+                team([200, 110, 240], [140, 70, 190]),
+                team([240, 210, 80], [180, 150, 40]),
+                team([80, 210, 210], [40, 150, 150]),
+            ],
+        }
+    }
+}
+
+pub fn color([r, g, b]: [u8; 3]) -> Color {
+    Color::rgb_u8(r, g, b)
+}
+
+// every valid theme file in the directory, sorted by
+// name. broken files are skipped with a warning so a
+// typo can't keep the game from starting
+pub fn load_themes(directory: impl AsRef<Path>) -> Vec<Theme> {
+    let Ok(entries) = fs::read_dir(directory) else {
+        return Vec::new();
+    };
+
+    let mut themes = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|extension| extension == "json"))
+        .filter_map(|path| {
+            let theme = fs::read_to_string(&path)
+                .map_err(|error| error.to_string())
+                .and_then(|text| {
+                    serde_json::from_str::<Theme>(&text).map_err(|error| error.to_string())
+                });
+
+            match theme {
+                Ok(theme) if theme.teams.len() >= MAX_TEAMS => Some(theme),
+                Ok(theme) => {
+                    warn!("theme {} needs colors for {MAX_TEAMS} teams", theme.name);
+                    None
+                }
+                Err(error) => {
+                    warn!("skipping theme {}: {error}", path.display());
+                    None
+                }
+            }
+        })
+        .collect::<Vec<_>>();
+
+    themes.sort_by(|a, b| a.name.cmp(&b.name));
+    themes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bundled_themes_load() {
+        let themes = load_themes(THEME_DIRECTORY);
+        let names = themes.iter().map(|theme| theme.name.as_str()).collect::<Vec<_>>();
+
+        assert_eq!(names, ["colorblind", "default", "high_contrast"]);
+    }
+
+    #[test]
+    fn default_theme_file_matches_builtin() {
+        let themes = load_themes(THEME_DIRECTORY);
+        let default = themes.iter().find(|theme| theme.name == "default");
+
+        assert_eq!(default, Some(&Theme::default()));
+    }
+}
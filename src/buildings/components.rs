@@ -0,0 +1,200 @@
+use bevy::prelude::*;
+use hexx::Hex;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    board::components::Team,
+    units::components::{armored_damage, Keyword, Target, Unit},
+};
+
+#[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum BuildingKind {
+    // units can be recruited on it
+    // like on the capital
+    Barracks,
+    // units on or next to it can attack
+    // anything in range of the tower
+    Watchtower,
+    // nothing can move onto it
+    Wall,
+    // pays coins every round
+    Market,
+}
+
+impl BuildingKind {
+    pub const ALL: [BuildingKind; 4] = [
+        BuildingKind::Barracks,
+        BuildingKind::Watchtower,
+        BuildingKind::Wall,
+        BuildingKind::Market,
+    ];
+
+    pub fn cost(&self) -> i32 {
+        match self {
+            BuildingKind::Barracks => 8,
+            BuildingKind::Watchtower => 6,
+            BuildingKind::Wall => 3,
+            BuildingKind::Market => 10,
+        }
+    }
+
+    pub fn max_health(&self) -> i32 {
+        match self {
+            BuildingKind::Barracks => 5,
+            BuildingKind::Watchtower => 3,
+            BuildingKind::Wall => 6,
+            BuildingKind::Market => 3,
+        }
+    }
+
+    pub fn keywords(&self) -> Vec<Keyword> {
+        match self {
+            BuildingKind::Wall => vec![Keyword::Armor(1)],
+            _ => Vec::new(),
+        }
+    }
+
+    pub fn income(&self) -> i32 {
+        match self {
+            BuildingKind::Market => 2,
+            _ => 0,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            BuildingKind::Barracks => "Barracks",
+            BuildingKind::Watchtower => "Watchtower",
+            BuildingKind::Wall => "Wall",
+            BuildingKind::Market => "Market",
+        }
+    }
+
+    pub fn sprite_location(&self) -> String {
+        match self {
+            BuildingKind::Barracks => "sprites/barracks.png".to_string(),
+            BuildingKind::Watchtower => "sprites/watchtower.png".to_string(),
+            BuildingKind::Wall => "sprites/wall.png".to_string(),
+            BuildingKind::Market => "sprites/market.png".to_string(),
+        }
+    }
+}
+
+#[derive(Component, Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct Building {
+    pub kind: BuildingKind,
+    pub position: Hex,
+    // buildings on neutral tiles do nothing
+    // until someone captures the tile
+    pub team: Team,
+    pub max_health: i32,
+    pub health: i32,
+    pub keywords: Vec<Keyword>,
+}
+
+impl Building {
+    pub fn new(kind: BuildingKind, position: Hex, team: Team) -> Self {
+        Building {
+            kind,
+            position,
+            team,
+            max_health: kind.max_health(),
+            health: kind.max_health(),
+            keywords: kind.keywords(),
+        }
+    }
+
+    // called when its tile changes hands, walls
+    // are torn down and everything else is taken over
+    pub fn follow_owner(&mut self, owner: Team) {
+        if self.team == owner {
+            return;
+        }
+
+        if self.kind == BuildingKind::Wall {
+            self.health = 0;
+        }
+
+        self.team = owner;
+    }
+
+    pub fn active(&self) -> bool {
+        self.team != Team::Neutral && self.health > 0
+    }
+}
+
+// the friendly watchtower a unit is on or next
+// to, the lowest one when there are several
+pub fn manned_watchtower<'a>(
+    unit: &Unit,
+    buildings: impl IntoIterator<Item = &'a Building>,
+) -> Option<Hex> {
+    buildings
+        .into_iter()
+        .filter(|b| b.kind == BuildingKind::Watchtower && b.team == unit.team && b.active())
+        .map(|b| b.position)
+        .filter(|at| at.unsigned_distance_to(unit.position) <= 1)
+        .min_by_key(|at| (at.x, at.y))
+}
+
+impl Target for Building {
+    fn position(&self) -> Hex {
+        self.position
+    }
+
+    fn health(&self) -> i32 {
+        self.health
+    }
+
+    fn damage(&self) -> i32 {
+        0
+    }
+
+    fn keywords(&self) -> &[Keyword] {
+        &self.keywords
+    }
+
+    fn take_damage(&mut self, damage: i32) -> bool {
+        self.health -= armored_damage(&self.keywords, damage);
+
+        self.health <= 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use hexx::hex;
+
+    use crate::units::components::{Action, Unit, UnitDefault};
+
+    use super::*;
+
+    #[test]
+    fn buildings_are_attacked_like_units() {
+        let mut attacker = Unit::new_default(UnitDefault::Knight, hex(0, 0), Team::Player(0));
+        attacker.actions = vec![Action::Attack];
+        let mut wall = Building::new(BuildingKind::Wall, hex(1, 0), Team::Player(1));
+
+        attacker.attack(&mut Transform::default(), &mut wall);
+
+        // the wall's armor takes one off the knight's two
+        assert_eq!(wall.health, 5);
+        assert_eq!(attacker.health, attacker.max_health);
+        assert!(!attacker.actions.contains(&Action::Attack));
+    }
+
+    #[test]
+    fn captured_buildings_change_owner() {
+        let mut market = Building::new(BuildingKind::Market, hex(0, 0), Team::Player(0));
+        let mut wall = Building::new(BuildingKind::Wall, hex(1, 0), Team::Player(0));
+
+        market.follow_owner(Team::Neutral);
+        assert!(!market.active());
+        market.follow_owner(Team::Player(1));
+        assert!(market.active());
+        assert_eq!(market.team, Team::Player(1));
+
+        wall.follow_owner(Team::Neutral);
+        assert_eq!(wall.health, 0);
+    }
+}
@@ -0,0 +1,45 @@
+use bevy::prelude::*;
+
+use crate::{
+    animation::systems::animations_finished,
+    command::CommandSet,
+    player::systems::player_turn,
+    state::{game_running, game_undecided, AppState},
+    util::despawn_all,
+};
+
+use self::{
+    components::Building,
+    systems::{
+        attach_building_sprites, build_on_hovered_tile, color_buildings,
+        despawn_destroyed_buildings,
+    },
+};
+
+pub mod components;
+pub mod systems;
+
+// how far around a watchtower its
+// garrison can attack
+pub const WATCHTOWER_RANGE: u32 = 2;
+
+pub struct BuildingsPlugin;
+
+impl Plugin for BuildingsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnExit(AppState::InGame), despawn_all::<Building>)
+            .add_systems(
+                Update,
+                (
+                    despawn_destroyed_buildings
+                        .after(CommandSet::Apply)
+                        .run_if(in_state(AppState::InGame)),
+                    build_on_hovered_tile
+                        .run_if(game_running.and_then(player_turn).and_then(animations_finished))
+                        .run_if(game_undecided)
+                        .in_set(CommandSet::Issue),
+                    (attach_building_sprites, color_buildings).run_if(game_running),
+                ),
+            );
+    }
+}
@@ -0,0 +1,89 @@
+use bevy::prelude::*;
+
+use crate::{
+    board::{resources::HexColors, HEX_LAYOUT, HEX_SIZE},
+    command::events::{CommandEvent, GameCommand},
+    util::BoardCursor,
+};
+
+use super::components::{Building, BuildingKind};
+
+// hover an owned tile and press one of these
+const BUILD_KEYS: [(KeyCode, BuildingKind); 4] = [
+    (KeyCode::B, BuildingKind::Barracks),
+    (KeyCode::V, BuildingKind::Watchtower),
+    (KeyCode::W, BuildingKind::Wall),
+    (KeyCode::M, BuildingKind::Market),
+];
+
+// sprites are attached by attach_building_sprites,
+// so buildings can also be spawned without assets
+pub fn spawn_building(commands: &mut Commands, building: Building) -> Entity {
+    commands
+        .spawn(Transform::from_translation(building_position(&building)))
+        .insert(building)
+        .id()
+}
+
+// above the unit, resource icons are below it
+fn building_position(building: &Building) -> Vec3 {
+    let position = HEX_LAYOUT.hex_to_world_pos(building.position) + Vec2::Y * HEX_SIZE * 0.6;
+    position.extend(1.5)
+}
+
+pub fn attach_building_sprites(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    buildings: Query<(Entity, &Building), Added<Building>>,
+) {
+    for (entity, building) in &buildings {
+        commands.entity(entity).insert(SpriteBundle {
+            transform: Transform {
+                translation: building_position(building),
+                scale: Vec3::splat(HEX_SIZE / 400.),
+                ..default()
+            },
+            texture: asset_server.load(building.kind.sprite_location()),
+            ..default()
+        });
+    }
+}
+
+pub fn color_buildings(mut buildings: Query<(&Building, &mut Sprite)>, colors: Res<HexColors>) {
+    for (building, mut sprite) in &mut buildings {
+        let color = colors
+            .team(building.team)
+            .map_or(Color::GRAY, |team_colors| team_colors.sprite);
+
+        if sprite.color != color {
+            sprite.color = color;
+        }
+    }
+}
+
+pub fn despawn_destroyed_buildings(mut commands: Commands, buildings: Query<(Entity, &Building)>) {
+    for (entity, building) in &buildings {
+        if building.health <= 0 {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+pub fn build_on_hovered_tile(
+    keys: Res<Input<KeyCode>>,
+    cursor: BoardCursor,
+    mut game_commands: EventWriter<CommandEvent>,
+) {
+    let Some((_, kind)) = BUILD_KEYS.iter().find(|(key, _)| keys.just_released(*key)) else {
+        return;
+    };
+
+    let Some(hovered_hex) = cursor.hex() else {
+        return;
+    };
+
+    game_commands.send(CommandEvent::local(GameCommand::Build {
+        at: hovered_hex,
+        kind: *kind,
+    }));
+}
@@ -0,0 +1,45 @@
+use bevy::prelude::*;
+
+use crate::{
+    command::CommandSet,
+    save::LoadedGame,
+    state::{game_running, game_undecided, AppState, GameSetupSet},
+};
+
+use self::{
+    resources::TurnClocks,
+    systems::{end_forfeited_games, enforce_time_limit, setup_clocks, tick_clocks},
+};
+
+pub mod resources;
+pub mod systems;
+
+// per turn time limits and chess clocks, a loaded
+// game keeps the clocks it was saved with
+pub struct ClockPlugin;
+
+impl Plugin for ClockPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<TurnClocks>()
+            .add_systems(
+                OnEnter(AppState::InGame),
+                setup_clocks
+                    .run_if(not(resource_exists::<LoadedGame>()))
+                    .in_set(GameSetupSet::Resources),
+            )
+            .add_systems(
+                Update,
+                (
+                    end_forfeited_games
+                        .after(CommandSet::Apply)
+                        .run_if(in_state(AppState::InGame)),
+                    (
+                        enforce_time_limit.in_set(CommandSet::Issue),
+                        tick_clocks.after(CommandSet::Apply),
+                    )
+                        .run_if(game_running),
+                )
+                    .run_if(game_undecided),
+            );
+    }
+}
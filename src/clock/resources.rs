@@ -0,0 +1,96 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{board::components::Team, state::TimeControl};
+
+// the time used so far, kept in save files but not in
+// snapshots, every peer measures it on its own
+#[derive(Resource, Serialize, Deserialize, Clone, Debug, PartialEq, Default)]
+pub struct TurnClocks {
+    // what's left on each team's chess clock, by team id
+    pub remaining: Vec<f32>,
+    // (TurnCounter, active team index) the elapsed time belongs to
+    pub turn: (i32, usize),
+    // used by the active team this turn
+    pub elapsed: f32,
+}
+
+impl TurnClocks {
+    pub fn new(control: TimeControl, teams: usize) -> Self {
+        let remaining = match control {
+            TimeControl::ChessClock { total, .. } => vec![total as f32; teams],
+            _ => Vec::new(),
+        };
+
+        TurnClocks {
+            remaining,
+            turn: (0, 0),
+            elapsed: 0.,
+        }
+    }
+
+    // seconds the team has left, counting this turn's
+    // when it's the one playing, None without a limit
+    pub fn left(&self, control: TimeControl, team: Team, playing: bool) -> Option<f32> {
+        let left = match control {
+            TimeControl::Unlimited => return None,
+            TimeControl::PerTurn { seconds } => seconds as f32,
+            TimeControl::ChessClock { .. } => *team.id().and_then(|id| self.remaining.get(id))?,
+        };
+
+        match playing {
+            true => Some((left - self.elapsed).max(0.)),
+            false => Some(left),
+        }
+    }
+
+    // charges the turn that just ended to its team
+    pub fn end_turn(&mut self, control: TimeControl, team: Team, next: (i32, usize)) {
+        if let TimeControl::ChessClock { increment, .. } = control {
+            if let Some(remaining) = team.id().and_then(|id| self.remaining.get_mut(id)) {
+                *remaining = (*remaining - self.elapsed).max(0.) + increment as f32;
+            }
+        }
+
+        self.turn = next;
+        self.elapsed = 0.;
+    }
+}
+
+// minutes and seconds, rounded up so 0:00 means out of time
+pub fn format_clock(seconds: f32) -> String {
+    let seconds = seconds.ceil() as i32;
+    format!("{}:{:02}", seconds / 60, seconds % 60)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn chess_clocks_keep_what_is_left_plus_the_increment() {
+        let control = TimeControl::ChessClock {
+            total: 60,
+            increment: 5,
+        };
+        let mut clocks = TurnClocks::new(control, 2);
+
+        clocks.elapsed = 20.;
+        assert_eq!(clocks.left(control, Team::Player(0), true), Some(40.));
+        assert_eq!(clocks.left(control, Team::Player(1), false), Some(60.));
+        clocks.end_turn(control, Team::Player(0), (0, 1));
+        assert_eq!(clocks.left(control, Team::Player(0), false), Some(45.));
+
+        clocks.elapsed = 90.;
+        assert_eq!(clocks.left(control, Team::Player(1), true), Some(0.));
+
+        let per_turn = TimeControl::PerTurn { seconds: 30 };
+        clocks.end_turn(per_turn, Team::Player(1), (1, 0));
+        assert_eq!(clocks.left(per_turn, Team::Player(0), true), Some(30.));
+        assert_eq!(
+            clocks.left(TimeControl::Unlimited, Team::Player(0), true),
+            None
+        );
+        assert_eq!(format_clock(65.2), "1:06");
+    }
+}
@@ -0,0 +1,91 @@
+use bevy::prelude::*;
+
+use crate::{
+    board::resources::{Controller, TeamSettings},
+    command::events::{CommandEvent, GameCommand},
+    player::resources::{PlayerTeam, TurnCounter, TurnOrder},
+    scenario::objectives::ScenarioOutcome,
+    state::{MatchSettings, TimeControl},
+};
+
+use super::resources::TurnClocks;
+
+pub fn setup_clocks(
+    mut commands: Commands,
+    match_settings: Res<MatchSettings>,
+    team_settings: Res<TeamSettings>,
+) {
+    commands.insert_resource(TurnClocks::new(
+        match_settings.time_control,
+        team_settings.teams.len(),
+    ));
+}
+
+// stopped while paused, the time of a turn is charged
+// to its team once the next one has started
+pub fn tick_clocks(
+    time: Res<Time>,
+    match_settings: Res<MatchSettings>,
+    turn: (Res<TurnCounter>, Res<TurnOrder>),
+    mut clocks: ResMut<TurnClocks>,
+) {
+    let (turn_counter, turn_order) = turn;
+    let control = match_settings.time_control;
+
+    let current = (turn_counter.0, turn_order.current);
+    if clocks.turn != current {
+        match turn_order.teams.get(clocks.turn.1) {
+            Some(team) => clocks.end_turn(control, *team, current),
+            None => *clocks = TurnClocks::new(control, turn_order.teams.len()),
+        }
+    }
+
+    if control != TimeControl::Unlimited {
+        clocks.elapsed += time.delta_seconds();
+    }
+}
+
+// only the machine playing the team acts on its time, the
+// command reaches the other peers like any other
+pub fn enforce_time_limit(
+    match_settings: Res<MatchSettings>,
+    clocks: Res<TurnClocks>,
+    teams: (Res<TurnOrder>, Res<TeamSettings>),
+    mut timed_out: Local<Option<(i32, usize)>>,
+    mut game_commands: EventWriter<CommandEvent>,
+) {
+    let (turn_order, team_settings) = teams;
+    let team = turn_order.active();
+
+    if clocks.left(match_settings.time_control, team, true) != Some(0.)
+        || team_settings.controller(team) != Some(Controller::Local)
+        || *timed_out == Some(clocks.turn)
+    {
+        return;
+    }
+
+    *timed_out = Some(clocks.turn);
+    game_commands.send(CommandEvent::local(match match_settings.forfeit_on_time {
+        true => GameCommand::Forfeit,
+        false => GameCommand::EndTurn,
+    }));
+}
+
+// the match is over once every other team gave up, won
+// for the team left and lost for the ones that forfeited,
+// a loaded game that had ended ends again
+pub fn end_forfeited_games(
+    mut commands: Commands,
+    turn_order: Res<TurnOrder>,
+    player_team: Res<PlayerTeam>,
+) {
+    let standing = turn_order.standing();
+    if turn_order.forfeited.is_empty() || standing.len() > 1 {
+        return;
+    }
+
+    commands.insert_resource(match standing.contains(&player_team.0) {
+        true => ScenarioOutcome::Victory,
+        false => ScenarioOutcome::Defeat,
+    });
+}
@@ -0,0 +1,83 @@
+use bevy::prelude::*;
+use hexx::Hex;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    board::components::Team,
+    buildings::components::BuildingKind,
+    units::components::{Keyword, Promotion, UnitDefault},
+};
+
+use super::rules::Strike;
+
+// everything that changes the game
+// state goes through one of these, so
+// two peers applying the same commands
+// in the same order stay in sync
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum GameCommand {
+    PlaceCapital { hex: Hex },
+    Move { from: Hex, to: Hex },
+    Attack { from: Hex, target: Hex },
+    Build { at: Hex, kind: BuildingKind },
+    Recruit { at: Hex, kind: UnitDefault },
+    Promote { at: Hex, promotion: Promotion },
+    EndTurn,
+    // the active team gives up, like when its time runs out
+    Forfeit,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum CommandSource {
+    Local,
+    Remote,
+}
+
+#[derive(Event, Clone, Copy, Debug)]
+pub struct CommandEvent {
+    pub command: GameCommand,
+    pub source: CommandSource,
+}
+
+impl CommandEvent {
+    pub fn local(command: GameCommand) -> Self {
+        CommandEvent {
+            command,
+            source: CommandSource::Local,
+        }
+    }
+
+    pub fn remote(command: GameCommand) -> Self {
+        CommandEvent {
+            command,
+            source: CommandSource::Remote,
+        }
+    }
+}
+
+// what applying the commands did, for
+// everything showing the game to the player
+#[derive(Event, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct GameEvent {
+    pub turn: i32,
+    // the team that acted, or the one it happened to
+    pub team: Team,
+    pub kind: GameEventKind,
+}
+
+// the damage of an attack is what the target lost,
+// entities are only meaningful on this machine
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum GameEventKind {
+    Moved { unit: Entity, from: Hex, to: Hex },
+    Attacked { attacker: Entity, from: Hex, target: Hex, strike: Strike, damage: i32 },
+    Triggered { at: Hex, keyword: Keyword },
+    Died { unit: Entity, at: Hex, kind: UnitDefault },
+    Destroyed { at: Hex, kind: BuildingKind },
+    Recruited { at: Hex, kind: UnitDefault },
+    Built { at: Hex, kind: BuildingKind },
+    Captured { at: Hex },
+    CapitalLost { at: Hex },
+    Income { coins: i32 },
+    Forfeited,
+}
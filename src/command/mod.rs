@@ -0,0 +1,63 @@
+use bevy::prelude::*;
+
+use crate::{
+    board::{resources::BoardIndex, systems::sync_board_index},
+    state::{AppState, GameSetupSet},
+};
+
+use self::{
+    events::{CommandEvent, GameEvent},
+    rules::{CaptureRules, CombatRng, CombatRules},
+    systems::{apply_game_commands, setup_combat},
+};
+
+pub mod events;
+pub mod rules;
+pub mod systems;
+
+#[derive(SystemSet, Debug, Hash, PartialEq, Eq, Clone)]
+pub enum CommandSet {
+    // systems turning input (local or
+    // remote) into CommandEvents
+    Issue,
+    // the only place CommandEvents
+    // change the game state
+    Apply,
+}
+
+pub struct CommandPlugin;
+
+impl Plugin for CommandPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<CommandEvent>()
+            .add_event::<GameEvent>()
+            .init_resource::<BoardIndex>()
+            .init_resource::<CaptureRules>()
+            .init_resource::<CombatRules>()
+            .init_resource::<CombatRng>()
+            .add_systems(
+                OnEnter(AppState::InGame),
+                setup_combat.in_set(GameSetupSet::Resources),
+            )
+            // not paused with the rest of the game,
+            // a peer keeps playing while we are
+            .configure_sets(
+                Update,
+                (
+                    CommandSet::Issue.run_if(in_state(AppState::InGame)),
+                    CommandSet::Apply.run_if(in_state(AppState::InGame)),
+                )
+                    .chain(),
+            )
+            .add_systems(
+                Update,
+                (
+                    sync_board_index
+                        .after(CommandSet::Issue)
+                        .before(CommandSet::Apply)
+                        .run_if(in_state(AppState::InGame)),
+                    apply_game_commands.in_set(CommandSet::Apply),
+                ),
+            );
+    }
+}
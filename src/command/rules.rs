@@ -0,0 +1,456 @@
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+use hexx::Hex;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    board::components::{HexTile, Team, TileVariant},
+    units::components::{armored_damage, Keyword},
+};
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ContestedRule {
+    // capturing goes on as if nobody defended
+    Ignore,
+    // nothing changes while a defender is next to the tile
+    Freeze,
+}
+
+// how tiles change hands at the end of every round
+#[derive(Resource, Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct CaptureRules {
+    // the progress a tile needs, an owned
+    // tile sits at it and has to be worn
+    // down to zero before it turns neutral
+    pub land_threshold: i32,
+    pub capital_threshold: i32,
+    // progress a capturing unit makes per round
+    pub capture_step: i32,
+    // every other unit of the capturing team
+    // next to the tile adds another step
+    pub stacking: bool,
+    // a tile is contested when a unit of another
+    // team stands next to it
+    pub contested: ContestedRule,
+    // capturing needs a tile of your own next to it
+    pub require_adjacent_territory: bool,
+    // how fast an unoccupied neutral tile
+    // loses progress and an unoccupied owned
+    // tile recovers it
+    pub neutral_decay: i32,
+    pub owned_recovery: i32,
+}
+
+impl Default for CaptureRules {
+    fn default() -> Self {
+        CaptureRules {
+            land_threshold: 3,
+            capital_threshold: 5,
+            capture_step: 1,
+            stacking: false,
+            contested: ContestedRule::Ignore,
+            require_adjacent_territory: true,
+            neutral_decay: 1,
+            owned_recovery: 1,
+        }
+    }
+}
+
+impl CaptureRules {
+    pub fn threshold(&self, variant: TileVariant) -> i32 {
+        match variant {
+            TileVariant::Land => self.land_threshold,
+            TileVariant::Capital => self.capital_threshold,
+        }
+    }
+}
+
+// how an attack lands, the damage is before armor
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Strike {
+    // too far away to hit
+    Miss,
+    Dodged,
+    Hit(i32),
+    Critical(i32),
+}
+
+impl Strike {
+    pub fn damage(&self) -> i32 {
+        match self {
+            Strike::Miss | Strike::Dodged => 0,
+            Strike::Hit(damage) | Strike::Critical(damage) => *damage,
+        }
+    }
+}
+
+// dice for attacks, off unless the match turns them
+// on. strike backs always hit exactly
+#[derive(Resource, Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct CombatRules {
+    pub random: bool,
+    // damage is rolled between damage - variance
+    // and damage + variance, chances are in percent
+    pub damage_variance: i32,
+    pub critical_chance: u32,
+    pub critical_multiplier: i32,
+    pub dodge_chance: u32,
+    // for every hex past the first
+    pub range_miss_chance: u32,
+}
+
+impl Default for CombatRules {
+    fn default() -> Self {
+        CombatRules {
+            random: false,
+            damage_variance: 1,
+            critical_chance: 10,
+            critical_multiplier: 2,
+            dodge_chance: 10,
+            range_miss_chance: 10,
+        }
+    }
+}
+
+// the state of the seeded rng combat rolls with, part
+// of the game state so every peer and every reload
+// rolls the same numbers
+#[derive(Resource, Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub struct CombatRng(pub u64);
+
+impl CombatRng {
+    fn roll<T>(&mut self, roll: impl FnOnce(&mut fastrand::Rng) -> T) -> T {
+        let mut rng = fastrand::Rng::with_seed(self.0);
+        let value = roll(&mut rng);
+        self.0 = rng.get_seed();
+        value
+    }
+
+    fn chance(&mut self, percent: u32) -> bool {
+        self.roll(|rng| rng.u32(0..100)) < percent
+    }
+}
+
+impl CombatRules {
+    fn miss_chance(&self, distance: u32) -> u32 {
+        (self.range_miss_chance * distance.saturating_sub(1)).min(100)
+    }
+
+    // only rolls when combat is random, so a fixed
+    // game never touches the rng
+    pub fn strike(&self, rng: &mut CombatRng, damage: i32, distance: u32) -> Strike {
+        if !self.random {
+            return Strike::Hit(damage);
+        }
+
+        if rng.chance(self.miss_chance(distance)) {
+            return Strike::Miss;
+        }
+        if rng.chance(self.dodge_chance) {
+            return Strike::Dodged;
+        }
+
+        let variance = self.damage_variance;
+        let damage = rng
+            .roll(|rng| rng.i32(damage - variance..=damage + variance))
+            .max(0);
+        if rng.chance(self.critical_chance) {
+            Strike::Critical(damage * self.critical_multiplier)
+        } else {
+            Strike::Hit(damage)
+        }
+    }
+
+    // (chance to hit, chance of a critical, average damage
+    // after the target's armor) of an attack
+    pub fn expected(&self, damage: i32, keywords: &[Keyword], distance: u32) -> (f32, f32, f32) {
+        if !self.random {
+            return (1., 0., armored_damage(keywords, damage) as f32);
+        }
+
+        let hit = (1. - self.miss_chance(distance) as f32 / 100.)
+            * (1. - self.dodge_chance as f32 / 100.);
+        let critical = self.critical_chance as f32 / 100.;
+
+        let rolls = (damage - self.damage_variance..=damage + self.damage_variance)
+            .map(|rolled| rolled.max(0))
+            .collect::<Vec<_>>();
+        let average = |multiplier: i32| {
+            rolls
+                .iter()
+                .map(|rolled| armored_damage(keywords, rolled * multiplier) as f32)
+                .sum::<f32>()
+                / rolls.len() as f32
+        };
+
+        let damage =
+            hit * ((1. - critical) * average(1) + critical * average(self.critical_multiplier));
+        (hit, hit * critical, damage)
+    }
+}
+
+// every tile after a round of capturing, units
+// are given as (position, team) of the living ones
+pub fn advance_capture(
+    rules: &CaptureRules,
+    tiles: &[HexTile],
+    units: &[(Hex, Team)],
+) -> Vec<HexTile> {
+    let owners = tiles
+        .iter()
+        .map(|tile| (tile.coordinate, tile.team))
+        .collect::<HashMap<_, _>>();
+
+    let occupants = units.iter().copied().collect::<HashMap<_, _>>();
+
+    tiles
+        .iter()
+        .map(|tile| {
+            let mut tile = tile.clone();
+            let threshold = rules.threshold(tile.variant);
+            let neighbors = tile.coordinate.all_neighbors();
+
+            let capturer = occupants
+                .get(&tile.coordinate)
+                .copied()
+                .filter(|team| *team != tile.team)
+                .filter(|team| {
+                    !rules.require_adjacent_territory
+                        || neighbors.iter().any(|n| owners.get(n) == Some(team))
+                });
+
+            let Some(capturer) = capturer else {
+                if tile.team == Team::Neutral {
+                    move_toward(&mut tile.capture_progress, 0, rules.neutral_decay);
+                } else {
+                    move_toward(&mut tile.capture_progress, threshold, rules.owned_recovery);
+                }
+                return tile;
+            };
+
+            let neighbor_teams = neighbors
+                .iter()
+                .filter_map(|n| occupants.get(n))
+                .collect::<Vec<_>>();
+
+            if rules.contested == ContestedRule::Freeze
+                && neighbor_teams.iter().any(|team| **team != capturer)
+            {
+                return tile;
+            }
+
+            let helpers = if rules.stacking {
+                neighbor_teams
+                    .iter()
+                    .filter(|team| ***team == capturer)
+                    .count() as i32
+            } else {
+                0
+            };
+            let step = rules.capture_step * (1 + helpers);
+
+            if tile.team == Team::Neutral {
+                if move_toward(&mut tile.capture_progress, threshold, step) {
+                    tile.team = capturer;
+                }
+            } else if move_toward(&mut tile.capture_progress, 0, step) {
+                tile.team = Team::Neutral;
+            }
+
+            tile
+        })
+        .collect()
+}
+
+fn move_toward(value: &mut i32, target: i32, step: i32) -> bool {
+    *value = (*value - step).max((*value + step).min(target));
+    *value == target
+}
+
+#[cfg(test)]
+mod tests {
+    use hexx::hex;
+
+    use super::*;
+
+    const RED: Team = Team::Player(0);
+    const BLUE: Team = Team::Player(1);
+
+    fn tile(x: i32, y: i32, team: Team, capture_progress: i32) -> HexTile {
+        HexTile {
+            coordinate: hex(x, y),
+            variant: TileVariant::Land,
+            capture_progress,
+            team,
+            resource: None,
+        }
+    }
+
+    // a neutral target at the origin with a red tile
+    // and a blue tile next to it
+    fn board() -> Vec<HexTile> {
+        vec![
+            tile(0, 0, Team::Neutral, 0),
+            tile(1, 0, RED, 3),
+            tile(-1, 0, BLUE, 3),
+        ]
+    }
+
+    fn rounds(
+        rules: &CaptureRules,
+        tiles: &[HexTile],
+        units: &[(Hex, Team)],
+        count: usize,
+    ) -> HexTile {
+        let mut tiles = tiles.to_vec();
+        for _ in 0..count {
+            tiles = advance_capture(rules, &tiles, units);
+        }
+        tiles[0].clone()
+    }
+
+    #[test]
+    fn land_is_captured_at_its_threshold() {
+        let rules = CaptureRules::default();
+        let units = [(hex(0, 0), RED)];
+
+        assert_eq!(rounds(&rules, &board(), &units, 2).team, Team::Neutral);
+        assert_eq!(rounds(&rules, &board(), &units, 3).team, RED);
+    }
+
+    #[test]
+    fn capitals_take_longer_to_capture() {
+        let rules = CaptureRules::default();
+        let mut tiles = board();
+        tiles[0].variant = TileVariant::Capital;
+        let units = [(hex(0, 0), RED)];
+
+        assert_eq!(rounds(&rules, &tiles, &units, 4).team, Team::Neutral);
+        assert_eq!(rounds(&rules, &tiles, &units, 5).team, RED);
+    }
+
+    #[test]
+    fn owned_tiles_are_worn_down_to_neutral() {
+        let rules = CaptureRules::default();
+        let tiles = [tile(0, 0, BLUE, 3), tile(1, 0, RED, 3)];
+        let units = [(hex(0, 0), RED)];
+
+        let captured = rounds(&rules, &tiles, &units, 3);
+        assert_eq!(
+            (captured.team, captured.capture_progress),
+            (Team::Neutral, 0)
+        );
+    }
+
+    #[test]
+    fn capturing_needs_adjacent_territory_unless_disabled() {
+        let tiles = [tile(0, 0, Team::Neutral, 0)];
+        let units = [(hex(0, 0), RED)];
+
+        let rules = CaptureRules::default();
+        assert_eq!(rounds(&rules, &tiles, &units, 3).capture_progress, 0);
+
+        let rules = CaptureRules {
+            require_adjacent_territory: false,
+            ..default()
+        };
+        assert_eq!(rounds(&rules, &tiles, &units, 3).team, RED);
+    }
+
+    #[test]
+    fn stacking_units_speed_up_capture() {
+        let units = [(hex(0, 0), RED), (hex(0, 1), RED), (hex(1, -1), RED)];
+
+        let rules = CaptureRules::default();
+        assert_eq!(rounds(&rules, &board(), &units, 1).capture_progress, 1);
+
+        let rules = CaptureRules {
+            stacking: true,
+            ..default()
+        };
+        assert_eq!(rounds(&rules, &board(), &units, 1).team, RED);
+    }
+
+    #[test]
+    fn contested_tiles_freeze_when_configured() {
+        let units = [(hex(0, 0), RED), (hex(-1, 1), BLUE)];
+
+        let rules = CaptureRules::default();
+        assert_eq!(rounds(&rules, &board(), &units, 1).capture_progress, 1);
+
+        let rules = CaptureRules {
+            contested: ContestedRule::Freeze,
+            ..default()
+        };
+        assert_eq!(rounds(&rules, &board(), &units, 2).capture_progress, 0);
+    }
+
+    #[test]
+    fn neutral_progress_decays() {
+        let rules = CaptureRules {
+            neutral_decay: 2,
+            ..default()
+        };
+        let tiles = [tile(0, 0, Team::Neutral, 3)];
+
+        assert_eq!(rounds(&rules, &tiles, &[], 1).capture_progress, 1);
+        assert_eq!(rounds(&rules, &tiles, &[], 2).capture_progress, 0);
+    }
+
+    #[test]
+    fn fixed_combat_never_rolls() {
+        let rules = CombatRules::default();
+        let mut rng = CombatRng(7);
+
+        assert_eq!(rules.strike(&mut rng, 3, 5), Strike::Hit(3));
+        assert_eq!(rng, CombatRng(7));
+        assert_eq!(rules.expected(3, &[Keyword::Armor(1)], 1), (1., 0., 2.));
+    }
+
+    #[test]
+    fn random_combat_repeats_from_the_same_seed() {
+        let rules = CombatRules {
+            random: true,
+            ..default()
+        };
+        let strikes = |seed| {
+            let mut rng = CombatRng(seed);
+            (0..50)
+                .map(|_| rules.strike(&mut rng, 2, 2))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(strikes(1), strikes(1));
+        assert_ne!(strikes(1), strikes(2));
+
+        let strikes = strikes(1);
+        assert!(strikes.contains(&Strike::Miss));
+        assert!(strikes.contains(&Strike::Dodged));
+        assert!(strikes
+            .iter()
+            .all(|strike| (0..=6).contains(&strike.damage())));
+
+        // 10% range miss, then 10% dodge, crits double the 1..=3 roll
+        let (hit, critical, damage) = rules.expected(2, &[], 2);
+        assert!((hit - 0.81).abs() < 1e-5);
+        assert!((critical - 0.081).abs() < 1e-5);
+        assert!((damage - 0.81 * (0.9 * 2. + 0.1 * 4.)).abs() < 1e-5);
+    }
+
+    #[test]
+    fn owned_tiles_recover_up_to_their_threshold() {
+        let rules = CaptureRules {
+            owned_recovery: 2,
+            ..default()
+        };
+        let mut capital = tile(0, 0, RED, 0);
+        capital.variant = TileVariant::Capital;
+
+        assert_eq!(
+            rounds(&rules, &[capital.clone()], &[], 2).capture_progress,
+            4
+        );
+        assert_eq!(rounds(&rules, &[capital], &[], 3).capture_progress, 5);
+    }
+}
@@ -0,0 +1,805 @@
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+use hexx::Hex;
+
+use crate::{
+    board::{
+        components::{HexTile, Team, TileVariant},
+        resources::BoardIndex,
+        HEX_LAYOUT,
+    },
+    buildings::{
+        components::{manned_watchtower, Building, BuildingKind},
+        systems::spawn_building,
+    },
+    player::{
+        income::IncomeBreakdown,
+        resources::{TeamCoins, TurnCounter, TurnOrder},
+    },
+    state::MatchSettings,
+    units::{
+        components::{Action, Unit},
+        systems::spawn_unit,
+    },
+};
+
+use super::{
+    events::{CommandEvent, GameCommand, GameEvent, GameEventKind},
+    rules::{advance_capture, CaptureRules, CombatRng, CombatRules},
+};
+
+// the seed makes random combat repeat on every peer
+pub fn setup_combat(
+    mut commands: Commands,
+    match_settings: Res<MatchSettings>,
+    mut combat_rules: ResMut<CombatRules>,
+) {
+    combat_rules.random = match_settings.random_combat;
+    commands.insert_resource(CombatRng(match_settings.seed));
+}
+
+pub fn apply_game_commands(
+    mut commands: Commands,
+    mut game_commands: EventReader<CommandEvent>,
+    turn: (ResMut<TurnOrder>, ResMut<TurnCounter>, ResMut<TeamCoins>),
+    rules: (Res<CaptureRules>, Res<CombatRules>, ResMut<CombatRng>),
+    board: (ResMut<BoardIndex>, Query<&mut HexTile>, Query<&mut Building>),
+    mut units: Query<(&mut Unit, &mut Transform)>,
+    mut game_events: EventWriter<GameEvent>,
+) {
+    let (mut turn_order, mut turn_counter, mut team_coins) = turn;
+    let (capture_rules, combat_rules, mut combat_rng) = rules;
+    let (mut board_index, mut hexes, mut buildings) = board;
+
+    for event in game_commands.iter() {
+        let team = turn_order.active();
+
+        let happened = match event.command {
+            GameCommand::PlaceCapital { hex } => {
+                place_capital(&mut hexes, &board_index, team, hex);
+                Vec::new()
+            }
+            GameCommand::Move { from, to } => {
+                move_unit(&mut units, &buildings, &mut board_index, team, from, to)
+            }
+            GameCommand::Attack { from, target } => {
+                let dice = (combat_rules.as_ref(), combat_rng.as_mut());
+                if board_index.unit(target).is_some() {
+                    attack_unit(&mut units, &mut board_index, dice, team, from, target)
+                } else {
+                    let board = (&mut buildings, board_index.as_mut());
+                    attack_building(&mut units, board, dice, team, from, target)
+                }
+            }
+            GameCommand::Build { at, kind } => {
+                if !can_build(&hexes, &board_index, team, at, kind)
+                    || !team_coins.spend(team, kind.cost())
+                {
+                    continue;
+                }
+
+                // the entity only exists once commands are
+                // applied, but the tile is taken right away
+                let entity = spawn_building(&mut commands, Building::new(kind, at, team));
+                board_index.place_building(entity, at);
+                vec![(team, GameEventKind::Built { at, kind })]
+            }
+            GameCommand::Recruit { at, kind } => {
+                if !can_recruit(&hexes, &buildings, &board_index, team, at)
+                    || !team_coins.spend(team, kind.cost())
+                {
+                    continue;
+                }
+
+                let entity = spawn_unit(&mut commands, Unit::new_default(kind, at, team));
+                board_index.place_unit(entity, at);
+                vec![(team, GameEventKind::Recruited { at, kind })]
+            }
+            GameCommand::Promote { at, promotion } => {
+                let Some((mut unit, _transform)) =
+                    board_index.unit(at).and_then(|entity| units.get_mut(entity).ok())
+                else {
+                    continue;
+                };
+
+                if unit.team == team {
+                    unit.promote(promotion);
+                }
+                Vec::new()
+            }
+            GameCommand::EndTurn | GameCommand::Forfeit => {
+                // the team is out, its turn ends for good
+                if event.command == GameCommand::Forfeit {
+                    turn_order.forfeit(team);
+                    game_events.send(GameEvent {
+                        turn: turn_counter.0,
+                        team,
+                        kind: GameEventKind::Forfeited,
+                    });
+                }
+
+                if !turn_order.advance() {
+                    continue;
+                }
+
+                let mut happened = Vec::new();
+                for team in &turn_order.standing() {
+                    let income = IncomeBreakdown::for_team(*team, hexes.iter(), buildings.iter());
+                    team_coins.add(*team, income.total());
+                    happened.push((*team, GameEventKind::Income { coins: income.total() }));
+                }
+
+                for (mut unit, _transform) in &mut units {
+                    unit.new_turn();
+
+                    let healing = board_index
+                        .tile(unit.position)
+                        .and_then(|entity| hexes.get(entity).ok())
+                        .filter(|tile| tile.team == unit.team)
+                        .and_then(|tile| tile.resource)
+                        .map_or(0, |resource| resource.healing());
+                    unit.heal(healing);
+                }
+
+                turn_counter.0 += 1;
+
+                happened.extend(update_capture_progress(&mut hexes, &units, &capture_rules));
+                happened.extend(update_building_owners(&hexes, &mut buildings, &mut board_index));
+                happened
+            }
+        };
+
+        man_watchtowers(&mut units, &buildings);
+
+        game_events.send_batch(happened.into_iter().map(|(team, kind)| GameEvent {
+            turn: turn_counter.0,
+            team,
+            kind,
+        }));
+    }
+}
+
+fn place_capital(
+    hexes: &mut Query<&mut HexTile>,
+    board_index: &BoardIndex,
+    team: Team,
+    capital: Hex,
+) {
+    if hexes
+        .iter()
+        .any(|hex| hex.team == team && hex.variant == TileVariant::Capital)
+    {
+        return;
+    }
+
+    if !board_index.contains(capital) {
+        return;
+    }
+
+    let mut claim_tiles = capital.all_neighbors().to_vec();
+    claim_tiles.push(capital);
+
+    let claim_entities = claim_tiles
+        .iter()
+        .filter_map(|hex| board_index.tile(*hex))
+        .collect::<Vec<_>>();
+
+    if hexes
+        .iter_many(&claim_entities)
+        .any(|hex| hex.team != Team::Neutral && hex.team != team)
+    {
+        return;
+    }
+
+    let mut claimed = hexes.iter_many_mut(&claim_entities);
+    while let Some(mut hex) = claimed.fetch_next() {
+        hex.team = team;
+
+        if hex.coordinate == capital {
+            hex.variant = TileVariant::Capital;
+        }
+    }
+}
+
+fn move_unit(
+    units: &mut Query<(&mut Unit, &mut Transform)>,
+    buildings: &Query<&mut Building>,
+    board_index: &mut BoardIndex,
+    team: Team,
+    from: Hex,
+    to: Hex,
+) -> Vec<(Team, GameEventKind)> {
+    if !board_index.contains(to) || board_index.unit(to).is_some() {
+        return Vec::new();
+    }
+
+    if board_index
+        .building(to)
+        .and_then(|entity| buildings.get(entity).ok())
+        .is_some_and(|building| building.kind == BuildingKind::Wall)
+    {
+        return Vec::new();
+    }
+
+    let Some(entity) = board_index.unit(from) else {
+        return Vec::new();
+    };
+
+    let Ok((mut unit, mut unit_transform)) = units.get_mut(entity) else {
+        return Vec::new();
+    };
+
+    if unit.team != team
+        || !unit.relative_move_hexes().contains(&to)
+        || !unit.actions.contains(&Action::Move)
+    {
+        return Vec::new();
+    }
+
+    unit_transform.translation = HEX_LAYOUT.hex_to_world_pos(to).extend(1.);
+    unit.position = to;
+    unit.remove_action(Action::Move);
+
+    board_index.place_unit(entity, to);
+    vec![(team, GameEventKind::Moved { unit: entity, from, to })]
+}
+
+fn attack_unit(
+    units: &mut Query<(&mut Unit, &mut Transform)>,
+    board_index: &mut BoardIndex,
+    (combat_rules, combat_rng): (&CombatRules, &mut CombatRng),
+    team: Team,
+    from: Hex,
+    target: Hex,
+) -> Vec<(Team, GameEventKind)> {
+    let (Some(attacker_entity), Some(defender_entity)) =
+        (board_index.unit(from), board_index.unit(target))
+    else {
+        return Vec::new();
+    };
+
+    let Ok([(mut attacker_unit, mut attacker_transform), (mut defender_unit, _defender_transform)]) =
+        units.get_many_mut([attacker_entity, defender_entity])
+    else {
+        return Vec::new();
+    };
+
+    if attacker_unit.team != team
+        || defender_unit.team == team
+        || !attacker_unit.relative_attack_hexes().contains(&target)
+        || !attacker_unit.actions.contains(&Action::Attack)
+    {
+        return Vec::new();
+    }
+
+    let distance = from.unsigned_distance_to(target);
+    let strike = combat_rules.strike(combat_rng, attacker_unit.damage, distance);
+    let health_before = defender_unit.health;
+    let triggered =
+        attacker_unit.attack_with(&mut attacker_transform, defender_unit.as_mut(), strike.damage());
+
+    let damage = health_before - defender_unit.health;
+    let mut happened = vec![(
+        team,
+        GameEventKind::Attacked { attacker: attacker_entity, from, target, strike, damage },
+    )];
+    happened.extend(
+        triggered
+            .into_iter()
+            .map(|keyword| (team, GameEventKind::Triggered { at: target, keyword })),
+    );
+
+    // dead units are only despawned at the end
+    // of the frame, so they leave the index now.
+    // a Nimble attacker may have taken the tile
+    if defender_unit.health <= 0 {
+        board_index.remove_unit(defender_entity);
+        let (unit, kind) = (defender_entity, defender_unit.kind);
+        happened.push((defender_unit.team, GameEventKind::Died { unit, at: target, kind }));
+    }
+
+    if attacker_unit.health <= 0 {
+        board_index.remove_unit(attacker_entity);
+        let (unit, kind) = (attacker_entity, attacker_unit.kind);
+        happened.push((team, GameEventKind::Died { unit, at: from, kind }));
+    } else if attacker_unit.position != from {
+        board_index.place_unit(attacker_entity, attacker_unit.position);
+        let (unit, to) = (attacker_entity, attacker_unit.position);
+        happened.push((team, GameEventKind::Moved { unit, from, to }));
+    }
+
+    happened
+}
+
+// units standing on a building are hit
+// instead, so this only runs on empty tiles
+fn attack_building(
+    units: &mut Query<(&mut Unit, &mut Transform)>,
+    (buildings, board_index): (&mut Query<&mut Building>, &mut BoardIndex),
+    (combat_rules, combat_rng): (&CombatRules, &mut CombatRng),
+    team: Team,
+    from: Hex,
+    target: Hex,
+) -> Vec<(Team, GameEventKind)> {
+    let (Some(attacker_entity), Some(building_entity)) =
+        (board_index.unit(from), board_index.building(target))
+    else {
+        return Vec::new();
+    };
+
+    let (Ok((mut attacker_unit, mut attacker_transform)), Ok(mut building)) =
+        (units.get_mut(attacker_entity), buildings.get_mut(building_entity))
+    else {
+        return Vec::new();
+    };
+
+    if attacker_unit.team != team
+        || building.team == team
+        || !attacker_unit.relative_attack_hexes().contains(&target)
+        || !attacker_unit.actions.contains(&Action::Attack)
+    {
+        return Vec::new();
+    }
+
+    let distance = from.unsigned_distance_to(target);
+    let strike = combat_rules.strike(combat_rng, attacker_unit.damage, distance);
+    let health_before = building.health;
+    let triggered =
+        attacker_unit.attack_with(&mut attacker_transform, building.as_mut(), strike.damage());
+
+    let damage = health_before - building.health;
+    let mut happened = vec![(
+        team,
+        GameEventKind::Attacked { attacker: attacker_entity, from, target, strike, damage },
+    )];
+    happened.extend(
+        triggered
+            .into_iter()
+            .map(|keyword| (team, GameEventKind::Triggered { at: target, keyword })),
+    );
+
+    if building.health <= 0 {
+        board_index.remove_building(building_entity);
+        let kind = building.kind;
+        happened.push((building.team, GameEventKind::Destroyed { at: target, kind }));
+    }
+
+    if attacker_unit.position != from {
+        board_index.place_unit(attacker_entity, attacker_unit.position);
+        let (unit, to) = (attacker_entity, attacker_unit.position);
+        happened.push((team, GameEventKind::Moved { unit, from, to }));
+    }
+
+    happened
+}
+
+// on an owned tile that isn't the capital,
+// walls also need the tile to be empty
+fn can_build(
+    hexes: &Query<&mut HexTile>,
+    board_index: &BoardIndex,
+    team: Team,
+    at: Hex,
+    kind: BuildingKind,
+) -> bool {
+    let Some(tile) = board_index.tile(at).and_then(|entity| hexes.get(entity).ok()) else {
+        return false;
+    };
+
+    tile.team == team
+        && tile.variant != TileVariant::Capital
+        && board_index.building(at).is_none()
+        && (kind != BuildingKind::Wall || board_index.unit(at).is_none())
+}
+
+// on the team's capital or one of its barracks
+fn can_recruit(
+    hexes: &Query<&mut HexTile>,
+    buildings: &Query<&mut Building>,
+    board_index: &BoardIndex,
+    team: Team,
+    at: Hex,
+) -> bool {
+    if board_index.unit(at).is_some() {
+        return false;
+    }
+
+    let capital = board_index
+        .tile(at)
+        .and_then(|entity| hexes.get(entity).ok())
+        .is_some_and(|tile| tile.team == team && tile.variant == TileVariant::Capital);
+
+    let barracks = board_index
+        .building(at)
+        .and_then(|entity| buildings.get(entity).ok())
+        .is_some_and(|building| {
+            building.kind == BuildingKind::Barracks && building.team == team && building.active()
+        });
+
+    capital || barracks
+}
+
+// done after every command, so moves, kills and
+// lost towers change what a unit can reach at once
+fn man_watchtowers(
+    units: &mut Query<(&mut Unit, &mut Transform)>,
+    buildings: &Query<&mut Building>,
+) {
+    for (mut unit, _transform) in units {
+        let tower = manned_watchtower(&unit, buildings);
+        if unit.watchtower != tower {
+            unit.watchtower = tower;
+        }
+    }
+}
+
+fn update_building_owners(
+    hexes: &Query<&mut HexTile>,
+    buildings: &mut Query<&mut Building>,
+    board_index: &mut BoardIndex,
+) -> Vec<(Team, GameEventKind)> {
+    let mut happened = Vec::new();
+
+    for tile in hexes.iter() {
+        let Some(entity) = board_index.building(tile.coordinate) else {
+            continue;
+        };
+
+        let Ok(mut building) = buildings.get_mut(entity) else {
+            continue;
+        };
+
+        if building.team == tile.team {
+            continue;
+        }
+
+        let team = building.team;
+        building.follow_owner(tile.team);
+
+        if building.health <= 0 {
+            board_index.remove_building(entity);
+            let kind = building.kind;
+            happened.push((team, GameEventKind::Destroyed { at: tile.coordinate, kind }));
+        }
+    }
+
+    happened
+}
+
+fn update_capture_progress(
+    hexes: &mut Query<&mut HexTile>,
+    units: &Query<(&mut Unit, &mut Transform)>,
+    rules: &CaptureRules,
+) -> Vec<(Team, GameEventKind)> {
+    let tiles = hexes.iter().cloned().collect::<Vec<_>>();
+    let living_units = units
+        .iter()
+        .filter(|(unit, _)| unit.health > 0)
+        .map(|(unit, _)| (unit.position, unit.team))
+        .collect::<Vec<_>>();
+
+    let captured = advance_capture(rules, &tiles, &living_units)
+        .into_iter()
+        .map(|tile| (tile.coordinate, tile))
+        .collect::<HashMap<_, _>>();
+
+    let mut happened = Vec::new();
+    for mut tile in hexes {
+        let Some(next) = captured.get(&tile.coordinate) else {
+            continue;
+        };
+
+        if tile.team != next.team {
+            let at = tile.coordinate;
+            if tile.variant == TileVariant::Capital && tile.team != Team::Neutral {
+                happened.push((tile.team, GameEventKind::CapitalLost { at }));
+            }
+            if next.team != Team::Neutral {
+                happened.push((next.team, GameEventKind::Captured { at }));
+            }
+        }
+
+        if *tile != *next {
+            *tile = next.clone();
+        }
+    }
+
+    happened
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use hexx::hex;
+
+    use crate::{
+        testing::{TestBoard, FIRST, SECOND},
+        units::components::UnitDefault,
+    };
+
+    use super::*;
+
+    #[test]
+    fn executioners_keep_attacking_and_nimble_units_follow_their_kills() {
+        let mut board = TestBoard::parse(
+            "
+            . B . .
+            . . s .
+            s . . .
+            ",
+        );
+
+        board.attack(hex(1, 0), hex(2, 1));
+        let dancer = board.unit(hex(2, 1)).unwrap();
+        assert_eq!(dancer.kind, UnitDefault::BladeDancer);
+        assert!(dancer.actions.contains(&Action::Attack));
+
+        board.attack(hex(2, 1), hex(0, 2));
+        let dancer = board.unit(hex(0, 2)).unwrap();
+        assert!(dancer.actions.contains(&Action::Attack));
+        assert_eq!(board.units().len(), 1);
+    }
+
+    #[test]
+    fn despised_units_strike_back_without_using_up_the_attack() {
+        let mut board = TestBoard::parse(
+            "
+            . . . .
+            . K n .
+            ",
+        );
+
+        board.attack(hex(1, 1), hex(2, 1));
+        board.attack(hex(1, 1), hex(2, 1));
+
+        let knight = board.unit(hex(1, 1)).unwrap();
+        let newt = board.unit(hex(2, 1)).unwrap();
+        // the knight's armor takes one off every strike back
+        assert_eq!((knight.health, newt.health), (2, 2));
+        assert!(knight.actions.contains(&Action::Attack));
+    }
+
+    #[test]
+    fn strike_back_can_kill_the_attacker_for_good() {
+        let mut board = TestBoard::parse(
+            "
+            . B . .
+            . . n .
+            ",
+        );
+
+        board.attack(hex(1, 0), hex(2, 1));
+        assert_eq!(board.unit(hex(1, 0)), None);
+        assert_eq!(board.unit(hex(2, 1)).unwrap().health, 4);
+
+        // nothing is left to act from that tile
+        board.move_unit(hex(1, 0), hex(0, 0));
+        board.attack(hex(1, 0), hex(2, 1));
+        assert_eq!(board.units().len(), 1);
+        assert_eq!(board.unit(hex(2, 1)).unwrap().health, 4);
+    }
+
+    #[test]
+    fn slow_units_wait_after_moving() {
+        let mut board = TestBoard::parse("P . . .");
+
+        board.move_unit(hex(0, 0), hex(1, 0));
+        board.end_round();
+        board.move_unit(hex(1, 0), hex(2, 0));
+        board.end_round();
+        board.move_unit(hex(1, 0), hex(2, 0));
+        assert!(board.unit(hex(1, 0)).is_some());
+
+        board.end_round();
+        board.move_unit(hex(1, 0), hex(2, 0));
+        assert!(board.unit(hex(2, 0)).is_some());
+    }
+
+    #[test]
+    fn regeneration_stops_at_max_health() {
+        let mut board = TestBoard::parse("N .");
+
+        let mut health = Vec::new();
+        for _ in 0..3 {
+            board.end_round();
+            health.push(board.unit(hex(0, 0)).unwrap().health);
+        }
+        assert_eq!(health, [8, 10, 10]);
+    }
+
+    #[test]
+    fn forfeited_teams_never_get_their_turn_back() {
+        let mut board = TestBoard::parse("N .");
+
+        board.command(GameCommand::Forfeit);
+        assert_eq!(board.active_team(), SECOND);
+
+        // a whole round goes by with a single turn
+        board.command(GameCommand::EndTurn);
+        assert_eq!(board.active_team(), SECOND);
+        assert_eq!(board.app.world.resource::<TurnCounter>().0, 1);
+        assert_eq!(board.app.world.resource::<TurnOrder>().forfeited, [FIRST]);
+    }
+
+    #[test]
+    fn watchtowers_extend_the_reach_of_the_units_manning_them() {
+        let mut board = TestBoard::parse("K . . . s");
+        board
+            .app
+            .world
+            .spawn(Building::new(BuildingKind::Watchtower, hex(2, 0), FIRST));
+        board.app.update();
+
+        // out of reach until the knight stands next to the tower
+        board.attack(hex(0, 0), hex(4, 0));
+        assert!(board.unit(hex(4, 0)).is_some());
+
+        board.move_unit(hex(0, 0), hex(1, 0));
+        assert_eq!(board.unit(hex(1, 0)).unwrap().watchtower, Some(hex(2, 0)));
+
+        board.attack(hex(1, 0), hex(4, 0));
+        assert!(board.unit(hex(4, 0)).is_none());
+    }
+
+    #[test]
+    fn watchtowers_do_not_shoot_on_their_own() {
+        let mut board = TestBoard::parse(". . s");
+        board
+            .app
+            .world
+            .spawn(Building::new(BuildingKind::Watchtower, hex(0, 0), FIRST));
+        board.app.update();
+
+        board.end_round();
+        assert!(board.unit(hex(2, 0)).is_some());
+    }
+
+    #[test]
+    fn forecasts_match_the_attacks() {
+        let fights = [
+            // blade dancers hit diagonally
+            (". B .\n. . s", hex(1, 0), hex(2, 1)),
+            (". B .\n. . n", hex(1, 0), hex(2, 1)),
+            ("K n .", hex(0, 0), hex(1, 0)),
+            ("A . k", hex(0, 0), hex(2, 0)),
+        ];
+
+        for (text, from, target) in fights {
+            let mut board = TestBoard::parse(text);
+            let attacker = board.unit(from).unwrap();
+            let defender = board.unit(target).unwrap();
+            let forecast = attacker.forecast(&defender);
+
+            board.attack(from, target);
+
+            let landed = forecast.moves_to.unwrap_or(from);
+            let attacker_after = board.unit(landed);
+            assert_eq!(attacker_after.is_none(), forecast.attacker_dies, "{text}");
+            if let Some(attacker_after) = attacker_after {
+                assert_eq!(attacker.health - attacker_after.health, forecast.retaliation, "{text}");
+                assert_eq!(
+                    attacker_after.actions.contains(&Action::Attack),
+                    forecast.keeps_attack,
+                    "{text}"
+                );
+            }
+
+            match board.unit(target).filter(|unit| unit.team == defender.team) {
+                Some(defender_after) => {
+                    assert!(!forecast.kills, "{text}");
+                    assert_eq!(defender.health - defender_after.health, forecast.damage, "{text}");
+                }
+                None => assert!(forecast.kills, "{text}"),
+            }
+        }
+    }
+
+    // random boards and commands, most of them
+    // invalid, checked after every single one
+    #[test]
+    fn random_fights_keep_the_invariants() {
+        const KINDS: [UnitDefault; 7] = [
+            UnitDefault::Archer,
+            UnitDefault::BladeDancer,
+            UnitDefault::Scout,
+            UnitDefault::Knight,
+            UnitDefault::Catapult,
+            UnitDefault::Sniper,
+            UnitDefault::Newt,
+        ];
+
+        for seed in 0..40 {
+            let mut rng = fastrand::Rng::with_seed(seed);
+
+            let hexes = Hex::ZERO.range(3).collect::<Vec<_>>();
+            let tiles = hexes
+                .iter()
+                .map(|coordinate| HexTile {
+                    coordinate: *coordinate,
+                    variant: TileVariant::Land,
+                    capture_progress: 0,
+                    team: Team::Neutral,
+                    resource: None,
+                })
+                .collect();
+
+            let mut free = hexes.clone();
+            rng.shuffle(&mut free);
+            let units = free
+                .iter()
+                .take(rng.usize(4..12))
+                .map(|position| {
+                    let team = if rng.bool() { FIRST } else { SECOND };
+                    let kind = KINDS[rng.usize(..KINDS.len())];
+                    let mut unit = Unit::new_default(kind, *position, team);
+                    unit.actions = vec![Action::Move, Action::Attack];
+                    unit
+                })
+                .collect();
+
+            let mut board = TestBoard::new(tiles, units);
+
+            for _ in 0..200 {
+                let before = board.units_by_entity();
+                let active = board.active_team();
+
+                let command = match rng.u8(..10) {
+                    0 => GameCommand::EndTurn,
+                    roll => {
+                        let Some(unit) = before.values().nth(rng.usize(..before.len().max(1)))
+                        else {
+                            break;
+                        };
+                        // mostly hexes the unit could reach
+                        let reach = if roll < 6 {
+                            unit.relative_attack_hexes()
+                        } else {
+                            unit.relative_move_hexes()
+                        };
+                        let to = match rng.u8(..5) {
+                            0 => hexes[rng.usize(..hexes.len())],
+                            _ => reach[rng.usize(..reach.len())],
+                        };
+
+                        let from = unit.position;
+                        if roll < 6 {
+                            GameCommand::Attack { from, target: to }
+                        } else {
+                            GameCommand::Move { from, to }
+                        }
+                    }
+                };
+                board.command(command);
+                let after = board.units_by_entity();
+
+                let positions = after
+                    .values()
+                    .map(|unit| unit.position)
+                    .collect::<HashSet<_>>();
+                assert_eq!(positions.len(), after.len(), "{seed}: units share a tile");
+
+                for (entity, unit) in &after {
+                    assert!(unit.health > 0, "{seed}: {unit:?} is dead but still there");
+                    assert!(unit.health <= unit.max_health, "{seed}: {unit:?} overhealed");
+
+                    // the dead never come back
+                    let Some(earlier) = before.get(entity) else {
+                        panic!("{seed}: {unit:?} appeared after {command:?}");
+                    };
+
+                    // outside of a new round nobody heals, and only
+                    // the units of the team whose turn it is move
+                    if command != GameCommand::EndTurn {
+                        assert!(unit.health <= earlier.health, "{seed}: {unit:?} healed");
+                        assert!(
+                            unit.team == active || unit.position == earlier.position,
+                            "{seed}: {unit:?} moved on the other team's turn"
+                        );
+                    }
+                }
+            }
+        }
+    }
+}
@@ -1,13 +1,26 @@
 use bevy::prelude::*;
 
-use self::systems::place_enemy_capital;
+use crate::{
+    animation::systems::animations_finished,
+    command::CommandSet,
+    state::{game_running, game_undecided},
+};
 
+use self::systems::play_ai_turns;
+
+pub mod planner;
 mod systems;
 
 pub struct EnemyPlugin;
 
 impl Plugin for EnemyPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(PostStartup, place_enemy_capital);
+        app.add_systems(
+            Update,
+            play_ai_turns
+                .run_if(game_running.and_then(animations_finished))
+                .run_if(game_undecided)
+                .in_set(CommandSet::Issue),
+        );
     }
-}
\ No newline at end of file
+}
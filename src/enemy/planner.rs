@@ -0,0 +1,434 @@
+use std::collections::{HashMap, HashSet};
+
+use hexx::Hex;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    board::components::{HexTile, Team, TileVariant},
+    buildings::components::{manned_watchtower, Building, BuildingKind},
+    command::events::GameCommand,
+    units::components::{armored_damage, Action, Keyword, Unit, UnitDefault},
+};
+
+// how an ai team plays, the simulation
+// pits two of these against each other
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct AiProfile {
+    pub name: String,
+    // recruited one after another, as long
+    // as the coins stay above the reserve
+    pub recruits: Vec<UnitDefault>,
+    #[serde(default)]
+    pub buildings: Vec<BuildingKind>,
+    #[serde(default)]
+    pub reserve: i32,
+    // units that stay next to the capital
+    #[serde(default)]
+    pub defenders: usize,
+    // no recruits past this many units
+    #[serde(default)]
+    pub max_units: Option<usize>,
+}
+
+impl AiProfile {
+    pub fn presets() -> Vec<AiProfile> {
+        vec![
+            AiProfile {
+                name: "balanced".to_string(),
+                recruits: vec![UnitDefault::Knight, UnitDefault::Archer, UnitDefault::Scout],
+                buildings: vec![BuildingKind::Market, BuildingKind::Barracks],
+                reserve: 2,
+                defenders: 1,
+                max_units: None,
+            },
+            // balanced, but never more than two units
+            AiProfile {
+                name: "timid".to_string(),
+                recruits: vec![UnitDefault::Knight, UnitDefault::Archer, UnitDefault::Scout],
+                buildings: vec![BuildingKind::Market, BuildingKind::Barracks],
+                reserve: 2,
+                defenders: 1,
+                max_units: Some(2),
+            },
+            AiProfile {
+                name: "rush".to_string(),
+                recruits: vec![UnitDefault::Scout, UnitDefault::BladeDancer],
+                buildings: Vec::new(),
+                reserve: 0,
+                defenders: 0,
+                max_units: None,
+            },
+            AiProfile {
+                name: "economy".to_string(),
+                recruits: vec![UnitDefault::Knight, UnitDefault::Catapult],
+                buildings: vec![
+                    BuildingKind::Market,
+                    BuildingKind::Market,
+                    BuildingKind::Watchtower,
+                ],
+                reserve: 4,
+                defenders: 2,
+                max_units: None,
+            },
+        ]
+    }
+
+    pub fn preset(name: &str) -> Option<AiProfile> {
+        AiProfile::presets()
+            .into_iter()
+            .find(|profile| profile.name == name)
+    }
+}
+
+// what the ai sees of the game
+pub struct AiView<'a> {
+    pub tiles: &'a [HexTile],
+    pub units: &'a [Unit],
+    pub buildings: &'a [Building],
+    pub coins: i32,
+}
+
+// every command of one turn, ending it. commands
+// go through the same checks as a player's, so a
+// bad guess is dropped instead of breaking the game
+pub fn plan_turn(profile: &AiProfile, team: Team, view: &AiView) -> Vec<GameCommand> {
+    let owners = view
+        .tiles
+        .iter()
+        .map(|tile| (tile.coordinate, tile.team))
+        .collect::<HashMap<_, _>>();
+    let capital = view
+        .tiles
+        .iter()
+        .find(|tile| tile.team == team && tile.variant == TileVariant::Capital)
+        .map(|tile| tile.coordinate);
+
+    let mut occupied = view
+        .units
+        .iter()
+        .filter(|unit| unit.health > 0)
+        .map(|unit| (unit.position, unit.team))
+        .collect::<HashMap<_, _>>();
+    let mut enemy_health = view
+        .units
+        .iter()
+        .filter(|unit| unit.team != team && unit.health > 0)
+        .map(|unit| (unit.position, unit.health))
+        .collect::<HashMap<_, _>>();
+    let walls = view
+        .buildings
+        .iter()
+        .filter(|building| building.kind == BuildingKind::Wall)
+        .map(|building| building.position)
+        .collect::<HashSet<_>>();
+
+    let mut own_units = view
+        .units
+        .iter()
+        .filter(|unit| unit.team == team && unit.health > 0)
+        .cloned()
+        .collect::<Vec<_>>();
+    // closest to the capital first, they are the defenders
+    own_units.sort_by_key(|unit| {
+        (
+            capital.map_or(0, |capital| unit.position.unsigned_distance_to(capital)),
+            unit.position.x,
+            unit.position.y,
+        )
+    });
+
+    let mut plan = Vec::new();
+
+    for (index, unit) in own_units.iter_mut().enumerate() {
+        if let Some(target) = pick_target(unit, team, view, &enemy_health) {
+            attack(
+                &mut plan,
+                unit,
+                target,
+                &mut occupied,
+                &mut enemy_health,
+                view,
+            );
+        }
+
+        if !unit.actions.contains(&Action::Move) {
+            continue;
+        }
+
+        let goal = if index < profile.defenders {
+            capital
+        } else {
+            advance_goal(unit, team, &owners, &occupied)
+        };
+        let Some(goal) = goal else {
+            continue;
+        };
+
+        let step = unit
+            .relative_move_hexes()
+            .into_iter()
+            .filter(|hex| owners.contains_key(hex) && !occupied.contains_key(hex))
+            .filter(|hex| !walls.contains(hex))
+            .min_by_key(|hex| (hex.unsigned_distance_to(goal), hex.x, hex.y));
+
+        let Some(step) = step else {
+            continue;
+        };
+        if step.unsigned_distance_to(goal) >= unit.position.unsigned_distance_to(goal) {
+            continue;
+        }
+
+        plan.push(GameCommand::Move {
+            from: unit.position,
+            to: step,
+        });
+        occupied.remove(&unit.position);
+        occupied.insert(step, team);
+        unit.position = step;
+        unit.watchtower = manned_watchtower(unit, view.buildings);
+
+        if let Some(target) = pick_target(unit, team, view, &enemy_health) {
+            attack(
+                &mut plan,
+                unit,
+                target,
+                &mut occupied,
+                &mut enemy_health,
+                view,
+            );
+        }
+    }
+
+    let mut coins = view.coins;
+    let own_count = view.units.iter().filter(|unit| unit.team == team).count();
+    let mut army = own_units.len();
+
+    let spawn_points = capital.into_iter().chain(
+        view.buildings
+            .iter()
+            .filter(|b| b.kind == BuildingKind::Barracks && b.team == team && b.active())
+            .map(|b| b.position),
+    );
+    for (index, at) in spawn_points.enumerate() {
+        let Some(kind) = cycle(&profile.recruits, own_count + index) else {
+            break;
+        };
+        if profile.max_units.is_some_and(|max| army >= max) {
+            break;
+        }
+        if occupied.contains_key(&at) || coins - kind.cost() < profile.reserve {
+            continue;
+        }
+
+        plan.push(GameCommand::Recruit { at, kind });
+        occupied.insert(at, team);
+        coins -= kind.cost();
+        army += 1;
+    }
+
+    let own_buildings = view.buildings.iter().filter(|b| b.team == team).count();
+    if let Some(kind) = cycle(&profile.buildings, own_buildings) {
+        let built = view
+            .buildings
+            .iter()
+            .map(|b| b.position)
+            .collect::<HashSet<_>>();
+        let site = view
+            .tiles
+            .iter()
+            .filter(|tile| tile.team == team && tile.variant == TileVariant::Land)
+            .filter(|tile| !built.contains(&tile.coordinate))
+            .filter(|tile| kind != BuildingKind::Wall || !occupied.contains_key(&tile.coordinate))
+            .map(|tile| tile.coordinate)
+            .min_by_key(|hex| {
+                let distance = capital.map_or(0, |capital| hex.unsigned_distance_to(capital));
+                // defenses go to the border, the rest stays home
+                match kind {
+                    BuildingKind::Watchtower | BuildingKind::Wall => {
+                        (u32::MAX - distance, hex.x, hex.y)
+                    }
+                    _ => (distance, hex.x, hex.y),
+                }
+            });
+
+        if let Some(at) = site {
+            if coins - kind.cost() >= profile.reserve {
+                plan.push(GameCommand::Build { at, kind });
+            }
+        }
+    }
+
+    plan.push(GameCommand::EndTurn);
+    plan
+}
+
+fn cycle<T: Copy>(items: &[T], index: usize) -> Option<T> {
+    (!items.is_empty()).then(|| items[index % items.len()])
+}
+
+// a kill if possible, otherwise the weakest enemy in reach
+fn pick_target(
+    unit: &Unit,
+    team: Team,
+    view: &AiView,
+    enemy_health: &HashMap<Hex, i32>,
+) -> Option<Hex> {
+    if !unit.actions.contains(&Action::Attack) {
+        return None;
+    }
+
+    let reach = unit.relative_attack_hexes();
+    let units = view
+        .units
+        .iter()
+        .filter(|enemy| reach.contains(&enemy.position))
+        .filter_map(|enemy| {
+            let health = *enemy_health.get(&enemy.position)?;
+            let damage = armored_damage(&enemy.keywords, unit.damage);
+            Some((health > damage, false, health, enemy.position))
+        });
+
+    let buildings = view
+        .buildings
+        .iter()
+        .filter(|building| building.team != team && building.active())
+        .filter(|building| reach.contains(&building.position))
+        .filter(|building| !enemy_health.contains_key(&building.position))
+        .map(|building| (true, true, building.health, building.position));
+
+    units
+        .chain(buildings)
+        .min_by_key(|(survives, building, health, hex)| {
+            (*survives, *building, *health, hex.x, hex.y)
+        })
+        .map(|(_, _, _, hex)| hex)
+}
+
+fn attack(
+    plan: &mut Vec<GameCommand>,
+    unit: &mut Unit,
+    target: Hex,
+    occupied: &mut HashMap<Hex, Team>,
+    enemy_health: &mut HashMap<Hex, i32>,
+    view: &AiView,
+) {
+    plan.push(GameCommand::Attack {
+        from: unit.position,
+        target,
+    });
+    unit.remove_action(Action::Attack);
+
+    let Some(health) = enemy_health.get_mut(&target) else {
+        return;
+    };
+    let keywords = view
+        .units
+        .iter()
+        .find(|enemy| enemy.position == target)
+        .map_or(&[][..], |enemy| &enemy.keywords[..]);
+    *health -= armored_damage(keywords, unit.damage);
+
+    if *health > 0 {
+        return;
+    }
+
+    enemy_health.remove(&target);
+    occupied.remove(&target);
+
+    if unit.keywords.contains(&Keyword::Nimble) {
+        occupied.remove(&unit.position);
+        occupied.insert(target, unit.team);
+        unit.position = target;
+    }
+}
+
+// the closest tile that isn't ours yet, tiles
+// next to our territory can be captured right away
+fn advance_goal(
+    unit: &Unit,
+    team: Team,
+    owners: &HashMap<Hex, Team>,
+    occupied: &HashMap<Hex, Team>,
+) -> Option<Hex> {
+    let capturable = |hex: Hex| {
+        hex.all_neighbors()
+            .iter()
+            .any(|neighbor| owners.get(neighbor) == Some(&team))
+    };
+
+    // already capturing, so it stays put
+    if owners.get(&unit.position) != Some(&team) && capturable(unit.position) {
+        return None;
+    }
+
+    owners
+        .iter()
+        .filter(|(hex, owner)| **owner != team && occupied.get(hex) != Some(&team))
+        .map(|(hex, _)| *hex)
+        .min_by_key(|hex| {
+            let penalty = if capturable(*hex) { 0 } else { 2 };
+            (
+                unit.position.unsigned_distance_to(*hex) + penalty,
+                hex.x,
+                hex.y,
+            )
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use hexx::hex;
+
+    use super::*;
+
+    const AI: Team = Team::Player(1);
+    const PLAYER: Team = Team::Player(0);
+
+    fn tile(x: i32, y: i32, team: Team) -> HexTile {
+        HexTile {
+            coordinate: hex(x, y),
+            variant: TileVariant::Land,
+            capture_progress: 0,
+            team,
+            resource: None,
+        }
+    }
+
+    #[test]
+    fn kills_in_reach_come_first_and_the_turn_ends() {
+        let mut tiles = vec![
+            tile(0, 0, AI),
+            tile(1, 0, AI),
+            tile(-1, 0, PLAYER),
+            tile(0, -1, PLAYER),
+        ];
+        tiles[0].variant = TileVariant::Capital;
+
+        let mut knight = Unit::new_default(UnitDefault::Knight, hex(1, 0), AI);
+        knight.actions = vec![Action::Move, Action::Attack];
+        let mut hurt = Unit::new_default(UnitDefault::Knight, hex(1, -1), PLAYER);
+        hurt.health = 1;
+        let healthy = Unit::new_default(UnitDefault::Knight, hex(0, 1), PLAYER);
+
+        let view = AiView {
+            tiles: &tiles,
+            units: &[knight, hurt, healthy],
+            buildings: &[],
+            coins: 0,
+        };
+        let profile = AiProfile::preset("rush").unwrap();
+        let plan = plan_turn(&profile, AI, &view);
+
+        assert_eq!(
+            plan.first(),
+            Some(&GameCommand::Attack {
+                from: hex(1, 0),
+                target: hex(1, -1)
+            })
+        );
+        assert_eq!(plan.last(), Some(&GameCommand::EndTurn));
+        assert!(!plan
+            .iter()
+            .any(|command| matches!(command, GameCommand::Recruit { .. })));
+    }
+}
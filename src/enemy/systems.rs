@@ -1,20 +1,52 @@
 use bevy::prelude::*;
-use hexx::hex;
 
-use crate::board::components::{HexTile, TileVariant, Team};
+use crate::{
+    board::{
+        components::HexTile,
+        resources::{Controller, TeamSettings},
+    },
+    buildings::components::Building,
+    command::events::CommandEvent,
+    player::resources::{TeamCoins, TurnOrder},
+    state::{Difficulty, MatchSettings, Opponent},
+    units::components::Unit,
+};
 
-pub fn place_enemy_capital(mut hexes: Query<&mut HexTile>) {
-    let enemy_capital = hex(0, 0);
-    let enemy_tiles = enemy_capital.all_neighbors();
+use super::planner::{plan_turn, AiView};
 
-    for mut hex in &mut hexes {
-        if enemy_tiles.contains(&hex.coordinate) {
-            hex.team = Team::Enemy;
-        }
+// an ai team's whole turn at once, the same way
+// the simulation plays it, the difficulty picks
+// the profile and hotseat games fall back to normal
+pub fn play_ai_turns(
+    turn_order: Res<TurnOrder>,
+    team_settings: Res<TeamSettings>,
+    match_settings: Res<MatchSettings>,
+    team_coins: Res<TeamCoins>,
+    game: (Query<&HexTile>, Query<&Unit>, Query<&Building>),
+    mut game_commands: EventWriter<CommandEvent>,
+) {
+    let team = turn_order.active();
+    if team_settings.controller(team) != Some(Controller::Ai) {
+        return;
+    }
+
+    let difficulty = match match_settings.opponent {
+        Opponent::Ai(difficulty) => difficulty,
+        Opponent::Hotseat => Difficulty::Normal,
+    };
+
+    let (hexes, units, buildings) = game;
+    let tiles = hexes.iter().cloned().collect::<Vec<_>>();
+    let units = units.iter().cloned().collect::<Vec<_>>();
+    let buildings = buildings.iter().cloned().collect::<Vec<_>>();
+    let view = AiView {
+        tiles: &tiles,
+        units: &units,
+        buildings: &buildings,
+        coins: team_coins.get(team),
+    };
 
-        if hex.coordinate == enemy_capital {
-            hex.team = Team::Enemy;
-            hex.variant = TileVariant::Capital;
-        }
+    for command in plan_turn(&difficulty.profile(), team, &view) {
+        game_commands.send(CommandEvent::local(command));
     }
 }
@@ -0,0 +1,23 @@
+use bevy::prelude::*;
+
+// root of the log, shown and hidden with L
+#[derive(Component)]
+pub struct LogPanel;
+
+#[derive(Component, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum LogButton {
+    Team,
+    Category,
+    Export,
+}
+
+// the text on a LogButton
+#[derive(Component)]
+pub struct LogButtonLabel(pub LogButton);
+
+// the entries, moved up inside
+// the panel to scroll through them
+#[derive(Component, Default)]
+pub struct LogLines {
+    pub position: f32,
+}
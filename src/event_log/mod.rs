@@ -0,0 +1,59 @@
+use bevy::prelude::*;
+
+use crate::{
+    command::CommandSet,
+    state::{game_running, AppState, GameSetupSet},
+    util::despawn_all,
+};
+
+use self::{
+    components::LogPanel,
+    resources::{GameLog, LogFilter},
+    systems::{
+        press_log_buttons, record_game_events, reset_log, scroll_log, spawn_log_panel, toggle_log,
+        update_log_labels, update_log_lines,
+    },
+};
+
+pub mod components;
+pub mod resources;
+pub mod systems;
+
+pub const LOG_PATH: &str = "saves/game_log.txt";
+
+// turn stamped list of what happened in the game
+pub struct EventLogPlugin;
+
+impl Plugin for EventLogPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<GameLog>()
+            .init_resource::<LogFilter>()
+            .add_systems(
+                OnEnter(AppState::InGame),
+                (
+                    reset_log.in_set(GameSetupSet::Resources),
+                    spawn_log_panel.in_set(GameSetupSet::Spawn),
+                ),
+            )
+            .add_systems(OnExit(AppState::InGame), despawn_all::<LogPanel>)
+            .add_systems(
+                Update,
+                (
+                    // nothing is missed while paused
+                    record_game_events
+                        .after(CommandSet::Apply)
+                        .run_if(in_state(AppState::InGame)),
+                    (
+                        toggle_log,
+                        press_log_buttons,
+                        update_log_labels,
+                        update_log_lines,
+                        scroll_log,
+                    )
+                        .chain()
+                        .after(record_game_events)
+                        .run_if(game_running),
+                ),
+            );
+    }
+}
@@ -0,0 +1,177 @@
+use std::{fs, io, path::Path};
+
+use bevy::prelude::*;
+use hexx::Hex;
+
+use crate::{
+    board::components::Team,
+    command::{
+        events::{GameEvent, GameEventKind},
+        rules::Strike,
+    },
+};
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum LogCategory {
+    Movement,
+    Combat,
+    Territory,
+    Economy,
+}
+
+impl LogCategory {
+    pub const ALL: [LogCategory; 4] = [
+        LogCategory::Movement,
+        LogCategory::Combat,
+        LogCategory::Territory,
+        LogCategory::Economy,
+    ];
+
+    pub fn of(kind: &GameEventKind) -> Self {
+        match kind {
+            GameEventKind::Moved { .. } => LogCategory::Movement,
+            GameEventKind::Attacked { .. }
+            | GameEventKind::Triggered { .. }
+            | GameEventKind::Died { .. }
+            | GameEventKind::Destroyed { .. } => LogCategory::Combat,
+            GameEventKind::Captured { .. }
+            | GameEventKind::CapitalLost { .. }
+            | GameEventKind::Forfeited => LogCategory::Territory,
+            GameEventKind::Recruited { .. }
+            | GameEventKind::Built { .. }
+            | GameEventKind::Income { .. } => LogCategory::Economy,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            LogCategory::Movement => "Moves",
+            LogCategory::Combat => "Combat",
+            LogCategory::Territory => "Territory",
+            LogCategory::Economy => "Economy",
+        }
+    }
+}
+
+// everything that happened this game, oldest first
+#[derive(Resource, Default)]
+pub struct GameLog {
+    pub entries: Vec<GameEvent>,
+}
+
+impl GameLog {
+    pub fn filtered<'a>(&'a self, filter: &'a LogFilter) -> impl Iterator<Item = &'a GameEvent> {
+        self.entries.iter().filter(|event| filter.shows(event))
+    }
+
+    pub fn export(&self, filter: &LogFilter, path: impl AsRef<Path>) -> io::Result<()> {
+        let path = path.as_ref();
+        if let Some(directory) = path.parent() {
+            fs::create_dir_all(directory)?;
+        }
+
+        let lines = self
+            .filtered(filter)
+            .map(|event| describe(event) + "\n")
+            .collect::<String>();
+        fs::write(path, lines)
+    }
+}
+
+// None shows everything
+#[derive(Resource, Default)]
+pub struct LogFilter {
+    pub team: Option<Team>,
+    pub category: Option<LogCategory>,
+}
+
+impl LogFilter {
+    pub fn shows(&self, event: &GameEvent) -> bool {
+        self.team.is_none_or(|team| event.team == team)
+            && self
+                .category
+                .is_none_or(|category| LogCategory::of(&event.kind) == category)
+    }
+}
+
+pub fn describe(event: &GameEvent) -> String {
+    let what = match event.kind {
+        GameEventKind::Moved { from, to, .. } => format!("moved {} to {}", at(from), at(to)),
+        GameEventKind::Attacked {
+            target,
+            strike,
+            damage,
+            ..
+        } => match strike {
+            Strike::Miss => format!("missed {}", at(target)),
+            Strike::Dodged => format!("attacked {}, dodged", at(target)),
+            Strike::Hit(_) => format!("hit {} for {damage}", at(target)),
+            Strike::Critical(_) => format!("critically hit {} for {damage}", at(target)),
+        },
+        GameEventKind::Triggered { at: hex, keyword } => {
+            format!("{} at {}", keyword.name(), at(hex))
+        }
+        GameEventKind::Died { at: hex, kind, .. } => format!("lost a {kind:?} at {}", at(hex)),
+        GameEventKind::Destroyed { at: hex, kind } => {
+            format!("lost a {} at {}", kind.name(), at(hex))
+        }
+        GameEventKind::Recruited { at: hex, kind } => {
+            format!("recruited a {kind:?} at {}", at(hex))
+        }
+        GameEventKind::Built { at: hex, kind } => format!("built a {} at {}", kind.name(), at(hex)),
+        GameEventKind::Captured { at: hex } => format!("captured {}", at(hex)),
+        GameEventKind::CapitalLost { at: hex } => format!("lost the capital at {}", at(hex)),
+        GameEventKind::Income { coins } => format!("received {coins} coins"),
+        GameEventKind::Forfeited => "forfeited the game".to_string(),
+    };
+
+    format!("Turn {}, {:?}: {what}", event.turn, event.team)
+}
+
+fn at(hex: Hex) -> String {
+    format!("({}, {})", hex.x, hex.y)
+}
+
+#[cfg(test)]
+mod tests {
+    use hexx::hex;
+
+    use super::*;
+
+    #[test]
+    fn filters_by_team_and_category() {
+        let event = |team, kind| GameEvent {
+            turn: 2,
+            team,
+            kind,
+        };
+        let log = GameLog {
+            entries: vec![
+                event(
+                    Team::Player(0),
+                    GameEventKind::Moved {
+                        unit: Entity::PLACEHOLDER,
+                        from: hex(0, 0),
+                        to: hex(1, 0),
+                    },
+                ),
+                event(Team::Player(1), GameEventKind::Income { coins: 3 }),
+                event(Team::Player(0), GameEventKind::Income { coins: 5 }),
+            ],
+        };
+
+        let filter = LogFilter {
+            team: Some(Team::Player(0)),
+            category: Some(LogCategory::Economy),
+        };
+        let lines = log.filtered(&filter).map(describe).collect::<Vec<_>>();
+        assert_eq!(lines, ["Turn 2, Player(0): received 5 coins"]);
+
+        let everything = LogFilter::default();
+        assert_eq!(log.filtered(&everything).count(), 3);
+        assert_eq!(
+            describe(&log.entries[0]),
+            "Turn 2, Player(0): moved (0, 0) to (1, 0)"
+        );
+    }
+}
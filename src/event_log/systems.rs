@@ -0,0 +1,244 @@
+use bevy::{
+    input::mouse::{MouseScrollUnit, MouseWheel},
+    prelude::*,
+};
+
+use crate::{
+    command::events::GameEvent,
+    menu::systems::{BUTTON_COLOR, FONT},
+    player::resources::TurnOrder,
+};
+
+use super::{
+    components::{LogButton, LogButtonLabel, LogLines, LogPanel},
+    resources::{describe, GameLog, LogCategory, LogFilter},
+    LOG_PATH,
+};
+
+const LINE_HEIGHT: f32 = 20.;
+
+pub fn reset_log(mut commands: Commands) {
+    commands.insert_resource(GameLog::default());
+    commands.insert_resource(LogFilter::default());
+}
+
+pub fn spawn_log_panel(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let text_style = |font_size: f32, color: Color| TextStyle {
+        font: asset_server.load(FONT),
+        font_size,
+        color,
+    };
+
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                left: Val::Px(10.),
+                top: Val::Percent(35.),
+                width: Val::Px(420.),
+                height: Val::Percent(35.),
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(6.),
+                padding: UiRect::all(Val::Px(6.)),
+                ..default()
+            },
+            background_color: Color::rgba(0., 0., 0., 0.6).into(),
+            ..default()
+        })
+        // keeps clicks and the wheel off the board
+        .insert((LogPanel, Interaction::default()))
+        .with_children(|panel| {
+            panel
+                .spawn(NodeBundle {
+                    style: Style {
+                        column_gap: Val::Px(6.),
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|header| {
+                    for button in [LogButton::Team, LogButton::Category, LogButton::Export] {
+                        header
+                            .spawn(ButtonBundle {
+                                style: Style {
+                                    padding: UiRect::axes(Val::Px(8.), Val::Px(4.)),
+                                    ..default()
+                                },
+                                background_color: BUTTON_COLOR.into(),
+                                ..default()
+                            })
+                            .insert(button)
+                            .with_children(|parent| {
+                                parent
+                                    .spawn(TextBundle::from_section(
+                                        "Export",
+                                        text_style(18., Color::WHITE),
+                                    ))
+                                    .insert(LogButtonLabel(button));
+                            });
+                    }
+                });
+
+            panel
+                .spawn(NodeBundle {
+                    style: Style {
+                        flex_grow: 1.,
+                        overflow: Overflow::clip_y(),
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|list| {
+                    list.spawn(TextBundle::from_section("", text_style(16., Color::WHITE)))
+                        .insert(LogLines::default());
+                });
+        });
+}
+
+// every event since the game started, newest last
+pub fn record_game_events(mut game_events: EventReader<GameEvent>, mut game_log: ResMut<GameLog>) {
+    game_log.entries.extend(game_events.iter().copied());
+}
+
+pub fn toggle_log(keys: Res<Input<KeyCode>>, mut panels: Query<&mut Visibility, With<LogPanel>>) {
+    if !keys.just_released(KeyCode::L) {
+        return;
+    }
+
+    for mut visibility in &mut panels {
+        *visibility = match *visibility {
+            Visibility::Hidden => Visibility::Inherited,
+            _ => Visibility::Hidden,
+        };
+    }
+}
+
+pub fn press_log_buttons(
+    buttons: Query<(&Interaction, &LogButton), Changed<Interaction>>,
+    turn_order: Res<TurnOrder>,
+    game_log: Res<GameLog>,
+    mut filter: ResMut<LogFilter>,
+    mut labels: Query<(&mut Text, &LogButtonLabel)>,
+) {
+    for (interaction, button) in &buttons {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+
+        match button {
+            // All, then every team in turn order
+            LogButton::Team => {
+                let next = match filter.team {
+                    None => 0,
+                    Some(team) => turn_order
+                        .teams
+                        .iter()
+                        .position(|t| *t == team)
+                        .map_or(0, |i| i + 1),
+                };
+                filter.team = turn_order.teams.get(next).copied();
+            }
+            LogButton::Category => {
+                let next = match filter.category {
+                    None => 0,
+                    Some(category) => LogCategory::ALL
+                        .iter()
+                        .position(|c| *c == category)
+                        .map_or(0, |i| i + 1),
+                };
+                filter.category = LogCategory::ALL.get(next).copied();
+            }
+            LogButton::Export => {
+                let message = match game_log.export(&filter, LOG_PATH) {
+                    Ok(()) => format!("Saved to {LOG_PATH}"),
+                    Err(error) => {
+                        warn!("could not export the log to {LOG_PATH}: {error}");
+                        "Could not export".to_string()
+                    }
+                };
+
+                for (mut text, label) in &mut labels {
+                    if label.0 == LogButton::Export {
+                        text.sections[0].value = message.clone();
+                    }
+                }
+            }
+        }
+    }
+}
+
+pub fn update_log_labels(
+    filter: Res<LogFilter>,
+    mut labels: Query<(Ref<LogButtonLabel>, &mut Text)>,
+) {
+    for (label, mut text) in &mut labels {
+        if !filter.is_changed() && !label.is_added() {
+            continue;
+        }
+
+        text.sections[0].value = match label.0 {
+            LogButton::Team => match filter.team {
+                Some(team) => format!("Team: {team:?}"),
+                None => "Team: All".to_string(),
+            },
+            LogButton::Category => match filter.category {
+                Some(category) => format!("Type: {}", category.name()),
+                None => "Type: All".to_string(),
+            },
+            LogButton::Export => "Export".to_string(),
+        };
+    }
+}
+
+pub fn update_log_lines(
+    game_log: Res<GameLog>,
+    filter: Res<LogFilter>,
+    mut lines: Query<(&mut Text, &mut LogLines, &mut Style)>,
+) {
+    if !game_log.is_changed() && !filter.is_changed() {
+        return;
+    }
+
+    let mut entries = game_log.filtered(&filter).map(describe).collect::<Vec<_>>();
+    entries.reverse();
+
+    for (mut text, mut log_lines, mut style) in &mut lines {
+        // back to the newest entry
+        log_lines.position = 0.;
+        style.top = Val::Px(0.);
+        text.sections[0].value = entries.join("\n");
+    }
+}
+
+pub fn scroll_log(
+    mut mouse_wheel: EventReader<MouseWheel>,
+    panels: Query<&Interaction, With<LogPanel>>,
+    mut lines: Query<(&mut LogLines, &mut Style, &Parent, &Node)>,
+    nodes: Query<&Node>,
+) {
+    let scrolled = mouse_wheel
+        .iter()
+        .map(|wheel| match wheel.unit {
+            MouseScrollUnit::Line => wheel.y * LINE_HEIGHT,
+            MouseScrollUnit::Pixel => wheel.y,
+        })
+        .sum::<f32>();
+
+    if scrolled == 0.
+        || panels
+            .iter()
+            .all(|interaction| *interaction == Interaction::None)
+    {
+        return;
+    }
+
+    for (mut log_lines, mut style, parent, node) in &mut lines {
+        let Ok(list) = nodes.get(parent.get()) else {
+            continue;
+        };
+
+        let max_scroll = (node.size().y - list.size().y).max(0.);
+        log_lines.position = (log_lines.position + scrolled).clamp(-max_scroll, 0.);
+        style.top = Val::Px(log_lines.position);
+    }
+}
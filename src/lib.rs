@@ -0,0 +1,26 @@
+pub mod save;
+pub mod scenario;
+pub mod simulation;
+pub mod snapshot;
+pub mod state;
+pub mod util;
+
+pub mod animation;
+pub mod board;
+pub mod buildings;
+pub mod clock;
+pub mod command;
+pub mod enemy;
+pub mod event_log;
+pub mod menu;
+pub mod network;
+pub mod player;
+pub mod text;
+pub mod tile_highlighting;
+pub mod tutorial;
+pub mod units;
+
+#[cfg(test)]
+mod benches;
+#[cfg(test)]
+pub mod testing;
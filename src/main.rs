@@ -1,35 +1,77 @@
 use bevy::prelude::*;
-use board::BoardPlugin;
-use enemy::EnemyPlugin;
-use player::PlayerPlugin;
+use bevy_hexagons::{
+    animation::AnimationPlugin,
+    board::{
+        resources::{TeamSettings, Themes},
+        BoardPlugin,
+    },
+    buildings::BuildingsPlugin,
+    clock::ClockPlugin,
+    command::CommandPlugin,
+    enemy::EnemyPlugin,
+    event_log::EventLogPlugin,
+    menu::MenuPlugin,
+    network::{NetConfig, NetworkPlugin},
+    player::{resources::EndTurnPrompt, PlayerPlugin},
+    scenario::{
+        resources::{Profile, ScenarioLibrary},
+        ScenarioPlugin, CAMPAIGN_DIRECTORY, PROFILE_PATH, SCENARIO_DIRECTORY,
+    },
+    state::{AppState, StatePlugin},
+    text::TextPlugin,
+    tile_highlighting::TileHighlighting,
+    tutorial::TutorialPlugin,
+    units::UnitsPlugin,
+};
 
-use text::TextPlugin;
-use tile_highlighting::TileHighlighting;
-use units::UnitsPlugin;
+fn main() {
+    let mut app = App::new();
+    let net_config = NetConfig::from_args();
+    let scenarios = ScenarioLibrary::load(SCENARIO_DIRECTORY, CAMPAIGN_DIRECTORY);
+    let scenario = scenarios.scenario_from_args();
 
-pub mod util;
+    // network games and scenarios picked on the command
+    // line skip the menus, peers agree on the game from the start
+    if net_config.is_some() || scenario.is_some() {
+        app.insert_resource(State::new(AppState::InGame));
+    }
 
-pub mod board;
-mod enemy;
-mod player;
-mod text;
-mod tile_highlighting;
-pub mod units;
+    if let Some(scenario) = scenario {
+        app.insert_resource(scenario);
+    }
 
-fn main() {
-    App::new()
-        .insert_resource(ClearColor(Color::rgb_u8(20, 20, 20)))
+    app.insert_resource(TeamSettings::from_args())
+        .insert_resource(scenarios)
+        .insert_resource(Profile::read(PROFILE_PATH))
+        .insert_resource(Themes::from_args())
+        .insert_resource(EndTurnPrompt::from_args())
         .add_plugins(DefaultPlugins)
         .add_plugins((
+            StatePlugin,
+            MenuPlugin,
             BoardPlugin,
+            CommandPlugin,
             EnemyPlugin,
             PlayerPlugin,
             UnitsPlugin,
+            BuildingsPlugin,
+            ScenarioPlugin,
+            TutorialPlugin,
             TextPlugin,
+            EventLogPlugin,
+            AnimationPlugin,
+            ClockPlugin,
             TileHighlighting,
         ))
-        .add_systems(Startup, setup)
-        .run();
+        .add_systems(Startup, setup);
+
+    // has to come after PlayerPlugin, it
+    // replaces the local player's team
+    if let Some(config) = net_config {
+        app.add_plugins(NetworkPlugin { config });
+    }
+
+    app.run();
 }
 
 fn setup(mut commands: Commands) {
@@ -0,0 +1,56 @@
+use bevy::prelude::*;
+
+// root of a menu, despawned with
+// the state that spawned it
+#[derive(Component)]
+pub struct MenuScreen;
+
+#[derive(Component)]
+pub struct PauseMenu;
+
+// shown over the game once a scenario is won or lost
+#[derive(Component)]
+pub struct OutcomeBanner;
+
+#[derive(Component, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum MenuButton {
+    NewGame,
+    Load,
+    Settings,
+    Scenarios,
+    Tutorial,
+    // indices into the ScenarioLibrary
+    PlayScenario(usize),
+    PlayCampaign(usize),
+    Quit,
+    Start,
+    Back,
+    Adjust(SetupField, i32),
+    RandomSeed,
+    NextTheme,
+    Resume,
+    Save,
+    MainMenu,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SetupField {
+    MapSize,
+    Seed,
+    Opponent,
+    StartingCoins,
+    Combat,
+    Clock,
+    Timeout,
+}
+
+// shows the current value of a setup field
+#[derive(Component)]
+pub struct SetupValue(pub SetupField);
+
+#[derive(Component)]
+pub struct ThemeName;
+
+// feedback like a missing save file
+#[derive(Component)]
+pub struct MenuMessage;
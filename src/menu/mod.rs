@@ -0,0 +1,63 @@
+use bevy::prelude::*;
+
+use crate::{
+    save::LoadedGame,
+    scenario::objectives::ScenarioOutcome,
+    state::{AppState, GameSetupSet, PauseState},
+    util::despawn_all,
+};
+
+use self::{
+    components::{MenuScreen, OutcomeBanner, PauseMenu},
+    systems::{
+        adjust_match_settings, color_buttons, cycle_themes, load_saved_game, navigate_menus,
+        restore_loaded_game, save_current_game, spawn_main_menu, spawn_outcome_banner,
+        spawn_pause_menu, spawn_scenarios_screen, spawn_settings_screen, spawn_setup_screen,
+        start_scenario, toggle_pause, update_setup_values, update_theme_name,
+    },
+};
+
+mod components;
+pub mod systems;
+
+// every screen outside of the game, and the
+// pause menu, built with bevy ui
+pub struct MenuPlugin;
+
+impl Plugin for MenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(AppState::MainMenu), spawn_main_menu)
+            .add_systems(OnEnter(AppState::Setup), spawn_setup_screen)
+            .add_systems(OnEnter(AppState::Settings), spawn_settings_screen)
+            .add_systems(OnEnter(AppState::Scenarios), spawn_scenarios_screen)
+            .add_systems(OnEnter(PauseState::Paused), spawn_pause_menu)
+            .add_systems(OnExit(AppState::MainMenu), despawn_all::<MenuScreen>)
+            .add_systems(OnExit(AppState::Setup), despawn_all::<MenuScreen>)
+            .add_systems(OnExit(AppState::Settings), despawn_all::<MenuScreen>)
+            .add_systems(OnExit(AppState::Scenarios), despawn_all::<MenuScreen>)
+            .add_systems(OnExit(AppState::InGame), despawn_all::<OutcomeBanner>)
+            .add_systems(OnExit(PauseState::Paused), despawn_all::<PauseMenu>)
+            .add_systems(
+                OnEnter(AppState::InGame),
+                restore_loaded_game
+                    .run_if(resource_exists::<LoadedGame>())
+                    .in_set(GameSetupSet::Restore),
+            )
+            .add_systems(
+                Update,
+                (
+                    color_buttons,
+                    navigate_menus,
+                    adjust_match_settings,
+                    update_setup_values,
+                    cycle_themes,
+                    update_theme_name,
+                    load_saved_game,
+                    start_scenario,
+                    spawn_outcome_banner.run_if(resource_added::<ScenarioOutcome>()),
+                    save_current_game.run_if(in_state(AppState::InGame)),
+                    toggle_pause.run_if(in_state(AppState::InGame)),
+                ),
+            );
+    }
+}
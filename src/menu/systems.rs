@@ -0,0 +1,578 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use bevy::{app::AppExit, prelude::*};
+
+use crate::{
+    board::resources::{TeamSettings, Themes},
+    clock::resources::TurnClocks,
+    player::resources::TurnOrder,
+    save::{LoadedGame, SaveGame, SAVE_PATH},
+    scenario::{
+        objectives::ScenarioOutcome,
+        resources::{CampaignRun, Profile, Scenario, ScenarioLibrary},
+    },
+    snapshot::{CaptureSnapshot, RestoreSnapshot},
+    state::{AppState, Difficulty, MatchSettings, Opponent, PauseState, TimeControl},
+    tutorial::TUTORIAL_SCENARIO,
+};
+
+use super::components::{
+    MenuButton, MenuMessage, MenuScreen, OutcomeBanner, PauseMenu, SetupField, SetupValue, ThemeName,
+};
+
+pub const MIN_MAP_RADIUS: u32 = 4;
+pub const MAX_MAP_RADIUS: u32 = 8;
+const MAX_STARTING_COINS: i32 = 100;
+
+const OPPONENTS: [Opponent; 4] = [
+    Opponent::Ai(Difficulty::Easy),
+    Opponent::Ai(Difficulty::Normal),
+    Opponent::Ai(Difficulty::Hard),
+    Opponent::Hotseat,
+];
+
+pub const FONT: &str = "fonts/arial.ttf";
+
+pub const BUTTON_COLOR: Color = Color::rgb(0.15, 0.15, 0.15);
+const HOVERED_BUTTON_COLOR: Color = Color::rgb(0.25, 0.25, 0.25);
+const PRESSED_BUTTON_COLOR: Color = Color::rgb(0.35, 0.35, 0.35);
+
+fn text_style(asset_server: &AssetServer, font_size: f32, color: Color) -> TextStyle {
+    TextStyle {
+        font: asset_server.load(FONT),
+        font_size,
+        color,
+    }
+}
+
+// a full window column, centered
+fn spawn_screen(commands: &mut Commands, background: Color) -> Entity {
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                width: Val::Percent(100.),
+                height: Val::Percent(100.),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                row_gap: Val::Px(12.),
+                ..default()
+            },
+            background_color: background.into(),
+            ..default()
+        })
+        .id()
+}
+
+fn spawn_title(parent: &mut ChildBuilder, asset_server: &AssetServer, title: &str) {
+    parent.spawn(TextBundle::from_section(
+        title,
+        text_style(asset_server, 60., Color::WHITE),
+    ));
+}
+
+fn spawn_button(
+    parent: &mut ChildBuilder,
+    asset_server: &AssetServer,
+    label: &str,
+    button: MenuButton,
+) {
+    parent
+        .spawn(ButtonBundle {
+            style: Style {
+                min_width: Val::Px(60.),
+                padding: UiRect::axes(Val::Px(20.), Val::Px(8.)),
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+            background_color: BUTTON_COLOR.into(),
+            ..default()
+        })
+        .insert(button)
+        .with_children(|button| {
+            button.spawn(TextBundle::from_section(
+                label,
+                text_style(asset_server, 32., Color::WHITE),
+            ));
+        });
+}
+
+fn spawn_message(parent: &mut ChildBuilder, asset_server: &AssetServer) {
+    parent
+        .spawn(TextBundle::from_section(
+            "",
+            text_style(asset_server, 24., Color::GOLD),
+        ))
+        .insert(MenuMessage);
+}
+
+pub fn spawn_main_menu(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let screen = spawn_screen(&mut commands, Color::NONE);
+
+    commands
+        .entity(screen)
+        .insert(MenuScreen)
+        .with_children(|parent| {
+            spawn_title(parent, &asset_server, "Hex Tactics");
+            spawn_button(parent, &asset_server, "New Game", MenuButton::NewGame);
+            spawn_button(parent, &asset_server, "Load", MenuButton::Load);
+            spawn_button(parent, &asset_server, "Scenarios", MenuButton::Scenarios);
+            spawn_button(parent, &asset_server, "Tutorial", MenuButton::Tutorial);
+            spawn_button(parent, &asset_server, "Settings", MenuButton::Settings);
+            spawn_button(parent, &asset_server, "Quit", MenuButton::Quit);
+            spawn_message(parent, &asset_server);
+        });
+}
+
+pub fn spawn_setup_screen(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let screen = spawn_screen(&mut commands, Color::NONE);
+
+    let rows = [
+        ("Map size", SetupField::MapSize),
+        ("Seed", SetupField::Seed),
+        ("Opponent", SetupField::Opponent),
+        ("Starting coins", SetupField::StartingCoins),
+        ("Combat", SetupField::Combat),
+        ("Time limit", SetupField::Clock),
+        ("Out of time", SetupField::Timeout),
+    ];
+
+    commands
+        .entity(screen)
+        .insert(MenuScreen)
+        .with_children(|parent| {
+            spawn_title(parent, &asset_server, "New Game");
+
+            for (label, field) in rows {
+                parent
+                    .spawn(NodeBundle {
+                        style: Style {
+                            align_items: AlignItems::Center,
+                            column_gap: Val::Px(12.),
+                            ..default()
+                        },
+                        ..default()
+                    })
+                    .with_children(|row| {
+                        row.spawn(TextBundle::from_section(
+                            format!("{label}:"),
+                            text_style(&asset_server, 32., Color::WHITE),
+                        ));
+                        spawn_button(row, &asset_server, "<", MenuButton::Adjust(field, -1));
+                        row.spawn(TextBundle::from_section(
+                            "",
+                            text_style(&asset_server, 32., Color::GOLD),
+                        ))
+                        .insert(SetupValue(field));
+                        spawn_button(row, &asset_server, ">", MenuButton::Adjust(field, 1));
+
+                        if field == SetupField::Seed {
+                            spawn_button(row, &asset_server, "Random", MenuButton::RandomSeed);
+                        }
+                    });
+            }
+
+            spawn_button(parent, &asset_server, "Start", MenuButton::Start);
+            spawn_button(parent, &asset_server, "Back", MenuButton::Back);
+        });
+}
+
+pub fn spawn_settings_screen(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let screen = spawn_screen(&mut commands, Color::NONE);
+
+    commands
+        .entity(screen)
+        .insert(MenuScreen)
+        .with_children(|parent| {
+            spawn_title(parent, &asset_server, "Settings");
+            parent
+                .spawn(TextBundle::from_section(
+                    "",
+                    text_style(&asset_server, 32., Color::WHITE),
+                ))
+                .insert(ThemeName);
+            spawn_button(parent, &asset_server, "Next Theme", MenuButton::NextTheme);
+            spawn_button(parent, &asset_server, "Back", MenuButton::Back);
+        });
+}
+
+pub fn spawn_scenarios_screen(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    library: Res<ScenarioLibrary>,
+    profile: Res<Profile>,
+) {
+    let screen = spawn_screen(&mut commands, Color::NONE);
+
+    commands
+        .entity(screen)
+        .insert(MenuScreen)
+        .with_children(|parent| {
+            spawn_title(parent, &asset_server, "Scenarios");
+
+            for (index, scenario) in library.scenarios.iter().enumerate() {
+                let label = if profile.completed_scenarios.contains(&scenario.name) {
+                    format!("{} (won)", scenario.name)
+                } else {
+                    scenario.name.clone()
+                };
+                spawn_button(parent, &asset_server, &label, MenuButton::PlayScenario(index));
+            }
+
+            if !library.campaigns.is_empty() {
+                spawn_title(parent, &asset_server, "Campaigns");
+            }
+
+            for (index, campaign) in library.campaigns.iter().enumerate() {
+                let completed = profile
+                    .campaigns
+                    .get(&campaign.name)
+                    .map_or(0, |progress| progress.completed);
+                let label = format!(
+                    "{} ({completed}/{})",
+                    campaign.name,
+                    campaign.scenarios.len()
+                );
+                spawn_button(parent, &asset_server, &label, MenuButton::PlayCampaign(index));
+            }
+
+            spawn_button(parent, &asset_server, "Back", MenuButton::Back);
+        });
+}
+
+// a campaign picks up where the profile left it
+pub fn start_scenario(
+    mut commands: Commands,
+    buttons: Query<(&Interaction, &MenuButton), Changed<Interaction>>,
+    library: Res<ScenarioLibrary>,
+    profile: Res<Profile>,
+    mut next_app_state: ResMut<NextState<AppState>>,
+) {
+    for button in pressed(&buttons) {
+        let (scenario, campaign_run) = match button {
+            MenuButton::PlayScenario(index) => (library.scenarios.get(index), None),
+            MenuButton::Tutorial => (library.scenario(TUTORIAL_SCENARIO), None),
+            MenuButton::PlayCampaign(index) => {
+                let Some(campaign) = library.campaigns.get(index) else {
+                    continue;
+                };
+
+                let run = profile.next_run(campaign);
+                (library.scenario(&campaign.scenarios[run.stage]), Some(run))
+            }
+            _ => continue,
+        };
+
+        let Some(scenario) = scenario else {
+            continue;
+        };
+
+        commands.insert_resource(scenario.clone());
+        match campaign_run {
+            Some(run) => commands.insert_resource(run),
+            None => commands.remove_resource::<CampaignRun>(),
+        }
+        next_app_state.set(AppState::InGame);
+    }
+}
+
+// continuing a campaign goes through the
+// scenarios screen, it starts the next stage,
+// after a match a new one can be set up
+pub fn spawn_outcome_banner(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    outcome: Res<ScenarioOutcome>,
+    game: (Option<Res<Scenario>>, Res<MatchSettings>, Res<TurnOrder>),
+) {
+    let (scenario, match_settings, turn_order) = game;
+    let screen = spawn_screen(&mut commands, Color::rgba(0., 0., 0., 0.5));
+
+    let mut title = match *outcome {
+        ScenarioOutcome::Victory => "Victory".to_string(),
+        ScenarioOutcome::Defeat => "Defeat".to_string(),
+    };
+
+    // every team is played here, so the winner is named
+    if scenario.is_none() && match_settings.opponent == Opponent::Hotseat {
+        if let [winner] = turn_order.standing()[..] {
+            title = format!("{winner:?} wins");
+        }
+    }
+
+    let next = match scenario {
+        Some(_) => ("Continue", MenuButton::Scenarios),
+        None => ("New Game", MenuButton::NewGame),
+    };
+
+    commands
+        .entity(screen)
+        .insert(OutcomeBanner)
+        .with_children(|parent| {
+            spawn_title(parent, &asset_server, &title);
+            spawn_button(parent, &asset_server, next.0, next.1);
+            spawn_button(parent, &asset_server, "Main Menu", MenuButton::MainMenu);
+        });
+}
+
+pub fn spawn_pause_menu(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let screen = spawn_screen(&mut commands, Color::rgba(0., 0., 0., 0.7));
+
+    commands
+        .entity(screen)
+        .insert(PauseMenu)
+        .with_children(|parent| {
+            spawn_title(parent, &asset_server, "Paused");
+            spawn_button(parent, &asset_server, "Resume", MenuButton::Resume);
+            spawn_button(parent, &asset_server, "Save", MenuButton::Save);
+            spawn_button(parent, &asset_server, "Main Menu", MenuButton::MainMenu);
+            spawn_button(parent, &asset_server, "Quit", MenuButton::Quit);
+            spawn_message(parent, &asset_server);
+        });
+}
+
+type ChangedButtons<'w, 's, 'a> =
+    Query<'w, 's, (&'a Interaction, &'a mut BackgroundColor), (Changed<Interaction>, With<Button>)>;
+
+pub fn color_buttons(mut buttons: ChangedButtons) {
+    for (interaction, mut color) in &mut buttons {
+        *color = match interaction {
+            Interaction::Pressed => PRESSED_BUTTON_COLOR,
+            Interaction::Hovered => HOVERED_BUTTON_COLOR,
+            Interaction::None => BUTTON_COLOR,
+        }
+        .into();
+    }
+}
+
+fn pressed(buttons: &Query<(&Interaction, &MenuButton), Changed<Interaction>>) -> Vec<MenuButton> {
+    buttons
+        .iter()
+        .filter(|(interaction, _)| **interaction == Interaction::Pressed)
+        .map(|(_, button)| *button)
+        .collect()
+}
+
+pub fn navigate_menus(
+    buttons: Query<(&Interaction, &MenuButton), Changed<Interaction>>,
+    mut next_app_state: ResMut<NextState<AppState>>,
+    mut next_pause_state: ResMut<NextState<PauseState>>,
+    mut exit: EventWriter<AppExit>,
+) {
+    for button in pressed(&buttons) {
+        match button {
+            MenuButton::NewGame => next_app_state.set(AppState::Setup),
+            MenuButton::Settings => next_app_state.set(AppState::Settings),
+            MenuButton::Scenarios => next_app_state.set(AppState::Scenarios),
+            MenuButton::Start => next_app_state.set(AppState::InGame),
+            MenuButton::Back | MenuButton::MainMenu => next_app_state.set(AppState::MainMenu),
+            MenuButton::Resume => next_pause_state.set(PauseState::Running),
+            MenuButton::Quit => exit.send(AppExit),
+            _ => {}
+        }
+    }
+}
+
+pub fn toggle_pause(
+    keys: Res<Input<KeyCode>>,
+    pause_state: Res<State<PauseState>>,
+    mut next_pause_state: ResMut<NextState<PauseState>>,
+) {
+    if !keys.just_released(KeyCode::Escape) {
+        return;
+    }
+
+    next_pause_state.set(match pause_state.get() {
+        PauseState::Running => PauseState::Paused,
+        PauseState::Paused => PauseState::Running,
+    });
+}
+
+pub fn adjust_match_settings(
+    buttons: Query<(&Interaction, &MenuButton), Changed<Interaction>>,
+    mut match_settings: ResMut<MatchSettings>,
+) {
+    for button in pressed(&buttons) {
+        match button {
+            MenuButton::Adjust(field, step) => adjust(&mut match_settings, field, step),
+            MenuButton::RandomSeed => {
+                match_settings.seed = SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .map(|time| time.as_nanos() as u64)
+                    .unwrap_or_default();
+            }
+            _ => {}
+        }
+    }
+}
+
+fn adjust(match_settings: &mut MatchSettings, field: SetupField, step: i32) {
+    match field {
+        SetupField::MapSize => {
+            match_settings.map_radius = match_settings
+                .map_radius
+                .saturating_add_signed(step)
+                .clamp(MIN_MAP_RADIUS, MAX_MAP_RADIUS);
+        }
+        SetupField::Seed => {
+            match_settings.seed = match_settings.seed.wrapping_add_signed(step as i64);
+        }
+        SetupField::Opponent => {
+            let current = OPPONENTS
+                .iter()
+                .position(|opponent| *opponent == match_settings.opponent)
+                .unwrap_or(0);
+            let next = (current as i32 + step).rem_euclid(OPPONENTS.len() as i32);
+
+            match_settings.opponent = OPPONENTS[next as usize];
+        }
+        SetupField::StartingCoins => {
+            match_settings.starting_coins =
+                (match_settings.starting_coins + step * 5).clamp(0, MAX_STARTING_COINS);
+        }
+        SetupField::Combat => {
+            match_settings.random_combat = !match_settings.random_combat;
+        }
+        SetupField::Clock => {
+            let presets = TimeControl::PRESETS;
+            let current = presets
+                .iter()
+                .position(|control| *control == match_settings.time_control)
+                .unwrap_or(0);
+            let next = (current as i32 + step).rem_euclid(presets.len() as i32);
+
+            match_settings.time_control = presets[next as usize];
+        }
+        SetupField::Timeout => {
+            match_settings.forfeit_on_time = !match_settings.forfeit_on_time;
+        }
+    }
+}
+
+pub fn update_setup_values(
+    match_settings: Res<MatchSettings>,
+    mut values: Query<(Ref<SetupValue>, &mut Text)>,
+) {
+    for (value, mut text) in &mut values {
+        if !match_settings.is_changed() && !value.is_added() {
+            continue;
+        }
+
+        text.sections[0].value = match value.0 {
+            SetupField::MapSize => match_settings.map_radius.to_string(),
+            SetupField::Seed => match_settings.seed.to_string(),
+            SetupField::Opponent => match match_settings.opponent {
+                Opponent::Ai(difficulty) => format!("AI ({difficulty:?})"),
+                Opponent::Hotseat => "Hotseat".to_string(),
+            },
+            SetupField::StartingCoins => match_settings.starting_coins.to_string(),
+            SetupField::Combat if match_settings.random_combat => "Random".to_string(),
+            SetupField::Combat => "Fixed".to_string(),
+            SetupField::Clock => match_settings.time_control.name(),
+            SetupField::Timeout if match_settings.forfeit_on_time => "Forfeit".to_string(),
+            SetupField::Timeout => "End turn".to_string(),
+        };
+    }
+}
+
+pub fn cycle_themes(
+    buttons: Query<(&Interaction, &MenuButton), Changed<Interaction>>,
+    mut themes: ResMut<Themes>,
+) {
+    for button in pressed(&buttons) {
+        if button == MenuButton::NextTheme {
+            themes.next();
+        }
+    }
+}
+
+pub fn update_theme_name(themes: Res<Themes>, mut names: Query<(Ref<ThemeName>, &mut Text)>) {
+    for (name, mut text) in &mut names {
+        if themes.is_changed() || name.is_added() {
+            text.sections[0].value = format!("Theme: {}", themes.current().name);
+        }
+    }
+}
+
+fn show_message(messages: &mut Query<&mut Text, With<MenuMessage>>, message: String) {
+    for mut text in messages {
+        text.sections[0].value = message.clone();
+    }
+}
+
+// the loaded game is restored once
+// the game's setup has built the board
+pub fn load_saved_game(
+    mut commands: Commands,
+    buttons: Query<(&Interaction, &MenuButton), Changed<Interaction>>,
+    mut match_settings: ResMut<MatchSettings>,
+    mut team_settings: ResMut<TeamSettings>,
+    mut next_app_state: ResMut<NextState<AppState>>,
+    mut messages: Query<&mut Text, With<MenuMessage>>,
+) {
+    if !pressed(&buttons).contains(&MenuButton::Load) {
+        return;
+    }
+
+    let save = match SaveGame::read(SAVE_PATH) {
+        Ok(save) => save,
+        Err(error) => {
+            warn!("could not load {SAVE_PATH}: {error}");
+            show_message(&mut messages, "No saved game to load".to_string());
+            return;
+        }
+    };
+
+    *match_settings = save.settings;
+    *team_settings = TeamSettings::new(save.snapshot.coins.len());
+    commands.insert_resource(LoadedGame(save.snapshot));
+    commands.insert_resource(save.clocks);
+
+    if let Some(scenario) = save.scenario {
+        commands.insert_resource(scenario);
+    }
+    if let Some(campaign_run) = save.campaign {
+        commands.insert_resource(campaign_run);
+    }
+    next_app_state.set(AppState::InGame);
+}
+
+pub fn restore_loaded_game(
+    mut commands: Commands,
+    loaded_game: Res<LoadedGame>,
+    mut restore: RestoreSnapshot,
+) {
+    restore.restore(loaded_game.0.clone());
+    commands.remove_resource::<LoadedGame>();
+}
+
+pub fn save_current_game(
+    buttons: Query<(&Interaction, &MenuButton), Changed<Interaction>>,
+    match_settings: Res<MatchSettings>,
+    snapshot: CaptureSnapshot,
+    scenario: (Option<Res<Scenario>>, Option<Res<CampaignRun>>, Res<TurnClocks>),
+    mut messages: Query<&mut Text, With<MenuMessage>>,
+) {
+    if !pressed(&buttons).contains(&MenuButton::Save) {
+        return;
+    }
+
+    let (scenario, campaign_run, clocks) = scenario;
+    let save = SaveGame {
+        settings: match_settings.clone(),
+        snapshot: snapshot.capture(),
+        scenario: scenario.map(|scenario| scenario.clone()),
+        campaign: campaign_run.map(|campaign_run| campaign_run.clone()),
+        clocks: clocks.clone(),
+    };
+
+    let message = match save.write(SAVE_PATH) {
+        Ok(()) => format!("Saved to {SAVE_PATH}"),
+        Err(error) => {
+            warn!("could not save to {SAVE_PATH}: {error}");
+            "Could not save the game".to_string()
+        }
+    };
+
+    show_message(&mut messages, message);
+}
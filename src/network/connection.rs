@@ -0,0 +1,69 @@
+use std::{
+    io::{self, ErrorKind, Read, Write},
+    net::TcpStream,
+};
+
+use super::protocol::NetMessage;
+
+// a non-blocking tcp stream carrying one
+// json encoded NetMessage per line
+pub struct Connection {
+    stream: TcpStream,
+    incoming: Vec<u8>,
+    outgoing: Vec<u8>,
+}
+
+impl Connection {
+    pub fn new(stream: TcpStream) -> io::Result<Self> {
+        stream.set_nonblocking(true)?;
+        stream.set_nodelay(true)?;
+
+        Ok(Connection {
+            stream,
+            incoming: Vec::new(),
+            outgoing: Vec::new(),
+        })
+    }
+
+    pub fn send(&mut self, message: &NetMessage) {
+        serde_json::to_writer(&mut self.outgoing, message)
+            .expect("messages are always serializable");
+        self.outgoing.push(b'\n');
+    }
+
+    // flushes what was sent and returns every
+    // complete message received so far, errors
+    // once the peer is gone
+    pub fn poll(&mut self) -> io::Result<Vec<NetMessage>> {
+        while !self.outgoing.is_empty() {
+            match self.stream.write(&self.outgoing) {
+                Ok(0) => return Err(ErrorKind::WriteZero.into()),
+                Ok(written) => {
+                    self.outgoing.drain(..written);
+                }
+                Err(e) if e.kind() == ErrorKind::WouldBlock => break,
+                Err(e) => return Err(e),
+            }
+        }
+
+        let mut buffer = [0; 4096];
+        loop {
+            match self.stream.read(&mut buffer) {
+                Ok(0) => return Err(ErrorKind::ConnectionAborted.into()),
+                Ok(read) => self.incoming.extend_from_slice(&buffer[..read]),
+                Err(e) if e.kind() == ErrorKind::WouldBlock => break,
+                Err(e) => return Err(e),
+            }
+        }
+
+        let mut messages = Vec::new();
+        while let Some(end) = self.incoming.iter().position(|b| *b == b'\n') {
+            let line = self.incoming.drain(..=end).collect::<Vec<_>>();
+            let message = serde_json::from_slice(&line[..end])
+                .map_err(|e| io::Error::new(ErrorKind::InvalidData, e))?;
+            messages.push(message);
+        }
+
+        Ok(messages)
+    }
+}
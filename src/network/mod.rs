@@ -0,0 +1,93 @@
+use bevy::prelude::*;
+
+use crate::{
+    board::components::Team,
+    command::CommandSet,
+    player::resources::PlayerTeam,
+    state::{AppState, GameSetupSet},
+};
+
+use self::{
+    resources::NetSession,
+    systems::{
+        assign_network_teams, connect_peers, receive_messages, restore_snapshot,
+        send_local_commands, send_snapshots, send_turn_hashes,
+    },
+};
+
+mod connection;
+pub mod protocol;
+pub mod resources;
+mod systems;
+
+#[cfg(test)]
+mod tests;
+
+pub const HOST_TEAM: Team = Team::Player(0);
+pub const JOIN_TEAM: Team = Team::Player(1);
+
+pub enum NetConfig {
+    Host(String),
+    Join(String),
+}
+
+impl NetConfig {
+    // `--host <address>` or `--join <address>`,
+    // without either the game is played locally
+    pub fn from_args() -> Option<Self> {
+        let args = std::env::args().collect::<Vec<_>>();
+
+        args.windows(2).find_map(|pair| match pair[0].as_str() {
+            "--host" => Some(NetConfig::Host(pair[1].clone())),
+            "--join" => Some(NetConfig::Join(pair[1].clone())),
+            _ => None,
+        })
+    }
+}
+
+// two peers playing in lockstep, the host plays
+// the first team and runs the ais, the joining
+// peer plays the second team
+pub struct NetworkPlugin {
+    pub config: NetConfig,
+}
+
+impl Plugin for NetworkPlugin {
+    fn build(&self, app: &mut App) {
+        let (session, team) = match &self.config {
+            NetConfig::Host(address) => (
+                NetSession::host(address).expect("could not host on the given address"),
+                HOST_TEAM,
+            ),
+            NetConfig::Join(address) => (
+                NetSession::join(address).expect("could not resolve the given address"),
+                JOIN_TEAM,
+            ),
+        };
+
+        app.insert_resource(session)
+            .insert_resource(PlayerTeam(team))
+            // after the match settings picked the controllers
+            .add_systems(
+                OnEnter(AppState::InGame),
+                assign_network_teams.in_set(GameSetupSet::Spawn),
+            )
+            .add_systems(
+                Update,
+                (
+                    (
+                        connect_peers,
+                        receive_messages,
+                        restore_snapshot,
+                        apply_deferred,
+                    )
+                        .chain()
+                        .in_set(CommandSet::Issue),
+                    (send_local_commands, send_turn_hashes, send_snapshots)
+                        .chain()
+                        .after(CommandSet::Apply)
+                        .run_if(in_state(AppState::InGame)),
+                ),
+            );
+    }
+}
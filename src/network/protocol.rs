@@ -0,0 +1,18 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{command::events::GameCommand, snapshot::GameSnapshot};
+
+// bump whenever a message or the
+// snapshot layout changes
+pub const PROTOCOL_VERSION: u32 = 5;
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub enum NetMessage {
+    Hello { version: u32 },
+    Command(GameCommand),
+    // sent after every finished turn,
+    // a mismatch means the peers desynced
+    TurnHash { turn: i32, hash: u64 },
+    RequestSnapshot,
+    Snapshot(GameSnapshot),
+}
@@ -0,0 +1,134 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    io,
+    net::{SocketAddr, TcpListener, ToSocketAddrs},
+    time::Instant,
+};
+
+use bevy::prelude::*;
+
+use crate::snapshot::GameSnapshot;
+
+use super::{connection::Connection, protocol::NetMessage};
+
+pub enum NetRole {
+    // the host owns the authoritative
+    // state and serves snapshots
+    Host { listener: TcpListener },
+    Join { address: SocketAddr },
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum NetStatus {
+    Waiting,
+    Connected,
+    Desynced { turn: i32 },
+}
+
+#[derive(Resource)]
+pub struct NetSession {
+    pub role: NetRole,
+    pub connection: Option<Connection>,
+    pub status: NetStatus,
+    // received but not yet handled
+    pub inbox: VecDeque<NetMessage>,
+    // turn hashes are kept until the
+    // other peer's hash for that turn arrives
+    pub local_hashes: HashMap<i32, u64>,
+    pub remote_hashes: HashMap<i32, u64>,
+    pub last_hashed_turn: i32,
+    pub last_desync: Option<i32>,
+    pub snapshot_requested: bool,
+    pub received_snapshot: Option<GameSnapshot>,
+    pub next_connect_attempt: Instant,
+}
+
+impl NetSession {
+    pub fn host(address: &str) -> io::Result<Self> {
+        let listener = TcpListener::bind(address)?;
+        listener.set_nonblocking(true)?;
+
+        Ok(Self::new(NetRole::Host { listener }))
+    }
+
+    pub fn join(address: &str) -> io::Result<Self> {
+        let address = address
+            .to_socket_addrs()?
+            .next()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no address to join"))?;
+
+        Ok(Self::new(NetRole::Join { address }))
+    }
+
+    fn new(role: NetRole) -> Self {
+        NetSession {
+            role,
+            connection: None,
+            status: NetStatus::Waiting,
+            inbox: VecDeque::new(),
+            local_hashes: HashMap::new(),
+            remote_hashes: HashMap::new(),
+            last_hashed_turn: 0,
+            last_desync: None,
+            snapshot_requested: false,
+            received_snapshot: None,
+            next_connect_attempt: Instant::now(),
+        }
+    }
+
+    pub fn is_host(&self) -> bool {
+        matches!(self.role, NetRole::Host { .. })
+    }
+
+    pub fn local_addr(&self) -> Option<SocketAddr> {
+        match &self.role {
+            NetRole::Host { listener } => listener.local_addr().ok(),
+            NetRole::Join { .. } => None,
+        }
+    }
+
+    pub fn send(&mut self, message: &NetMessage) {
+        if let Some(connection) = &mut self.connection {
+            connection.send(message);
+        }
+    }
+
+    pub fn disconnect(&mut self) {
+        self.connection = None;
+        self.status = NetStatus::Waiting;
+        self.inbox.clear();
+        self.forget_hashes();
+    }
+
+    pub fn forget_hashes(&mut self) {
+        self.local_hashes.clear();
+        self.remote_hashes.clear();
+    }
+
+    // compares both peers' hashes once they
+    // are known, the joiner asks the host
+    // for its state when they differ
+    pub fn check_turn(&mut self, turn: i32) {
+        let (Some(local), Some(remote)) = (
+            self.local_hashes.get(&turn).copied(),
+            self.remote_hashes.get(&turn).copied(),
+        ) else {
+            return;
+        };
+
+        self.local_hashes.remove(&turn);
+        self.remote_hashes.remove(&turn);
+
+        if local == remote {
+            return;
+        }
+
+        error!("desync on turn {turn}: {local:x} != {remote:x}");
+        self.status = NetStatus::Desynced { turn };
+        self.last_desync = Some(turn);
+
+        if !self.is_host() {
+            self.send(&NetMessage::RequestSnapshot);
+        }
+    }
+}
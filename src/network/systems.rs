@@ -0,0 +1,202 @@
+use std::{
+    net::TcpStream,
+    time::{Duration, Instant},
+};
+
+use bevy::prelude::*;
+
+use crate::{
+    board::resources::{Controller, TeamSettings},
+    command::events::{CommandEvent, CommandSource},
+    player::resources::PlayerTeam,
+    snapshot::{CaptureSnapshot, RestoreSnapshot},
+};
+
+use super::{
+    connection::Connection,
+    JOIN_TEAM,
+    protocol::{NetMessage, PROTOCOL_VERSION},
+    resources::{NetRole, NetSession, NetStatus},
+};
+
+const CONNECT_TIMEOUT: Duration = Duration::from_millis(100);
+const RECONNECT_DELAY: Duration = Duration::from_millis(500);
+
+// ais are run by the host, so on the
+// joining peer every other team is remote
+pub fn assign_network_teams(
+    session: Res<NetSession>,
+    player_team: Res<PlayerTeam>,
+    mut team_settings: ResMut<TeamSettings>,
+) {
+    for (team, config) in team_settings.teams().into_iter().zip(&mut team_settings.teams) {
+        config.controller = if team == player_team.0 {
+            Controller::Local
+        } else if session.is_host() && team != JOIN_TEAM {
+            Controller::Ai
+        } else {
+            Controller::Remote
+        };
+    }
+}
+
+pub fn connect_peers(mut session: ResMut<NetSession>) {
+    if session.connection.is_some() {
+        return;
+    }
+
+    let stream = match &session.role {
+        NetRole::Host { listener } => {
+            let Ok((stream, address)) = listener.accept() else {
+                return;
+            };
+
+            info!("peer connected from {address}");
+            stream
+        }
+        NetRole::Join { address } => {
+            if Instant::now() < session.next_connect_attempt {
+                return;
+            }
+
+            let Ok(stream) = TcpStream::connect_timeout(address, CONNECT_TIMEOUT) else {
+                session.next_connect_attempt = Instant::now() + RECONNECT_DELAY;
+                return;
+            };
+
+            info!("connected to host {address}");
+            stream
+        }
+    };
+
+    let Ok(connection) = Connection::new(stream) else {
+        return;
+    };
+
+    session.connection = Some(connection);
+    session.send(&NetMessage::Hello {
+        version: PROTOCOL_VERSION,
+    });
+}
+
+pub fn receive_messages(
+    mut session: ResMut<NetSession>,
+    mut game_commands: EventWriter<CommandEvent>,
+) {
+    let session = session.as_mut();
+    let Some(connection) = &mut session.connection else {
+        return;
+    };
+
+    match connection.poll() {
+        Ok(messages) => session.inbox.extend(messages),
+        Err(e) => {
+            warn!("lost connection to peer: {e}");
+            session.disconnect();
+            return;
+        }
+    }
+
+    let mut received_commands = false;
+    while let Some(message) = session.inbox.pop_front() {
+        match message {
+            NetMessage::Hello { version } => {
+                if version != PROTOCOL_VERSION {
+                    error!("peer uses protocol {version}, we use {PROTOCOL_VERSION}");
+                    session.disconnect();
+                    return;
+                }
+
+                session.status = NetStatus::Connected;
+                // whoever (re)joins resumes
+                // from the host's state
+                if session.is_host() {
+                    session.snapshot_requested = true;
+                }
+            }
+            NetMessage::Command(command) => {
+                game_commands.send(CommandEvent::remote(command));
+                received_commands = true;
+            }
+            NetMessage::TurnHash { turn, hash } => {
+                session.remote_hashes.insert(turn, hash);
+                session.check_turn(turn);
+            }
+            NetMessage::RequestSnapshot => {
+                if session.is_host() {
+                    session.snapshot_requested = true;
+                }
+            }
+            NetMessage::Snapshot(snapshot) => {
+                // commands from this frame are applied after
+                // the snapshot, so it has to wait a frame
+                if received_commands {
+                    session.inbox.push_front(NetMessage::Snapshot(snapshot));
+                    return;
+                }
+
+                if !session.is_host() {
+                    session.received_snapshot = Some(snapshot);
+                }
+                return;
+            }
+        }
+    }
+}
+
+pub fn restore_snapshot(mut session: ResMut<NetSession>, mut restore: RestoreSnapshot) {
+    let Some(snapshot) = session.received_snapshot.take() else {
+        return;
+    };
+
+    let turn = snapshot.turn;
+    restore.restore(snapshot);
+
+    session.last_hashed_turn = turn;
+    session.forget_hashes();
+    session.status = NetStatus::Connected;
+    info!("resumed from the host's snapshot of turn {turn}");
+}
+
+pub fn send_local_commands(
+    mut session: ResMut<NetSession>,
+    mut game_commands: EventReader<CommandEvent>,
+) {
+    for event in game_commands.iter() {
+        if event.source == CommandSource::Local {
+            session.send(&NetMessage::Command(event.command));
+        }
+    }
+}
+
+pub fn send_turn_hashes(mut session: ResMut<NetSession>, snapshot: CaptureSnapshot) {
+    let turn = snapshot.turn();
+    if turn <= session.last_hashed_turn {
+        return;
+    }
+
+    let hash = snapshot.capture().hash();
+
+    session.last_hashed_turn = turn;
+    if session.connection.is_none() {
+        return;
+    }
+
+    session.local_hashes.insert(turn, hash);
+    session.send(&NetMessage::TurnHash { turn, hash });
+    session.check_turn(turn);
+}
+
+pub fn send_snapshots(mut session: ResMut<NetSession>, snapshot: CaptureSnapshot) {
+    if !session.snapshot_requested || session.connection.is_none() {
+        return;
+    }
+
+    let snapshot = snapshot.capture();
+
+    session.snapshot_requested = false;
+    session.last_hashed_turn = snapshot.turn;
+    session.forget_hashes();
+    session.status = NetStatus::Connected;
+    session.send(&NetMessage::Snapshot(snapshot));
+}
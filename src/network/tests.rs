@@ -0,0 +1,211 @@
+use std::{thread, time::Duration};
+
+use bevy::prelude::*;
+use hexx::{hex, Hex};
+
+use crate::{
+    board::{
+        components::{HexTile, Team, TileVariant},
+        resources::TeamSettings,
+        HEX_RADIUS,
+    },
+    buildings::components::Building,
+    command::{
+        events::{CommandEvent, GameCommand},
+        rules::CombatRng,
+        CommandPlugin,
+    },
+    player::resources::{TeamCoins, TurnCounter, TurnOrder},
+    snapshot::GameSnapshot,
+    state::{AppState, StatePlugin},
+    units::components::{Unit, UnitDefault},
+};
+
+use super::{
+    resources::{NetSession, NetStatus},
+    NetConfig, NetworkPlugin,
+};
+
+fn peer(config: NetConfig) -> App {
+    let team_settings = TeamSettings::default();
+
+    let mut app = App::new();
+    app.insert_resource(State::new(AppState::InGame))
+        .insert_resource(TurnCounter(0))
+        .insert_resource(TurnOrder::new(team_settings.teams()))
+        .insert_resource(TeamCoins(vec![0; team_settings.teams.len()]))
+        .insert_resource(team_settings)
+        .add_plugins((StatePlugin, CommandPlugin, NetworkPlugin { config }));
+
+    for coordinate in Hex::ZERO.range(HEX_RADIUS as u32) {
+        app.world.spawn(HexTile {
+            coordinate,
+            variant: TileVariant::Land,
+            capture_progress: 0,
+            team: Team::Neutral,
+            resource: None,
+        });
+    }
+
+    for unit in [
+        Unit::new_default(UnitDefault::Knight, hex(-2, 4), Team::Player(0)),
+        Unit::new_default(UnitDefault::Archer, hex(-3, 0), Team::Player(1)),
+    ] {
+        app.world.spawn((Transform::default(), unit));
+    }
+
+    app
+}
+
+fn connected_peers() -> (App, App) {
+    let mut host = peer(NetConfig::Host("127.0.0.1:0".to_string()));
+    let address = host.world.resource::<NetSession>().local_addr().unwrap();
+    let mut join = peer(NetConfig::Join(address.to_string()));
+
+    pump(&mut host, &mut join, |host, join| {
+        status(host) == NetStatus::Connected && status(join) == NetStatus::Connected
+    });
+    settle(&mut host, &mut join);
+
+    (host, join)
+}
+
+fn status(app: &App) -> NetStatus {
+    app.world.resource::<NetSession>().status
+}
+
+fn snapshot(app: &mut App) -> GameSnapshot {
+    let turn = app.world.resource::<TurnCounter>().0;
+    let coins = app.world.resource::<TeamCoins>().0.clone();
+    let combat_rng = *app.world.resource::<CombatRng>();
+    let mut tiles = app.world.query::<&HexTile>();
+    let mut units = app.world.query::<&Unit>();
+    let mut buildings = app.world.query::<&Building>();
+
+    GameSnapshot::capture(
+        tiles.iter(&app.world),
+        units.iter(&app.world),
+        buildings.iter(&app.world),
+        turn,
+        app.world.resource::<TurnOrder>(),
+        &coins,
+        combat_rng,
+    )
+}
+
+fn pump(host: &mut App, join: &mut App, done: impl Fn(&App, &App) -> bool) {
+    for _ in 0..1000 {
+        host.update();
+        join.update();
+
+        if done(host, join) {
+            return;
+        }
+
+        thread::sleep(Duration::from_millis(2));
+    }
+
+    panic!("peers never reached the expected state");
+}
+
+fn settle(host: &mut App, join: &mut App) {
+    for _ in 0..20 {
+        host.update();
+        join.update();
+        thread::sleep(Duration::from_millis(2));
+    }
+}
+
+fn issue(app: &mut App, command: GameCommand) {
+    app.world.send_event(CommandEvent::local(command));
+}
+
+fn play_round(host: &mut App, join: &mut App) {
+    let turn = host.world.resource::<TurnCounter>().0;
+
+    issue(
+        host,
+        GameCommand::Move {
+            from: hex(-2, 4),
+            to: hex(-1, 4),
+        },
+    );
+    issue(host, GameCommand::EndTurn);
+    pump(host, join, |_, join| {
+        join.world.resource::<TurnOrder>().current == 1
+    });
+
+    issue(
+        join,
+        GameCommand::Move {
+            from: hex(-3, 0),
+            to: hex(-3, 1),
+        },
+    );
+    issue(join, GameCommand::EndTurn);
+    pump(host, join, |host, _| {
+        host.world.resource::<TurnCounter>().0 == turn + 1
+    });
+    settle(host, join);
+}
+
+#[test]
+fn commands_are_applied_in_lockstep() {
+    let (mut host, mut join) = connected_peers();
+
+    play_round(&mut host, &mut join);
+
+    let host_snapshot = snapshot(&mut host);
+    assert_eq!(host_snapshot, snapshot(&mut join));
+    assert!(host_snapshot.units.iter().any(|u| u.position == hex(-1, 4)));
+    assert!(host_snapshot.units.iter().any(|u| u.position == hex(-3, 1)));
+    assert_eq!(host_snapshot.turn, 1);
+
+    for app in [&host, &join] {
+        assert_eq!(app.world.resource::<NetSession>().last_desync, None);
+    }
+}
+
+#[test]
+fn desync_is_detected_and_resynced() {
+    let (mut host, mut join) = connected_peers();
+
+    let mut tiles = join.world.query::<&mut HexTile>();
+    for mut tile in tiles.iter_mut(&mut join.world) {
+        if tile.coordinate == hex(5, 0) {
+            tile.team = Team::Player(1);
+        }
+    }
+
+    play_round(&mut host, &mut join);
+    pump(&mut host, &mut join, |_, join| {
+        status(join) == NetStatus::Connected
+    });
+    settle(&mut host, &mut join);
+
+    assert_eq!(join.world.resource::<NetSession>().last_desync, Some(1));
+    assert_eq!(snapshot(&mut host), snapshot(&mut join));
+}
+
+#[test]
+fn reconnecting_resumes_from_snapshot() {
+    let (mut host, mut join) = connected_peers();
+
+    join.world.resource_mut::<NetSession>().disconnect();
+    issue(
+        &mut host,
+        GameCommand::Move {
+            from: hex(-2, 4),
+            to: hex(-1, 4),
+        },
+    );
+    issue(&mut host, GameCommand::EndTurn);
+
+    pump(&mut host, &mut join, |_, join| {
+        join.world.resource::<TurnOrder>().current == 1
+    });
+    settle(&mut host, &mut join);
+
+    assert_eq!(status(&join), NetStatus::Connected);
+    assert_eq!(snapshot(&mut host), snapshot(&mut join));
+}
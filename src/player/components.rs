@@ -0,0 +1,9 @@
+use bevy::prelude::*;
+
+// shown after the first Space while units can still act
+#[derive(Component)]
+pub struct EndTurnPromptText;
+
+// what the last round brought, at the start of our turn
+#[derive(Component)]
+pub struct TurnSummaryText;
@@ -0,0 +1,131 @@
+use crate::{
+    board::components::{HexTile, Team, TileResource},
+    buildings::components::{Building, BuildingKind},
+};
+
+// every team gets these each round
+pub const BASE_INCOME: i32 = 2;
+// what an owned tile without a resource pays
+pub const LAND_INCOME: i32 = 1;
+
+// what a team earns at the end of a round
+#[derive(Clone, Debug, PartialEq)]
+pub struct IncomeBreakdown {
+    pub land_tiles: i32,
+    // (resource, tiles owned) in a fixed order
+    pub resources: Vec<(TileResource, i32)>,
+    // (building, count) for the ones that pay
+    pub buildings: Vec<(BuildingKind, i32)>,
+}
+
+impl IncomeBreakdown {
+    pub fn for_team<'a>(
+        team: Team,
+        tiles: impl Iterator<Item = &'a HexTile>,
+        buildings: impl Iterator<Item = &'a Building>,
+    ) -> Self {
+        let mut land_tiles = 0;
+        let mut resources = [TileResource::GoldMine, TileResource::Farm, TileResource::Shrine]
+            .map(|resource| (resource, 0));
+
+        for tile in tiles.filter(|tile| tile.team == team) {
+            match tile.resource {
+                Some(resource) => {
+                    if let Some((_, count)) = resources.iter_mut().find(|(r, _)| *r == resource) {
+                        *count += 1;
+                    }
+                }
+                None => land_tiles += 1,
+            }
+        }
+
+        let mut paying_buildings = BuildingKind::ALL
+            .into_iter()
+            .filter(|kind| kind.income() > 0)
+            .map(|kind| (kind, 0))
+            .collect::<Vec<_>>();
+
+        for building in buildings.filter(|b| b.team == team && b.active()) {
+            if let Some((_, count)) = paying_buildings.iter_mut().find(|(k, _)| *k == building.kind) {
+                *count += 1;
+            }
+        }
+
+        IncomeBreakdown {
+            land_tiles,
+            resources: resources.into_iter().filter(|(_, count)| *count > 0).collect(),
+            buildings: paying_buildings.into_iter().filter(|(_, count)| *count > 0).collect(),
+        }
+    }
+
+    // (label, coins) for every source of income
+    pub fn items(&self) -> Vec<(String, i32)> {
+        let mut items = vec![
+            ("Base".to_string(), BASE_INCOME),
+            (format!("Land x{}", self.land_tiles), self.land_tiles * LAND_INCOME),
+        ];
+
+        for (resource, count) in &self.resources {
+            items.push((format!("{} x{count}", resource.name()), count * resource.income()));
+        }
+
+        for (building, count) in &self.buildings {
+            items.push((format!("{} x{count}", building.name()), count * building.income()));
+        }
+
+        items
+    }
+
+    pub fn total(&self) -> i32 {
+        self.items().iter().map(|(_, coins)| coins).sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use hexx::hex;
+
+    use crate::{board::components::TileVariant, buildings::components::BuildingKind};
+
+    use super::*;
+
+    #[test]
+    fn resources_replace_land_income() {
+        let tile = |x, team, resource| HexTile {
+            coordinate: hex(x, 0),
+            variant: TileVariant::Land,
+            capture_progress: 3,
+            team,
+            resource,
+        };
+        let team = Team::Player(0);
+        let tiles = [
+            tile(0, team, None),
+            tile(1, team, None),
+            tile(2, team, Some(TileResource::GoldMine)),
+            tile(3, team, Some(TileResource::Farm)),
+            tile(4, team, Some(TileResource::Farm)),
+            tile(5, Team::Player(1), Some(TileResource::Shrine)),
+        ];
+
+        let buildings = [
+            Building::new(BuildingKind::Market, hex(0, 0), team),
+            Building::new(BuildingKind::Barracks, hex(1, 0), team),
+            Building::new(BuildingKind::Market, hex(5, 0), Team::Player(1)),
+        ];
+
+        let income = IncomeBreakdown::for_team(team, tiles.iter(), buildings.iter());
+
+        assert_eq!(
+            income.items(),
+            [
+                ("Base".to_string(), 2),
+                ("Land x2".to_string(), 2),
+                ("Gold mine x1".to_string(), 3),
+                ("Farm x2".to_string(), 4),
+                ("Market x1".to_string(), 2),
+            ]
+        );
+        assert_eq!(income.total(), 13);
+    }
+}
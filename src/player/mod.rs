@@ -1,20 +1,70 @@
 use bevy::prelude::*;
 
+use crate::{
+    animation::systems::animations_finished,
+    board::components::Team,
+    command::CommandSet,
+    state::{game_running, game_undecided, AppState, GameSetupSet},
+    util::despawn_all,
+};
+
 use self::{
-    resources::{AllyCapital, PlayerCoins, TurnCounter},
-    systems::{pass_turn, place_ally_capital}
+    components::{EndTurnPromptText, TurnSummaryText},
+    resources::{EndTurnPrompt, PlayerTeam, TurnCounter, TurnSummaries},
+    systems::{
+        dismiss_turn_summary, follow_local_team, pass_turn, place_player_capital, player_turn,
+        reset_turn_summaries, setup_turns, spawn_turn_texts, summarize_turns,
+        update_end_turn_prompt, update_turn_summary,
+    },
 };
 
+pub mod components;
+pub mod income;
 pub mod resources;
-mod systems;
+pub mod systems;
 
 pub struct PlayerPlugin;
 
 impl Plugin for PlayerPlugin {
     fn build(&self, app: &mut App) {
-        app.insert_resource(AllyCapital { position: None })
-            .insert_resource(TurnCounter(0))
-            .insert_resource(PlayerCoins(10))
-            .add_systems(Update, (place_ally_capital, pass_turn));
+        app.insert_resource(TurnCounter(0))
+            .insert_resource(PlayerTeam(Team::Player(0)))
+            .init_resource::<EndTurnPrompt>()
+            .init_resource::<TurnSummaries>()
+            .add_systems(
+                OnEnter(AppState::InGame),
+                (
+                    (setup_turns, reset_turn_summaries).in_set(GameSetupSet::Resources),
+                    spawn_turn_texts.in_set(GameSetupSet::Spawn),
+                ),
+            )
+            .add_systems(
+                OnExit(AppState::InGame),
+                (despawn_all::<EndTurnPromptText>, despawn_all::<TurnSummaryText>),
+            )
+            .add_systems(
+                Update,
+                (
+                    // income is never missed while paused
+                    summarize_turns
+                        .after(CommandSet::Apply)
+                        .run_if(in_state(AppState::InGame)),
+                    (dismiss_turn_summary, update_end_turn_prompt, update_turn_summary)
+                        .chain()
+                        .after(summarize_turns)
+                        .run_if(game_running),
+                ),
+            )
+            .add_systems(
+                Update,
+                (
+                    follow_local_team.run_if(game_running),
+                    (place_player_capital, pass_turn)
+                        .run_if(game_running.and_then(player_turn).and_then(animations_finished))
+                        .run_if(game_undecided),
+                )
+                    .chain()
+                    .in_set(CommandSet::Issue),
+            );
     }
 }
@@ -1,13 +1,213 @@
+use std::collections::{HashMap, HashSet};
+
 use bevy::prelude::*;
 use hexx::Hex;
 
+use crate::board::components::Team;
+
+pub const STARTING_COINS: i32 = 10;
+
+#[derive(Resource)]
+pub struct TurnCounter(pub i32);
+
+// indexed by team id
 #[derive(Resource)]
-pub struct AllyCapital {
-    pub position: Option<Hex>,
+pub struct TeamCoins(pub Vec<i32>);
+
+impl TeamCoins {
+    pub fn get(&self, team: Team) -> i32 {
+        team.id().and_then(|id| self.0.get(id)).copied().unwrap_or(0)
+    }
+
+    pub fn add(&mut self, team: Team, amount: i32) {
+        if let Some(coins) = team.id().and_then(|id| self.0.get_mut(id)) {
+            *coins += amount;
+        }
+    }
+
+    // returns false and takes nothing
+    // when the team can't afford it
+    pub fn spend(&mut self, team: Team, amount: i32) -> bool {
+        let Some(coins) = team.id().and_then(|id| self.0.get_mut(id)) else {
+            return false;
+        };
+
+        if *coins < amount {
+            return false;
+        }
+
+        *coins -= amount;
+        true
+    }
 }
 
+// the team controlled from this machine
 #[derive(Resource)]
-pub struct TurnCounter(pub i32);
+pub struct PlayerTeam(pub Team);
+
+// teams take their turns in this order,
+// a new turn (TurnCounter) starts once
+// every team has ended theirs
+#[derive(Resource)]
+pub struct TurnOrder {
+    pub teams: Vec<Team>,
+    pub current: usize,
+    // out of the game, their turns are skipped
+    pub forfeited: Vec<Team>,
+}
+
+impl TurnOrder {
+    pub fn new(teams: Vec<Team>) -> Self {
+        TurnOrder {
+            teams,
+            current: 0,
+            forfeited: Vec::new(),
+        }
+    }
+
+    pub fn active(&self) -> Team {
+        self.teams[self.current]
+    }
+
+    // returns true when the last team
+    // in the order ended their turn
+    pub fn advance(&mut self) -> bool {
+        let mut wrapped = false;
+        for _ in 0..self.teams.len() {
+            self.current = (self.current + 1) % self.teams.len();
+            wrapped |= self.current == 0;
+
+            if !self.forfeited.contains(&self.active()) {
+                break;
+            }
+        }
+
+        wrapped
+    }
+
+    pub fn forfeit(&mut self, team: Team) {
+        if !self.forfeited.contains(&team) {
+            self.forfeited.push(team);
+        }
+    }
+
+    // the teams still playing
+    pub fn standing(&self) -> Vec<Team> {
+        self.teams
+            .iter()
+            .filter(|team| !self.forfeited.contains(team))
+            .copied()
+            .collect()
+    }
+}
 
+// asks before ending a turn while units can still
+// act, unless started with --no-end-turn-prompt
 #[derive(Resource)]
-pub struct PlayerCoins(pub i32);
+pub struct EndTurnPrompt {
+    pub enabled: bool,
+    // how many units could still act when Space was
+    // pressed once, the next press ends the turn
+    pub waiting: Option<usize>,
+}
+
+impl Default for EndTurnPrompt {
+    fn default() -> Self {
+        EndTurnPrompt {
+            enabled: true,
+            waiting: None,
+        }
+    }
+}
+
+impl EndTurnPrompt {
+    pub fn from_args() -> Self {
+        EndTurnPrompt {
+            enabled: !std::env::args().any(|arg| arg == "--no-end-turn-prompt"),
+            waiting: None,
+        }
+    }
+}
+
+// what changed for a team since its last turn started
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct TurnSummary {
+    pub income: i32,
+    pub gained: usize,
+    pub lost: usize,
+}
+
+#[derive(Resource, Default)]
+pub struct TurnSummaries {
+    // (TurnCounter, active team) when last checked
+    pub last_turn: Option<(i32, Team)>,
+    // the tiles each team held when its last turn started
+    pub tiles: HashMap<Team, HashSet<Hex>>,
+    // coins each team earned since
+    pub income: HashMap<Team, i32>,
+    // for a team playing on this machine, until clicked away
+    pub shown: Option<TurnSummary>,
+}
+
+impl TurnSummaries {
+    pub fn earn(&mut self, team: Team, coins: i32) {
+        *self.income.entry(team).or_default() += coins;
+    }
+
+    // None on the team's first turn
+    pub fn start_turn(&mut self, team: Team, tiles: HashSet<Hex>) -> Option<TurnSummary> {
+        let income = self.income.remove(&team).unwrap_or(0);
+        let before = self.tiles.insert(team, tiles)?;
+        let now = &self.tiles[&team];
+
+        Some(TurnSummary {
+            income,
+            gained: now.difference(&before).count(),
+            lost: before.difference(now).count(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use hexx::hex;
+
+    use super::*;
+
+    #[test]
+    fn summaries_cover_everything_since_the_last_turn() {
+        let mut summaries = TurnSummaries::default();
+        let team = Team::Player(0);
+
+        summaries.earn(team, 3);
+        assert_eq!(summaries.start_turn(team, HashSet::from([hex(0, 0), hex(1, 0)])), None);
+
+        summaries.earn(team, 4);
+        summaries.earn(team, 1);
+        summaries.earn(Team::Player(1), 9);
+        let summary = summaries.start_turn(team, HashSet::from([hex(0, 0), hex(0, 1), hex(1, 1)]));
+        assert_eq!(
+            summary,
+            Some(TurnSummary {
+                income: 5,
+                gained: 2,
+                lost: 1,
+            })
+        );
+    }
+
+    #[test]
+    fn forfeited_teams_are_skipped() {
+        let teams = (0..3).map(Team::Player).collect::<Vec<_>>();
+        let mut turn_order = TurnOrder::new(teams.clone());
+
+        turn_order.forfeit(teams[1]);
+        assert!(!turn_order.advance());
+        assert_eq!(turn_order.active(), teams[2]);
+
+        turn_order.forfeit(teams[0]);
+        assert!(turn_order.advance());
+        assert_eq!(turn_order.active(), teams[2]);
+        assert_eq!(turn_order.standing(), [teams[2]]);
+    }
+}
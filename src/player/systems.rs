@@ -2,140 +2,249 @@ use bevy::prelude::*;
 
 
 use crate::{
-    board::components::{HexTile, Team, TileVariant},
-    units::components::Unit, util::cursor_to_hex,
+    board::{
+        components::{HexTile, Team, TileVariant},
+        resources::{BoardIndex, Controller, TeamSettings},
+    },
+    command::events::{CommandEvent, GameCommand, GameEvent, GameEventKind},
+    menu::systems::FONT,
+    state::MatchSettings,
+    units::components::{needs_orders, Order, Unit},
+    util::BoardCursor,
 };
 
-use super::resources::{AllyCapital, PlayerCoins, TurnCounter};
+use super::{
+    components::{EndTurnPromptText, TurnSummaryText},
+    resources::{EndTurnPrompt, PlayerTeam, TeamCoins, TurnCounter, TurnOrder, TurnSummaries},
+};
+
+pub fn setup_turns(
+    mut commands: Commands,
+    team_settings: Res<TeamSettings>,
+    match_settings: Res<MatchSettings>,
+) {
+    commands.insert_resource(TurnCounter(0));
+    commands.insert_resource(TurnOrder::new(team_settings.teams()));
+    commands.insert_resource(TeamCoins(vec![
+        match_settings.starting_coins;
+        team_settings.teams.len()
+    ]));
+}
+
+// in hotseat games every local team
+// takes over the input on its turn
+pub fn follow_local_team(
+    turn_order: Res<TurnOrder>,
+    team_settings: Res<TeamSettings>,
+    mut player_team: ResMut<PlayerTeam>,
+) {
+    let active = turn_order.active();
+
+    if team_settings.controller(active) == Some(Controller::Local) && player_team.0 != active {
+        player_team.0 = active;
+    }
+}
+
+pub fn player_turn(turn_order: Res<TurnOrder>, player_team: Res<PlayerTeam>) -> bool {
+    turn_order.active() == player_team.0
+}
 
-pub fn place_ally_capital(
-    mut ally_capital: ResMut<AllyCapital>,
+pub fn place_player_capital(
     buttons: Res<Input<MouseButton>>,
-    windows: Query<&Window>,
-    mut hexes: Query<&mut HexTile>,
+    cursor: BoardCursor,
+    hexes: Query<&HexTile>,
+    board_index: Res<BoardIndex>,
+    player_team: Res<PlayerTeam>,
+    mut game_commands: EventWriter<CommandEvent>,
 ) {
-    if ally_capital.position.is_some() {
+    if hexes
+        .iter()
+        .any(|hex| hex.team == player_team.0 && hex.variant == TileVariant::Capital)
+    {
         return;
     }
     if !buttons.just_pressed(MouseButton::Left) {
         return;
     }
 
-    let Some(hovered_hex) = cursor_to_hex(windows) else {
+    let Some(hovered_hex) = cursor.hex() else {
         return;
     };
 
-    let claim_tiles = hovered_hex.all_neighbors();
-
-    for hex in &hexes {
-        if !claim_tiles.contains(&hex.coordinate) {
-            continue;
-        }
+    let claim_tiles = hovered_hex
+        .all_neighbors()
+        .iter()
+        .filter_map(|hex| board_index.tile(*hex))
+        .collect::<Vec<_>>();
 
-        if hex.team == Team::Enemy {
-            return;
-        }
+    if hexes
+        .iter_many(&claim_tiles)
+        .any(|hex| hex.team != Team::Neutral && hex.team != player_team.0)
+    {
+        return;
     }
 
-    for mut hex in &mut hexes {
-        if claim_tiles.contains(&hex.coordinate) {
-            hex.team = Team::Ally;
-        }
-
-        if hex.coordinate == hovered_hex {
-            hex.team = Team::Ally;
-            hex.variant = TileVariant::Capital;
-            ally_capital.position = Some(hovered_hex);
-        }
-    }
+    game_commands.send(CommandEvent::local(GameCommand::PlaceCapital { hex: hovered_hex }));
 }
 
+// with units left to act the first Space only asks and the
+// second ends the turn, anything a unit does asks again
 pub fn pass_turn(
-    mut turn_counter: ResMut<TurnCounter>,
-    mut player_coin_count: ResMut<PlayerCoins>,
-    mut units: Query<&mut Unit>,
-    hex_tiles: Query<&mut HexTile>,
     keys: Res<Input<KeyCode>>,
+    mut prompt: ResMut<EndTurnPrompt>,
+    player_team: Res<PlayerTeam>,
+    units: Query<(Ref<Unit>, Option<&Order>)>,
+    mut game_commands: EventWriter<CommandEvent>,
 ) {
+    if prompt.waiting.is_some() && units.iter().any(|(unit, _)| unit.is_changed()) {
+        prompt.waiting = None;
+    }
+
     if !keys.just_released(KeyCode::Space) {
         return;
     }
 
-    for hex_tile in &hex_tiles {
-        if hex_tile.team == Team::Ally {
-            player_coin_count.0 += 1;
-        }
-    }
+    let idle = units
+        .iter()
+        .filter(|(unit, order)| unit.team == player_team.0 && needs_orders(unit, *order))
+        .count();
 
-    for mut unit in &mut units {
-        unit.new_turn();
+    if prompt.enabled && idle > 0 && prompt.waiting.is_none() {
+        prompt.waiting = Some(idle);
+        return;
     }
 
-    // give the player a garanteed
-    // 2 coins per turn
-    player_coin_count.0 += 2;
+    prompt.waiting = None;
+    game_commands.send(CommandEvent::local(GameCommand::EndTurn));
+}
+
+pub fn reset_turn_summaries(mut commands: Commands, mut prompt: ResMut<EndTurnPrompt>) {
+    commands.insert_resource(TurnSummaries::default());
+    prompt.waiting = None;
+}
 
-    turn_counter.0 += 1;
+pub fn spawn_turn_texts(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let text_style = |font_size: f32, color: Color| TextStyle {
+        font: asset_server.load(FONT),
+        font_size,
+        color,
+    };
 
-    update_capture_progress(hex_tiles, units.to_readonly());
+    commands.spawn((
+        TextBundle::from_section("", text_style(30., Color::GOLD))
+            .with_style(Style {
+                position_type: PositionType::Absolute,
+                left: Val::Percent(35.),
+                top: Val::Percent(40.),
+                ..default()
+            })
+            .with_background_color(Color::rgba(0., 0., 0., 0.6)),
+        EndTurnPromptText,
+    ));
+
+    commands.spawn((
+        TextBundle::from_section("", text_style(26., Color::WHITE))
+            .with_style(Style {
+                position_type: PositionType::Absolute,
+                left: Val::Percent(35.),
+                top: Val::Percent(20.),
+                padding: UiRect::all(Val::Px(8.)),
+                ..default()
+            })
+            .with_background_color(Color::rgba(0., 0., 0., 0.6)),
+        TurnSummaryText,
+    ));
 }
 
-fn update_capture_progress(mut tiles: Query<&mut HexTile>, units: Query<&Unit>) {
-    let progress_capture_tiles = tiles
-        .iter()
-        .filter_map(|hex_tile| {
-            let Some(unit) = units.iter().find(|u| u.position == hex_tile.coordinate) else {
-            return None;
-        };
+// earnings are counted for every team and the tiles
+// compared whenever one starts its turn, only the
+// teams playing on this machine see the summary
+pub fn summarize_turns(
+    mut game_events: EventReader<GameEvent>,
+    mut summaries: ResMut<TurnSummaries>,
+    turn: (Res<TurnCounter>, Res<TurnOrder>, Res<TeamSettings>),
+    hexes: Query<&HexTile>,
+    mut prompt: ResMut<EndTurnPrompt>,
+) {
+    let (turn_counter, turn_order, team_settings) = turn;
 
-            if unit.team == hex_tile.team {
-                return None;
-            }
+    for event in game_events.iter() {
+        if let GameEventKind::Income { coins } = event.kind {
+            summaries.earn(event.team, coins);
+        }
+    }
 
-            let neighbors = hex_tile.coordinate.all_neighbors();
-            if tiles.iter().any(|neighbor| {
-                neighbors.contains(&neighbor.coordinate) && unit.team == neighbor.team
-            }) {
-                return Some((hex_tile.coordinate, unit.team));
-            }
+    let team = turn_order.active();
+    if summaries.last_turn == Some((turn_counter.0, team)) {
+        return;
+    }
+    summaries.last_turn = Some((turn_counter.0, team));
+    prompt.waiting = None;
 
-            None
-        })
-        .collect::<Vec<_>>();
+    let tiles = hexes
+        .iter()
+        .filter(|hex| hex.team == team)
+        .map(|hex| hex.coordinate)
+        .collect();
+    let summary = summaries.start_turn(team, tiles);
+    summaries.shown = summary.filter(|_| team_settings.controller(team) == Some(Controller::Local));
+}
 
-    for mut tile in &mut tiles {
-        let mut capture_team = None;
-        for (coord, team) in &progress_capture_tiles {
-            if tile.coordinate != *coord {
-                continue;
-            }
+pub fn dismiss_turn_summary(
+    buttons: Res<Input<MouseButton>>,
+    mut summaries: ResMut<TurnSummaries>,
+) {
+    if summaries.shown.is_some() && buttons.get_just_released().next().is_some() {
+        summaries.shown = None;
+    }
+}
 
-            capture_team = Some(*team);
-            break;
-        }
+pub fn update_end_turn_prompt(
+    prompt: Res<EndTurnPrompt>,
+    mut prompt_text: Query<(&mut Text, &mut Visibility), With<EndTurnPromptText>>,
+) {
+    if !prompt.is_changed() {
+        return;
+    }
 
-        match (capture_team, tile.team) {
-            (None, Team::Neutral) => {
-                move_toward(&mut tile.capture_progress, 0, 1);
-            }
-            (None, _) => {
-                move_toward(&mut tile.capture_progress, 3, 1);
-            }
-            (Some(team), Team::Neutral) => {
-                if move_toward(&mut tile.capture_progress, 3, 1) {
-                    tile.team = team
-                }
-            }
-            (Some(_), _) => {
-                if move_toward(&mut tile.capture_progress, 0, 1) {
-                    tile.team = Team::Neutral
-                }
-            }
+    for (mut text, mut visibility) in &mut prompt_text {
+        let Some(idle) = prompt.waiting else {
+            *visibility = Visibility::Hidden;
+            continue;
+        };
+
+        *visibility = Visibility::Inherited;
+        text.sections[0].value = match idle {
+            1 => "1 unit can still act, Space again ends the turn".to_string(),
+            _ => format!("{idle} units can still act, Space again ends the turn"),
         };
     }
 }
 
-fn move_toward(value: &mut i32, target: i32, step: i32) -> bool {
-    *value = (*value - step).max((*value + step).min(target));
-    *value == target
+pub fn update_turn_summary(
+    summaries: Res<TurnSummaries>,
+    turn: (Res<TurnCounter>, Res<TeamCoins>, Res<PlayerTeam>),
+    mut summary_text: Query<(&mut Text, &mut Visibility), With<TurnSummaryText>>,
+) {
+    let (turn_counter, team_coins, player_team) = turn;
+    if !summaries.is_changed() {
+        return;
+    }
+
+    for (mut text, mut visibility) in &mut summary_text {
+        let Some(summary) = summaries.shown else {
+            *visibility = Visibility::Hidden;
+            continue;
+        };
+
+        *visibility = Visibility::Inherited;
+        text.sections[0].value = format!(
+            "Turn {}\nIncome: +{}, now {} coins\nTiles gained: {}, lost: {}",
+            turn_counter.0,
+            summary.income,
+            team_coins.get(player_team.0),
+            summary.gained,
+            summary.lost
+        );
+    }
 }
@@ -0,0 +1,47 @@
+use std::{fs, io, path::Path};
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    clock::resources::TurnClocks,
+    scenario::resources::{CampaignRun, Scenario},
+    snapshot::GameSnapshot,
+    state::MatchSettings,
+};
+
+pub const SAVE_PATH: &str = "saves/quicksave.json";
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct SaveGame {
+    pub settings: MatchSettings,
+    pub snapshot: GameSnapshot,
+    #[serde(default)]
+    pub scenario: Option<Scenario>,
+    #[serde(default)]
+    pub campaign: Option<CampaignRun>,
+    #[serde(default)]
+    pub clocks: TurnClocks,
+}
+
+// replaces the fresh game once
+// the game's setup has spawned it
+#[derive(Resource)]
+pub struct LoadedGame(pub GameSnapshot);
+
+impl SaveGame {
+    pub fn write(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let path = path.as_ref();
+        if let Some(directory) = path.parent() {
+            fs::create_dir_all(directory)?;
+        }
+
+        let json = serde_json::to_string_pretty(self)?;
+        fs::write(path, json)
+    }
+
+    pub fn read(path: impl AsRef<Path>) -> io::Result<Self> {
+        let json = fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&json)?)
+    }
+}
@@ -0,0 +1,5 @@
+use bevy::prelude::*;
+
+// the scenario's objectives and how far along they are
+#[derive(Component)]
+pub struct ObjectiveText;
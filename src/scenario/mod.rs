@@ -0,0 +1,68 @@
+use bevy::prelude::*;
+
+use crate::{
+    board::components::Team,
+    command::CommandSet,
+    save::LoadedGame,
+    state::{game_running, AppState, GameSetupSet},
+    util::despawn_all,
+};
+
+use self::{
+    components::ObjectiveText,
+    objectives::ScenarioOutcome,
+    resources::{Profile, Scenario, ScenarioLibrary},
+    systems::{
+        apply_scenario, check_objectives, end_scenario, spawn_objective_text, spawn_scenario_units,
+        update_objective_text,
+    },
+};
+
+pub mod components;
+pub mod objectives;
+pub mod resources;
+pub mod systems;
+
+pub const SCENARIO_DIRECTORY: &str = "assets/scenarios";
+pub const CAMPAIGN_DIRECTORY: &str = "assets/campaigns";
+pub const PROFILE_PATH: &str = "saves/profile.json";
+
+// the team objectives are written for
+pub const SCENARIO_TEAM: Team = Team::Player(0);
+
+// games set up from a scenario file instead of the
+// setup screen, and campaigns chaining them
+pub struct ScenarioPlugin;
+
+impl Plugin for ScenarioPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ScenarioLibrary>()
+            .init_resource::<Profile>()
+            .add_systems(
+                OnEnter(AppState::InGame),
+                (
+                    apply_scenario.in_set(GameSetupSet::Scenario),
+                    spawn_scenario_units
+                        .run_if(not(resource_exists::<LoadedGame>()))
+                        .in_set(GameSetupSet::Spawn),
+                    spawn_objective_text.in_set(GameSetupSet::Spawn),
+                )
+                    .run_if(resource_exists::<Scenario>()),
+            )
+            .add_systems(
+                OnExit(AppState::InGame),
+                (despawn_all::<ObjectiveText>, end_scenario),
+            )
+            .add_systems(
+                Update,
+                (
+                    check_objectives
+                        .after(CommandSet::Apply)
+                        .run_if(in_state(AppState::InGame))
+                        .run_if(not(resource_exists::<ScenarioOutcome>())),
+                    update_objective_text.run_if(game_running),
+                )
+                    .run_if(resource_exists::<Scenario>()),
+            );
+    }
+}
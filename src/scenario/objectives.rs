@@ -0,0 +1,146 @@
+use bevy::prelude::*;
+use hexx::Hex;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    board::components::{HexTile, Team},
+    units::components::{Unit, UnitDefault},
+};
+
+// what the first team has to do to win a scenario
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Objective {
+    // still be in the game after this many rounds
+    SurviveTurns(i32),
+    CaptureTile(Hex),
+    // no enemy unit of this kind is left
+    KillUnit(UnitDefault),
+}
+
+#[derive(Resource, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ScenarioOutcome {
+    Victory,
+    Defeat,
+}
+
+impl Objective {
+    pub fn describe(&self, turn: i32) -> String {
+        match self {
+            Objective::SurviveTurns(turns) => {
+                format!("Survive {turns} turns ({}/{turns})", turn.min(*turns))
+            }
+            Objective::CaptureTile(hex) => format!("Capture the tile at ({}, {})", hex.x, hex.y),
+            Objective::KillUnit(kind) => format!("Kill the {kind:?}"),
+        }
+    }
+
+    pub fn met(&self, team: Team, turn: i32, tiles: &[&HexTile], units: &[&Unit]) -> bool {
+        match self {
+            Objective::SurviveTurns(turns) => turn >= *turns,
+            Objective::CaptureTile(hex) => tiles
+                .iter()
+                .any(|tile| tile.coordinate == *hex && tile.team == team),
+            Objective::KillUnit(kind) => !units
+                .iter()
+                .any(|unit| unit.kind == *kind && unit.team != team && unit.health > 0),
+        }
+    }
+}
+
+// a team is out once it has neither tiles nor
+// units, which it doesn't before the first round
+pub fn defeated(team: Team, turn: i32, tiles: &[&HexTile], units: &[&Unit]) -> bool {
+    turn > 0
+        && !tiles.iter().any(|tile| tile.team == team)
+        && !units
+            .iter()
+            .any(|unit| unit.team == team && unit.health > 0)
+}
+
+pub fn evaluate(
+    objectives: &[Objective],
+    team: Team,
+    turn: i32,
+    tiles: &[&HexTile],
+    units: &[&Unit],
+) -> Option<ScenarioOutcome> {
+    if defeated(team, turn, tiles, units) {
+        return Some(ScenarioOutcome::Defeat);
+    }
+
+    // without objectives (a tutorial) it can only be lost
+    (!objectives.is_empty()
+        && objectives
+            .iter()
+            .all(|objective| objective.met(team, turn, tiles, units)))
+    .then_some(ScenarioOutcome::Victory)
+}
+
+#[cfg(test)]
+mod tests {
+    use hexx::hex;
+
+    use crate::board::components::TileVariant;
+
+    use super::*;
+
+    const PLAYER: Team = Team::Player(0);
+    const ENEMY: Team = Team::Player(1);
+
+    fn tile(coordinate: Hex, team: Team) -> HexTile {
+        HexTile {
+            coordinate,
+            variant: TileVariant::Land,
+            capture_progress: 3,
+            team,
+            resource: None,
+        }
+    }
+
+    #[test]
+    fn every_objective_is_needed_to_win() {
+        let objectives = [
+            Objective::SurviveTurns(2),
+            Objective::CaptureTile(hex(1, 0)),
+            Objective::KillUnit(UnitDefault::Newt),
+        ];
+        let tiles = [tile(hex(0, 0), PLAYER), tile(hex(1, 0), ENEMY)];
+        let mut newt = Unit::new_default(UnitDefault::Newt, hex(2, 0), ENEMY);
+        let knight = Unit::new_default(UnitDefault::Knight, hex(0, 0), PLAYER);
+
+        let outcome = |turn, tiles: &[HexTile], units: &[&Unit]| {
+            evaluate(
+                &objectives,
+                PLAYER,
+                turn,
+                &tiles.iter().collect::<Vec<_>>(),
+                units,
+            )
+        };
+
+        assert_eq!(outcome(2, &tiles, &[&knight, &newt]), None);
+
+        let captured = [tile(hex(0, 0), PLAYER), tile(hex(1, 0), PLAYER)];
+        assert_eq!(outcome(2, &captured, &[&knight, &newt]), None);
+
+        newt.health = 0;
+        assert_eq!(outcome(1, &captured, &[&knight, &newt]), None);
+        assert_eq!(
+            outcome(2, &captured, &[&knight, &newt]),
+            Some(ScenarioOutcome::Victory)
+        );
+    }
+
+    #[test]
+    fn losing_everything_is_a_defeat() {
+        let tiles = [tile(hex(0, 0), ENEMY)];
+        let tiles = tiles.iter().collect::<Vec<_>>();
+        let objectives = [Objective::SurviveTurns(10)];
+
+        assert_eq!(evaluate(&objectives, PLAYER, 0, &tiles, &[]), None);
+        assert_eq!(
+            evaluate(&objectives, PLAYER, 1, &tiles, &[]),
+            Some(ScenarioOutcome::Defeat)
+        );
+    }
+}
@@ -0,0 +1,341 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs, io,
+    path::Path,
+};
+
+use bevy::prelude::*;
+use hexx::Hex;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::{
+    board::{
+        components::{Team, TileResource},
+        resources::{TeamSettings, MAX_TEAMS, MIN_TEAMS},
+    },
+    state::MatchSettings,
+    tutorial::steps::TutorialStep,
+    units::components::{Unit, UnitDefault},
+};
+
+use super::{objectives::Objective, SCENARIO_TEAM};
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct PlacedResource {
+    pub at: Hex,
+    pub resource: TileResource,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct ScenarioUnit {
+    pub kind: UnitDefault,
+    pub position: Hex,
+    pub team: Team,
+}
+
+// an authored game, replaces the match
+// settings while it is being played
+#[derive(Resource, Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Scenario {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    pub map_radius: u32,
+    pub starting_coins: i32,
+    // one per team, None lets the team pick it
+    pub capitals: Vec<Option<Hex>>,
+    // None scatters them like a normal game
+    #[serde(default)]
+    pub resources: Option<Vec<PlacedResource>>,
+    #[serde(default)]
+    pub units: Vec<ScenarioUnit>,
+    // where the units carried over from the
+    // last scenario of a campaign are placed
+    #[serde(default)]
+    pub reinforcements: Vec<Hex>,
+    // what the first team has to do to win
+    pub objectives: Vec<Objective>,
+    // a scenario with steps is won by finishing them
+    #[serde(default)]
+    pub tutorial: Vec<TutorialStep>,
+}
+
+// the settings a scenario replaced, they
+// are put back once it is over
+#[derive(Resource)]
+pub struct SettingsBeforeScenario {
+    pub match_settings: MatchSettings,
+    pub team_settings: TeamSettings,
+}
+
+// scenarios played one after another
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Campaign {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    // scenario names, in order
+    pub scenarios: Vec<String>,
+    // how many of the first team's units, most
+    // experienced first, go on to the next one
+    pub carried_units: usize,
+}
+
+// the campaign the current scenario belongs to
+#[derive(Resource, Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct CampaignRun {
+    pub campaign: String,
+    pub stage: usize,
+    // carried over from the scenario before
+    pub roster: Vec<Unit>,
+}
+
+// every scenario and campaign in the asset folders
+#[derive(Resource, Default)]
+pub struct ScenarioLibrary {
+    pub scenarios: Vec<Scenario>,
+    pub campaigns: Vec<Campaign>,
+}
+
+impl ScenarioLibrary {
+    pub fn load(
+        scenario_directory: impl AsRef<Path>,
+        campaign_directory: impl AsRef<Path>,
+    ) -> Self {
+        let scenarios = load_files::<Scenario>(scenario_directory)
+            .into_iter()
+            .filter(|scenario| {
+                let teams = scenario.capitals.len();
+                if !(MIN_TEAMS..=MAX_TEAMS).contains(&teams) {
+                    warn!("scenario {} has {teams} teams", scenario.name);
+                    return false;
+                }
+                true
+            })
+            .collect::<Vec<_>>();
+
+        let campaigns = load_files::<Campaign>(campaign_directory)
+            .into_iter()
+            .filter(|campaign| {
+                let missing = campaign
+                    .scenarios
+                    .iter()
+                    .find(|name| !scenarios.iter().any(|scenario| scenario.name == **name));
+
+                if let Some(missing) = missing {
+                    warn!(
+                        "campaign {} needs the missing scenario {missing}",
+                        campaign.name
+                    );
+                    return false;
+                }
+                true
+            })
+            .collect();
+
+        let mut library = ScenarioLibrary {
+            scenarios,
+            campaigns,
+        };
+        library.scenarios.sort_by(|a, b| a.name.cmp(&b.name));
+        library.campaigns.sort_by(|a, b| a.name.cmp(&b.name));
+        library
+    }
+
+    pub fn scenario(&self, name: &str) -> Option<&Scenario> {
+        self.scenarios.iter().find(|scenario| scenario.name == name)
+    }
+
+    // `--scenario <name>` skips the menus
+    pub fn scenario_from_args(&self) -> Option<Scenario> {
+        let args = std::env::args().collect::<Vec<_>>();
+
+        args.windows(2)
+            .find(|pair| pair[0] == "--scenario")
+            .and_then(|pair| self.scenario(&pair[1]))
+            .cloned()
+    }
+}
+
+// every json file in the directory that parses
+fn load_files<T: DeserializeOwned>(directory: impl AsRef<Path>) -> Vec<T> {
+    let Ok(entries) = fs::read_dir(directory) else {
+        return Vec::new();
+    };
+
+    entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.extension()
+                .is_some_and(|extension| extension == "json")
+        })
+        .filter_map(|path| {
+            let file = fs::read_to_string(&path)
+                .map_err(|error| error.to_string())
+                .and_then(|text| {
+                    serde_json::from_str::<T>(&text).map_err(|error| error.to_string())
+                });
+
+            match file {
+                Ok(file) => Some(file),
+                Err(error) => {
+                    warn!("skipping {}: {error}", path.display());
+                    None
+                }
+            }
+        })
+        .collect()
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct CampaignProgress {
+    // scenarios won so far
+    pub completed: usize,
+    pub roster: Vec<Unit>,
+}
+
+// what this machine's player has won so far
+#[derive(Resource, Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct Profile {
+    pub completed_scenarios: BTreeSet<String>,
+    pub campaigns: BTreeMap<String, CampaignProgress>,
+}
+
+impl Profile {
+    // a missing or broken profile starts over
+    pub fn read(path: impl AsRef<Path>) -> Self {
+        fs::read_to_string(path)
+            .ok()
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn write(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let path = path.as_ref();
+        if let Some(directory) = path.parent() {
+            fs::create_dir_all(directory)?;
+        }
+
+        let json = serde_json::to_string_pretty(self)?;
+        fs::write(path, json)
+    }
+
+    // a campaign goes on with the most
+    // experienced of the surviving units
+    pub fn record_victory(
+        &mut self,
+        scenario: &str,
+        campaign: Option<(&Campaign, &CampaignRun)>,
+        units: &[&Unit],
+    ) {
+        self.completed_scenarios.insert(scenario.to_string());
+
+        let Some((campaign, run)) = campaign else {
+            return;
+        };
+
+        let mut roster = units
+            .iter()
+            .filter(|unit| unit.team == SCENARIO_TEAM && unit.health > 0)
+            .map(|unit| (*unit).clone())
+            .collect::<Vec<_>>();
+        roster.sort_by_key(|unit| std::cmp::Reverse(unit.experience));
+        roster.truncate(campaign.carried_units);
+
+        self.campaigns.insert(
+            campaign.name.clone(),
+            CampaignProgress {
+                completed: run.stage + 1,
+                roster,
+            },
+        );
+    }
+
+    // the stage to play next, a finished
+    // campaign starts over from the first
+    pub fn next_run(&self, campaign: &Campaign) -> CampaignRun {
+        let progress = self
+            .campaigns
+            .get(&campaign.name)
+            .filter(|progress| progress.completed < campaign.scenarios.len())
+            .cloned()
+            .unwrap_or_default();
+
+        CampaignRun {
+            campaign: campaign.name.clone(),
+            stage: progress.completed,
+            roster: progress.roster,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use hexx::hex;
+
+    use crate::scenario::{CAMPAIGN_DIRECTORY, SCENARIO_DIRECTORY};
+
+    use super::*;
+
+    #[test]
+    fn bundled_scenarios_load_and_fit_their_board() {
+        let library = ScenarioLibrary::load(SCENARIO_DIRECTORY, CAMPAIGN_DIRECTORY);
+        let names = library
+            .scenarios
+            .iter()
+            .map(|scenario| scenario.name.as_str())
+            .collect::<Vec<_>>();
+
+        assert_eq!(names, ["Outpost", "River Crossing", "Skirmish", "Tutorial"]);
+        assert_eq!(library.campaigns.len(), 1);
+
+        for scenario in &library.scenarios {
+            let hexes = scenario
+                .capitals
+                .iter()
+                .flatten()
+                .chain(scenario.units.iter().map(|unit| &unit.position))
+                .chain(&scenario.reinforcements)
+                .chain(scenario.resources.iter().flatten().map(|placed| &placed.at))
+                .chain(scenario.tutorial.iter().flat_map(|step| &step.highlight));
+
+            for hex in hexes {
+                assert!(
+                    hex.ulength() <= scenario.map_radius,
+                    "{hex:?} is off the board in {}",
+                    scenario.name
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn campaigns_carry_their_veterans() {
+        let campaign = Campaign {
+            name: "Test".to_string(),
+            description: String::new(),
+            scenarios: vec!["A".to_string(), "B".to_string()],
+            carried_units: 1,
+        };
+        let mut profile = Profile::default();
+
+        let run = profile.next_run(&campaign);
+        assert_eq!(run.stage, 0);
+
+        let mut veteran = Unit::new_default(UnitDefault::Knight, hex(0, 0), SCENARIO_TEAM);
+        veteran.experience = 5;
+        let rookie = Unit::new_default(UnitDefault::Scout, hex(1, 0), SCENARIO_TEAM);
+        let enemy = Unit::new_default(UnitDefault::Newt, hex(2, 0), Team::Player(1));
+
+        profile.record_victory("A", Some((&campaign, &run)), &[&rookie, &veteran, &enemy]);
+
+        let run = profile.next_run(&campaign);
+        assert_eq!(run.stage, 1);
+        assert_eq!(run.roster, [veteran]);
+        assert!(profile.completed_scenarios.contains("A"));
+
+        profile.record_victory("B", Some((&campaign, &run)), &[]);
+        assert_eq!(profile.next_run(&campaign).stage, 0);
+    }
+}
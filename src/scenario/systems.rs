@@ -0,0 +1,233 @@
+use bevy::prelude::*;
+
+use crate::{
+    board::{components::HexTile, resources::TeamSettings},
+    player::resources::TurnCounter,
+    state::MatchSettings,
+    units::{
+        components::{Action, Unit},
+        systems::spawn_unit,
+    },
+};
+
+use super::{
+    components::ObjectiveText,
+    objectives::{evaluate, ScenarioOutcome},
+    resources::{CampaignRun, Profile, Scenario, ScenarioLibrary, SettingsBeforeScenario},
+    PROFILE_PATH, SCENARIO_TEAM,
+};
+
+// runs before everything that reads the
+// match or team settings to set up the game
+pub fn apply_scenario(
+    mut commands: Commands,
+    scenario: Res<Scenario>,
+    mut match_settings: ResMut<MatchSettings>,
+    mut team_settings: ResMut<TeamSettings>,
+) {
+    commands.insert_resource(SettingsBeforeScenario {
+        match_settings: match_settings.clone(),
+        team_settings: team_settings.clone(),
+    });
+
+    match_settings.map_radius = scenario.map_radius;
+    match_settings.starting_coins = scenario.starting_coins;
+
+    *team_settings = TeamSettings::new(scenario.capitals.len());
+    for (config, capital) in team_settings.teams.iter_mut().zip(&scenario.capitals) {
+        config.capital = *capital;
+    }
+}
+
+pub fn spawn_scenario_units(
+    mut commands: Commands,
+    scenario: Res<Scenario>,
+    campaign_run: Option<Res<CampaignRun>>,
+) {
+    for unit in &scenario.units {
+        spawn_unit(
+            &mut commands,
+            Unit::new_default(unit.kind, unit.position, unit.team),
+        );
+    }
+
+    let Some(campaign_run) = campaign_run else {
+        return;
+    };
+
+    // veterans keep their experience and
+    // promotions but start out fresh
+    for (unit, position) in campaign_run.roster.iter().zip(&scenario.reinforcements) {
+        let mut unit = unit.clone();
+        unit.position = *position;
+        unit.team = SCENARIO_TEAM;
+        unit.health = unit.max_health;
+        unit.actions = vec![Action::Move];
+
+        spawn_unit(&mut commands, unit);
+    }
+}
+
+pub fn check_objectives(
+    mut commands: Commands,
+    scenario: Res<Scenario>,
+    campaign_run: Option<Res<CampaignRun>>,
+    turn_counter: Res<TurnCounter>,
+    hexes: Query<&HexTile>,
+    units: Query<&Unit>,
+    progress: (Res<ScenarioLibrary>, ResMut<Profile>),
+) {
+    let (library, mut profile) = progress;
+
+    let tiles = hexes.iter().collect::<Vec<_>>();
+    let units = units.iter().collect::<Vec<_>>();
+
+    let Some(outcome) = evaluate(
+        &scenario.objectives,
+        SCENARIO_TEAM,
+        turn_counter.0,
+        &tiles,
+        &units,
+    ) else {
+        return;
+    };
+
+    commands.insert_resource(outcome);
+
+    if outcome != ScenarioOutcome::Victory {
+        return;
+    }
+
+    let campaign = campaign_run.as_deref().and_then(|run| {
+        library
+            .campaigns
+            .iter()
+            .find(|campaign| campaign.name == run.campaign)
+            .map(|campaign| (campaign, run))
+    });
+
+    profile.record_victory(&scenario.name, campaign, &units);
+    if let Err(error) = profile.write(PROFILE_PATH) {
+        warn!("could not save the profile to {PROFILE_PATH}: {error}");
+    }
+}
+
+pub fn spawn_objective_text(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    scenario: Res<Scenario>,
+) {
+    if scenario.objectives.is_empty() {
+        return;
+    }
+
+    commands.spawn((
+        TextBundle::from_section(
+            "",
+            TextStyle {
+                font: asset_server.load("fonts/arial.ttf"),
+                font_size: 24.0,
+                color: Color::WHITE,
+            },
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            right: Val::Px(10.),
+            bottom: Val::Px(10.),
+            ..default()
+        }),
+        ObjectiveText,
+    ));
+}
+
+pub fn update_objective_text(
+    scenario: Res<Scenario>,
+    turn_counter: Res<TurnCounter>,
+    hexes: Query<&HexTile>,
+    units: Query<&Unit>,
+    mut objective_text: Query<&mut Text, With<ObjectiveText>>,
+) {
+    let tiles = hexes.iter().collect::<Vec<_>>();
+    let units = units.iter().collect::<Vec<_>>();
+
+    let mut value = format!("{}\nObjectives:", scenario.name);
+    for objective in &scenario.objectives {
+        let mark = if objective.met(SCENARIO_TEAM, turn_counter.0, &tiles, &units) {
+            "[x]"
+        } else {
+            "[ ]"
+        };
+        value += &format!("\n{mark} {}", objective.describe(turn_counter.0));
+    }
+
+    for mut text in &mut objective_text {
+        if text.sections[0].value != value {
+            text.sections[0].value = value.clone();
+        }
+    }
+}
+
+// the next game is set up the way it was before
+pub fn end_scenario(mut commands: Commands, before: Option<Res<SettingsBeforeScenario>>) {
+    commands.remove_resource::<Scenario>();
+    commands.remove_resource::<CampaignRun>();
+    commands.remove_resource::<ScenarioOutcome>();
+
+    if let Some(before) = before {
+        commands.insert_resource(before.match_settings.clone());
+        commands.insert_resource(before.team_settings.clone());
+        commands.remove_resource::<SettingsBeforeScenario>();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        scenario::{CAMPAIGN_DIRECTORY, SCENARIO_DIRECTORY},
+        state::AppState,
+    };
+
+    use super::*;
+
+    fn go_to(app: &mut App, state: AppState) {
+        app.world.resource_mut::<NextState<AppState>>().set(state);
+        app.update();
+    }
+
+    #[test]
+    fn new_games_after_a_scenario_start_from_the_old_settings() {
+        let library = ScenarioLibrary::load(SCENARIO_DIRECTORY, CAMPAIGN_DIRECTORY);
+        let outpost = library.scenario("Outpost").unwrap().clone();
+
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .add_state::<AppState>()
+            .init_resource::<MatchSettings>()
+            .init_resource::<TeamSettings>()
+            .insert_resource(outpost.clone())
+            .add_systems(OnEnter(AppState::InGame), apply_scenario)
+            .add_systems(OnExit(AppState::InGame), end_scenario);
+
+        go_to(&mut app, AppState::InGame);
+        assert_eq!(
+            app.world.resource::<MatchSettings>().map_radius,
+            outpost.map_radius
+        );
+        assert_eq!(
+            app.world.resource::<TeamSettings>().teams[0].capital,
+            outpost.capitals[0]
+        );
+
+        go_to(&mut app, AppState::MainMenu);
+        go_to(&mut app, AppState::Setup);
+        assert!(!app.world.contains_resource::<Scenario>());
+        assert_eq!(
+            *app.world.resource::<MatchSettings>(),
+            MatchSettings::default()
+        );
+        assert_eq!(
+            *app.world.resource::<TeamSettings>(),
+            TeamSettings::default()
+        );
+    }
+}
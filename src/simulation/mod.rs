@@ -0,0 +1,305 @@
+use std::{fs, path::Path};
+
+use bevy::prelude::*;
+use hexx::Hex;
+
+use crate::{
+    board::{
+        resources::{BoardIndex, Controller, TeamSettings, Themes},
+        systems::{build_board, load_colors, place_team_capitals, sync_board_index},
+    },
+    buildings::systems::despawn_destroyed_buildings,
+    command::{
+        events::{CommandEvent, GameEvent},
+        rules::{CaptureRules, CombatRng, CombatRules},
+        systems::apply_game_commands,
+    },
+    enemy::planner::AiProfile,
+    player::{resources::TurnCounter, systems::setup_turns},
+    state::{Difficulty, MatchSettings, Opponent},
+    units::systems::despawn_dead_units,
+};
+
+use self::{
+    report::GameResult,
+    resources::{GameRecord, SimulatedAis},
+    systems::{play_simulated_turn, record_game},
+};
+
+pub mod report;
+pub mod resources;
+pub mod systems;
+
+// games that nobody wins by then are a draw
+pub const MAX_TURNS: i32 = 60;
+pub const MIN_RADIUS: u32 = 3;
+
+// ai against ai without a window, for
+// balancing units between real games
+#[derive(Clone, Debug)]
+pub struct SimulationConfig {
+    pub games: usize,
+    pub seeds: Vec<u64>,
+    pub map_radii: Vec<u32>,
+    pub ais: [AiProfile; 2],
+    pub max_turns: i32,
+    pub starting_coins: i32,
+    pub random_combat: bool,
+}
+
+impl Default for SimulationConfig {
+    fn default() -> Self {
+        SimulationConfig {
+            games: 10,
+            seeds: vec![0],
+            map_radii: vec![5],
+            ais: [
+                AiProfile::preset("balanced").unwrap(),
+                AiProfile::preset("rush").unwrap(),
+            ],
+            max_turns: MAX_TURNS,
+            starting_coins: MatchSettings::default().starting_coins,
+            random_combat: false,
+        }
+    }
+}
+
+impl SimulationConfig {
+    // `--games <n> --seeds <a,b> --sizes <a,b> --ai <first,second>
+    // --max-turns <n> --coins <n> --random-combat`, an ai
+    // is a preset name or a json file
+    pub fn from_args(args: &[String]) -> Result<Self, String> {
+        let mut config = SimulationConfig::default();
+        let value = |flag: &str| {
+            args.windows(2)
+                .find(|pair| pair[0] == flag)
+                .map(|pair| pair[1].clone())
+        };
+
+        if let Some(games) = value("--games") {
+            config.games = parse(&games)?;
+        }
+        if let Some(seeds) = value("--seeds") {
+            config.seeds = list(&seeds)?;
+        }
+        if let Some(sizes) = value("--sizes") {
+            config.map_radii = list(&sizes)?;
+        }
+        if let Some(max_turns) = value("--max-turns") {
+            config.max_turns = parse(&max_turns)?;
+        }
+        if let Some(coins) = value("--coins") {
+            config.starting_coins = parse(&coins)?;
+        }
+        config.random_combat = args.iter().any(|arg| arg == "--random-combat");
+        if let Some(ais) = value("--ai") {
+            let ais = ais.split(',').map(load_ai).collect::<Result<Vec<_>, _>>()?;
+            let [first, second] = <[AiProfile; 2]>::try_from(ais)
+                .map_err(|_| "--ai takes two ais, like balanced,rush".to_string())?;
+            config.ais = [first, second];
+        }
+
+        if config.seeds.is_empty() || config.map_radii.is_empty() {
+            return Err("there has to be at least one seed and map size".to_string());
+        }
+        if let Some(radius) = config.map_radii.iter().find(|radius| **radius < MIN_RADIUS) {
+            return Err(format!(
+                "a map size of {radius} leaves no room between the capitals"
+            ));
+        }
+
+        Ok(config)
+    }
+
+    // every seed and size is played from both sides,
+    // as (seed, map radius, swapped)
+    pub fn matchups(&self) -> Vec<(u64, u32, bool)> {
+        let boards = self
+            .seeds
+            .iter()
+            .flat_map(|seed| self.map_radii.iter().map(move |radius| (*seed, *radius)))
+            .collect::<Vec<_>>();
+
+        (0..self.games)
+            .map(|game| {
+                let (seed, radius) = boards[(game / 2) % boards.len()];
+                (seed, radius, game % 2 == 1)
+            })
+            .collect()
+    }
+}
+
+fn parse<T: std::str::FromStr>(value: &str) -> Result<T, String> {
+    value
+        .parse()
+        .map_err(|_| format!("{value} is not a valid number"))
+}
+
+fn list<T: std::str::FromStr>(values: &str) -> Result<Vec<T>, String> {
+    values.split(',').map(parse).collect()
+}
+
+fn load_ai(name: &str) -> Result<AiProfile, String> {
+    if let Some(profile) = AiProfile::preset(name) {
+        return Ok(profile);
+    }
+
+    let contents = fs::read_to_string(Path::new(name))
+        .map_err(|error| format!("{name} is no preset and can't be read: {error}"))?;
+    serde_json::from_str(&contents).map_err(|error| format!("{name} is not a valid ai: {error}"))
+}
+
+pub fn run(config: &SimulationConfig) -> Vec<GameResult> {
+    config
+        .matchups()
+        .into_iter()
+        .map(|(seed, radius, swapped)| {
+            let [first, second] = config.ais.clone();
+            let ais = if swapped {
+                vec![second, first]
+            } else {
+                vec![first, second]
+            };
+
+            run_game(config, seed, radius, ais)
+        })
+        .collect()
+}
+
+// the ais are given in turn order
+pub fn run_game(
+    config: &SimulationConfig,
+    seed: u64,
+    map_radius: u32,
+    ais: Vec<AiProfile>,
+) -> GameResult {
+    let mut app = simulation_app(config, seed, map_radius, ais.clone());
+
+    loop {
+        app.update();
+
+        let turn = app.world.resource::<TurnCounter>().0;
+        let record = app.world.resource::<GameRecord>();
+        if record.finished || turn >= config.max_turns {
+            return GameResult::new(seed, map_radius, &ais, turn, record);
+        }
+    }
+}
+
+// the rules of the game without anything drawn, the
+// board meshes are still built but never rendered
+fn simulation_app(
+    config: &SimulationConfig,
+    seed: u64,
+    map_radius: u32,
+    ais: Vec<AiProfile>,
+) -> App {
+    let mut team_settings = TeamSettings::new(ais.len());
+    // capitals on opposite sides of the board
+    let distance = map_radius as i32 - 1;
+    for (config, corner) in team_settings.teams.iter_mut().zip([3, 0]) {
+        config.capital = Some(Hex::NEIGHBORS_COORDS[corner] * distance);
+        config.controller = Controller::Ai;
+    }
+
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, AssetPlugin::default()))
+        .add_asset::<Mesh>()
+        .add_asset::<ColorMaterial>()
+        .add_event::<CommandEvent>()
+        .add_event::<GameEvent>()
+        .insert_resource(MatchSettings {
+            map_radius,
+            seed,
+            opponent: Opponent::Ai(Difficulty::Normal),
+            starting_coins: config.starting_coins,
+            random_combat: config.random_combat,
+            ..default()
+        })
+        .insert_resource(team_settings)
+        .init_resource::<Themes>()
+        .init_resource::<BoardIndex>()
+        .init_resource::<CaptureRules>()
+        .insert_resource(CombatRules {
+            random: config.random_combat,
+            ..default()
+        })
+        .insert_resource(CombatRng(seed))
+        .insert_resource(SimulatedAis(ais))
+        .init_resource::<GameRecord>()
+        .add_systems(
+            Startup,
+            (
+                load_colors,
+                setup_turns,
+                apply_deferred,
+                build_board,
+                apply_deferred,
+                place_team_capitals,
+            )
+                .chain(),
+        )
+        .add_systems(
+            Update,
+            (
+                play_simulated_turn,
+                sync_board_index,
+                apply_game_commands,
+                apply_deferred,
+                despawn_dead_units,
+                despawn_destroyed_buildings,
+                apply_deferred,
+                record_game,
+            )
+                .chain(),
+        );
+
+    app
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn games_are_played_from_both_sides_and_repeat() {
+        let config = SimulationConfig {
+            games: 3,
+            map_radii: vec![3],
+            max_turns: 20,
+            ..default()
+        };
+        assert_eq!(
+            config.matchups(),
+            [(0, 3, false), (0, 3, true), (0, 3, false)]
+        );
+
+        let results = run(&config);
+        assert_eq!(results[0].ais, ["balanced", "rush"]);
+        assert_eq!(results[1].ais, ["rush", "balanced"]);
+        assert_eq!(results[0], results[2]);
+
+        assert!(results[0].turns <= 20);
+        assert!(!results[0].recruits[0].is_empty());
+        assert_eq!(results[0].income[0].len(), results[0].turns as usize + 1);
+    }
+
+    #[test]
+    fn every_difficulty_beats_the_one_below_it() {
+        let ladder = [Difficulty::Easy, Difficulty::Normal, Difficulty::Hard];
+
+        for pair in ladder.windows(2) {
+            let (weaker, stronger) = (pair[0].profile(), pair[1].profile());
+            let config = SimulationConfig {
+                games: 2,
+                map_radii: vec![3],
+                ais: [weaker, stronger.clone()],
+                ..default()
+            };
+
+            for result in run(&config) {
+                assert_eq!(result.winner.as_ref(), Some(&stronger.name), "{pair:?}");
+            }
+        }
+    }
+}
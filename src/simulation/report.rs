@@ -0,0 +1,218 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::Write,
+};
+
+use serde::Serialize;
+
+use crate::{enemy::planner::AiProfile, units::components::UnitDefault};
+
+use super::resources::GameRecord;
+
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct GameResult {
+    pub seed: u64,
+    pub map_radius: u32,
+    // ai names in turn order
+    pub ais: Vec<String>,
+    // None for a draw
+    pub winner: Option<String>,
+    pub turns: i32,
+    pub recruits: Vec<BTreeMap<UnitDefault, u32>>,
+    pub income: Vec<Vec<i32>>,
+}
+
+impl GameResult {
+    pub fn new(
+        seed: u64,
+        map_radius: u32,
+        ais: &[AiProfile],
+        turns: i32,
+        record: &GameRecord,
+    ) -> Self {
+        GameResult {
+            seed,
+            map_radius,
+            ais: ais.iter().map(|ai| ai.name.clone()).collect(),
+            winner: record.winner.map(|id| ais[id].name.clone()),
+            turns,
+            recruits: record.recruits.clone(),
+            income: record.income.clone(),
+        }
+    }
+}
+
+#[derive(Serialize, Clone, Debug, PartialEq, Default)]
+pub struct AiSummary {
+    pub name: String,
+    pub games: u32,
+    pub wins: u32,
+    pub losses: u32,
+    pub draws: u32,
+    pub win_rate: f32,
+    pub recruits: BTreeMap<UnitDefault, u32>,
+    // averaged over the games still going in that round
+    pub average_income: Vec<f32>,
+}
+
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct Report {
+    pub average_turns: f32,
+    pub summaries: Vec<AiSummary>,
+    pub games: Vec<GameResult>,
+}
+
+impl Report {
+    pub fn new(games: Vec<GameResult>) -> Self {
+        let mut summaries = BTreeMap::<String, AiSummary>::new();
+        // (sum, games) of every round
+        let mut income = BTreeMap::<String, Vec<(i32, u32)>>::new();
+
+        for game in &games {
+            for (id, name) in game.ais.iter().enumerate() {
+                let summary = summaries.entry(name.clone()).or_insert_with(|| AiSummary {
+                    name: name.clone(),
+                    ..Default::default()
+                });
+
+                summary.games += 1;
+                match &game.winner {
+                    None => summary.draws += 1,
+                    Some(winner) if winner == name => summary.wins += 1,
+                    Some(_) => summary.losses += 1,
+                }
+
+                for (kind, count) in game.recruits.get(id).into_iter().flatten() {
+                    *summary.recruits.entry(*kind).or_default() += count;
+                }
+
+                let rounds = income.entry(name.clone()).or_default();
+                for (round, coins) in game.income.get(id).into_iter().flatten().enumerate() {
+                    if rounds.len() <= round {
+                        rounds.push((0, 0));
+                    }
+                    rounds[round].0 += coins;
+                    rounds[round].1 += 1;
+                }
+            }
+        }
+
+        for summary in summaries.values_mut() {
+            summary.win_rate = summary.wins as f32 / summary.games.max(1) as f32;
+            summary.average_income = income[&summary.name]
+                .iter()
+                .map(|(sum, games)| *sum as f32 / *games as f32)
+                .collect();
+        }
+
+        let average_turns =
+            games.iter().map(|game| game.turns).sum::<i32>() as f32 / games.len().max(1) as f32;
+
+        Report {
+            average_turns,
+            summaries: summaries.into_values().collect(),
+            games,
+        }
+    }
+
+    // one row per ai, the single games are only in the json
+    pub fn to_csv(&self) -> String {
+        let kinds = self
+            .summaries
+            .iter()
+            .flat_map(|summary| summary.recruits.keys().copied())
+            .collect::<BTreeSet<_>>();
+        let rounds = self
+            .summaries
+            .iter()
+            .map(|summary| summary.average_income.len())
+            .max()
+            .unwrap_or(0);
+
+        let mut csv = "ai,games,wins,losses,draws,win_rate,average_turns".to_string();
+        for kind in &kinds {
+            write!(csv, ",recruited_{kind:?}").unwrap();
+        }
+        for round in 0..rounds {
+            write!(csv, ",income_{round}").unwrap();
+        }
+        csv.push('\n');
+
+        for summary in &self.summaries {
+            write!(
+                csv,
+                "{},{},{},{},{},{:.3},{:.1}",
+                summary.name,
+                summary.games,
+                summary.wins,
+                summary.losses,
+                summary.draws,
+                summary.win_rate,
+                self.average_turns
+            )
+            .unwrap();
+            for kind in &kinds {
+                write!(csv, ",{}", summary.recruits.get(kind).unwrap_or(&0)).unwrap();
+            }
+            for round in 0..rounds {
+                match summary.average_income.get(round) {
+                    Some(income) => write!(csv, ",{income:.2}").unwrap(),
+                    None => csv.push(','),
+                }
+            }
+            csv.push('\n');
+        }
+
+        csv
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn game(ais: [&str; 2], winner: Option<&str>, income: [Vec<i32>; 2]) -> GameResult {
+        GameResult {
+            seed: 0,
+            map_radius: 4,
+            ais: ais.iter().map(|ai| ai.to_string()).collect(),
+            winner: winner.map(str::to_string),
+            turns: 10,
+            recruits: vec![
+                BTreeMap::from([(UnitDefault::Knight, 2)]),
+                BTreeMap::from([(UnitDefault::Scout, 3)]),
+            ],
+            income: income.to_vec(),
+        }
+    }
+
+    #[test]
+    fn summaries_count_both_sides() {
+        let report = Report::new(vec![
+            game(["rush", "economy"], Some("rush"), [vec![4, 6], vec![4]]),
+            game(["economy", "rush"], None, [vec![4, 8, 8], vec![2, 4]]),
+        ]);
+
+        let rush = &report.summaries[1];
+        assert_eq!(rush.name, "rush");
+        assert_eq!((rush.games, rush.wins, rush.draws), (2, 1, 1));
+        assert_eq!(rush.win_rate, 0.5);
+        assert_eq!(
+            rush.recruits,
+            BTreeMap::from([(UnitDefault::Knight, 2), (UnitDefault::Scout, 3)])
+        );
+        assert_eq!(rush.average_income, [3., 5.]);
+
+        let economy = &report.summaries[0];
+        assert_eq!((economy.losses, economy.draws), (1, 1));
+        assert_eq!(economy.average_income, [4., 8., 8.]);
+
+        let csv = report.to_csv();
+        let mut lines = csv.lines();
+        assert_eq!(
+            lines.next(),
+            Some("ai,games,wins,losses,draws,win_rate,average_turns,recruited_Scout,recruited_Knight,income_0,income_1,income_2")
+        );
+        assert_eq!(lines.nth(1), Some("rush,2,1,0,1,0.500,10.0,3,2,3.00,5.00,"));
+    }
+}
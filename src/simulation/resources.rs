@@ -0,0 +1,19 @@
+use std::collections::BTreeMap;
+
+use bevy::prelude::*;
+
+use crate::{enemy::planner::AiProfile, units::components::UnitDefault};
+
+// indexed by team id
+#[derive(Resource)]
+pub struct SimulatedAis(pub Vec<AiProfile>);
+
+// indexed by team id like the ais
+#[derive(Resource, Default)]
+pub struct GameRecord {
+    pub recruits: Vec<BTreeMap<UnitDefault, u32>>,
+    // the income of every round
+    pub income: Vec<Vec<i32>>,
+    pub winner: Option<usize>,
+    pub finished: bool,
+}
@@ -0,0 +1,90 @@
+use bevy::prelude::*;
+
+use crate::{
+    board::components::HexTile,
+    buildings::components::Building,
+    command::events::{CommandEvent, GameCommand},
+    enemy::planner::{plan_turn, AiView},
+    player::{
+        income::IncomeBreakdown,
+        resources::{TeamCoins, TurnCounter, TurnOrder},
+    },
+    scenario::objectives::defeated,
+    units::components::Unit,
+};
+
+use super::resources::{GameRecord, SimulatedAis};
+
+// a whole turn at once, it is applied in the same frame
+pub fn play_simulated_turn(
+    turn_order: Res<TurnOrder>,
+    team_coins: Res<TeamCoins>,
+    ais: Res<SimulatedAis>,
+    game: (Query<&HexTile>, Query<&Unit>, Query<&Building>),
+    mut game_commands: EventWriter<CommandEvent>,
+) {
+    let (hexes, units, buildings) = game;
+    let team = turn_order.active();
+    let Some(profile) = team.id().and_then(|id| ais.0.get(id)) else {
+        game_commands.send(CommandEvent::local(GameCommand::EndTurn));
+        return;
+    };
+
+    let tiles = hexes.iter().cloned().collect::<Vec<_>>();
+    let units = units.iter().cloned().collect::<Vec<_>>();
+    let buildings = buildings.iter().cloned().collect::<Vec<_>>();
+    let view = AiView {
+        tiles: &tiles,
+        units: &units,
+        buildings: &buildings,
+        coins: team_coins.get(team),
+    };
+
+    for command in plan_turn(profile, team, &view) {
+        game_commands.send(CommandEvent::local(command));
+    }
+}
+
+pub fn record_game(
+    mut record: ResMut<GameRecord>,
+    turn: (Res<TurnOrder>, Res<TurnCounter>),
+    hexes: Query<&HexTile>,
+    recruits: Query<&Unit, Added<Unit>>,
+    units: Query<&Unit>,
+    buildings: Query<&Building>,
+) {
+    let (turn_order, turn_counter) = turn;
+    let teams = turn_order.teams.len();
+    record.recruits.resize_with(teams, Default::default);
+    record.income.resize_with(teams, Default::default);
+
+    for unit in &recruits {
+        if let Some(id) = unit.team.id() {
+            *record.recruits[id].entry(unit.kind).or_default() += 1;
+        }
+    }
+
+    if !turn_counter.is_changed() {
+        return;
+    }
+
+    for (id, team) in turn_order.teams.iter().enumerate() {
+        let income = IncomeBreakdown::for_team(*team, hexes.iter(), buildings.iter());
+        record.income[id].push(income.total());
+    }
+
+    let tiles = hexes.iter().collect::<Vec<_>>();
+    let units = units.iter().collect::<Vec<_>>();
+    let standing = turn_order
+        .teams
+        .iter()
+        .enumerate()
+        .filter(|(_, team)| !defeated(**team, turn_counter.0, &tiles, &units))
+        .map(|(id, _)| id)
+        .collect::<Vec<_>>();
+
+    if standing.len() <= 1 {
+        record.winner = standing.first().copied();
+        record.finished = true;
+    }
+}
@@ -0,0 +1,149 @@
+use bevy::{ecs::system::SystemParam, prelude::*};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    board::components::{HexTile, Team},
+    buildings::{components::Building, systems::spawn_building},
+    command::rules::CombatRng,
+    player::resources::{TeamCoins, TurnCounter, TurnOrder},
+    units::{components::Unit, systems::spawn_unit},
+};
+
+// everything two peers need to agree on,
+// tiles, units and buildings are sorted by coordinate
+// so the same game always serializes the same
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct GameSnapshot {
+    pub turn: i32,
+    pub active_team: usize,
+    #[serde(default)]
+    pub forfeited: Vec<Team>,
+    pub coins: Vec<i32>,
+    pub tiles: Vec<HexTile>,
+    pub units: Vec<Unit>,
+    #[serde(default)]
+    pub buildings: Vec<Building>,
+    // the next random combat rolls
+    #[serde(default)]
+    pub combat_rng: CombatRng,
+}
+
+impl GameSnapshot {
+    pub fn capture<'a>(
+        tiles: impl Iterator<Item = &'a HexTile>,
+        units: impl Iterator<Item = &'a Unit>,
+        buildings: impl Iterator<Item = &'a Building>,
+        turn: i32,
+        turn_order: &TurnOrder,
+        coins: &[i32],
+        combat_rng: CombatRng,
+    ) -> Self {
+        let mut tiles = tiles.cloned().collect::<Vec<_>>();
+        tiles.sort_by_key(|t| (t.coordinate.x, t.coordinate.y));
+
+        // dead units wait for despawn_dead_units,
+        // they are not part of the game anymore
+        let mut units = units.filter(|u| u.health > 0).cloned().collect::<Vec<_>>();
+        units.sort_by_key(|u| (u.position.x, u.position.y));
+
+        let mut buildings = buildings.filter(|b| b.health > 0).cloned().collect::<Vec<_>>();
+        buildings.sort_by_key(|b| (b.position.x, b.position.y));
+
+        GameSnapshot {
+            turn,
+            active_team: turn_order.current,
+            forfeited: turn_order.forfeited.clone(),
+            coins: coins.to_vec(),
+            tiles,
+            units,
+            buildings,
+            combat_rng,
+        }
+    }
+
+    // FNV-1a over the serialized snapshot,
+    // stable across processes and machines
+    pub fn hash(&self) -> u64 {
+        let bytes = serde_json::to_vec(self).expect("snapshot is always serializable");
+
+        bytes.iter().fold(0xcbf29ce484222325, |hash, byte| {
+            (hash ^ *byte as u64).wrapping_mul(0x100000001b3)
+        })
+    }
+}
+
+// everything a snapshot is taken from
+#[derive(SystemParam)]
+pub struct CaptureSnapshot<'w, 's> {
+    hexes: Query<'w, 's, &'static HexTile>,
+    units: Query<'w, 's, &'static Unit>,
+    buildings: Query<'w, 's, &'static Building>,
+    turn_counter: Res<'w, TurnCounter>,
+    turn_order: Res<'w, TurnOrder>,
+    team_coins: Res<'w, TeamCoins>,
+    combat_rng: Res<'w, CombatRng>,
+}
+
+impl CaptureSnapshot<'_, '_> {
+    pub fn turn(&self) -> i32 {
+        self.turn_counter.0
+    }
+
+    pub fn capture(&self) -> GameSnapshot {
+        GameSnapshot::capture(
+            self.hexes.iter(),
+            self.units.iter(),
+            self.buildings.iter(),
+            self.turn_counter.0,
+            &self.turn_order,
+            &self.team_coins.0,
+            *self.combat_rng,
+        )
+    }
+}
+
+// everything a snapshot overwrites, tiles are
+// updated in place, units and buildings are respawned
+#[derive(SystemParam)]
+pub struct RestoreSnapshot<'w, 's> {
+    commands: Commands<'w, 's>,
+    hexes: Query<'w, 's, &'static mut HexTile>,
+    units: Query<'w, 's, Entity, With<Unit>>,
+    buildings: Query<'w, 's, Entity, With<Building>>,
+    turn_counter: ResMut<'w, TurnCounter>,
+    turn_order: ResMut<'w, TurnOrder>,
+    team_coins: ResMut<'w, TeamCoins>,
+    combat_rng: ResMut<'w, CombatRng>,
+}
+
+impl RestoreSnapshot<'_, '_> {
+    pub fn restore(&mut self, snapshot: GameSnapshot) {
+        for mut hex in &mut self.hexes {
+            if let Some(tile) = snapshot
+                .tiles
+                .iter()
+                .find(|t| t.coordinate == hex.coordinate)
+            {
+                *hex = tile.clone();
+            }
+        }
+
+        for entity in self.units.iter().chain(&self.buildings) {
+            self.commands.entity(entity).despawn_recursive();
+        }
+
+        for unit in snapshot.units {
+            spawn_unit(&mut self.commands, unit);
+        }
+
+        for building in snapshot.buildings {
+            spawn_building(&mut self.commands, building);
+        }
+
+        self.turn_counter.0 = snapshot.turn;
+        self.turn_order.current = snapshot.active_team;
+        self.turn_order.forfeited = snapshot.forfeited;
+        self.team_coins.0 = snapshot.coins;
+        *self.combat_rng = snapshot.combat_rng;
+    }
+}
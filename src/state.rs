@@ -0,0 +1,197 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    board::HEX_RADIUS, enemy::planner::AiProfile, player::resources::STARTING_COINS,
+    scenario::objectives::ScenarioOutcome,
+};
+
+#[derive(States, Default, Debug, Hash, PartialEq, Eq, Clone, Copy)]
+pub enum AppState {
+    #[default]
+    MainMenu,
+    Setup,
+    Settings,
+    Scenarios,
+    InGame,
+}
+
+// kept apart from AppState, so pausing
+// doesn't leave (and tear down) the game
+#[derive(States, Default, Debug, Hash, PartialEq, Eq, Clone, Copy)]
+pub enum PauseState {
+    #[default]
+    Running,
+    Paused,
+}
+
+// input, ai and rendering of the game
+pub fn game_running(app_state: Res<State<AppState>>, pause_state: Res<State<PauseState>>) -> bool {
+    *app_state.get() == AppState::InGame && *pause_state.get() == PauseState::Running
+}
+
+// nothing more is played once a game is won or lost
+pub fn game_undecided(outcome: Option<Res<ScenarioOutcome>>) -> bool {
+    outcome.is_none()
+}
+
+// OnEnter(AppState::InGame) runs in these
+// steps, with commands applied after each
+#[derive(SystemSet, Debug, Hash, PartialEq, Eq, Clone)]
+pub enum GameSetupSet {
+    // a scenario replaces the match settings
+    Scenario,
+    // resources the rest of the setup reads
+    Resources,
+    Spawn,
+    // changes to what was spawned
+    Place,
+    // a loaded game replaces the fresh one
+    Restore,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Difficulty {
+    Easy,
+    Normal,
+    Hard,
+}
+
+impl Difficulty {
+    // each one wins simulated games against
+    // the one below it, see the simulation tests
+    pub fn profile(&self) -> AiProfile {
+        let preset = match self {
+            Difficulty::Easy => "timid",
+            Difficulty::Normal => "rush",
+            Difficulty::Hard => "balanced",
+        };
+
+        AiProfile::preset(preset).expect("every difficulty has a preset")
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Opponent {
+    // the difficulty picks how the ai plays
+    Ai(Difficulty),
+    // every team is played on this machine
+    Hotseat,
+}
+
+// in seconds, checked on the machine playing the active team
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum TimeControl {
+    #[default]
+    Unlimited,
+    PerTurn {
+        seconds: u32,
+    },
+    // a total for the whole game, topped up after every turn
+    ChessClock {
+        total: u32,
+        increment: u32,
+    },
+}
+
+impl TimeControl {
+    pub const PRESETS: [TimeControl; 5] = [
+        TimeControl::Unlimited,
+        TimeControl::PerTurn { seconds: 60 },
+        TimeControl::PerTurn { seconds: 120 },
+        TimeControl::ChessClock {
+            total: 300,
+            increment: 5,
+        },
+        TimeControl::ChessClock {
+            total: 600,
+            increment: 10,
+        },
+    ];
+
+    pub fn name(&self) -> String {
+        match self {
+            TimeControl::Unlimited => "Unlimited".to_string(),
+            TimeControl::PerTurn { seconds } => format!("{seconds}s a turn"),
+            TimeControl::ChessClock { total, increment } => {
+                format!("{}min +{increment}s", total / 60)
+            }
+        }
+    }
+}
+
+// chosen on the setup screen
+#[derive(Resource, Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct MatchSettings {
+    pub map_radius: u32,
+    pub seed: u64,
+    pub opponent: Opponent,
+    pub starting_coins: i32,
+    // rolls attacks from a rng seeded with `seed`
+    #[serde(default)]
+    pub random_combat: bool,
+    #[serde(default)]
+    pub time_control: TimeControl,
+    // running out of time loses the game instead of the turn
+    #[serde(default)]
+    pub forfeit_on_time: bool,
+}
+
+impl Default for MatchSettings {
+    fn default() -> Self {
+        MatchSettings {
+            map_radius: HEX_RADIUS as u32,
+            seed: 0,
+            opponent: Opponent::Ai(Difficulty::Normal),
+            starting_coins: STARTING_COINS,
+            random_combat: false,
+            time_control: TimeControl::Unlimited,
+            forfeit_on_time: false,
+        }
+    }
+}
+
+pub struct StatePlugin;
+
+impl Plugin for StatePlugin {
+    fn build(&self, app: &mut App) {
+        let enter_game = OnEnter(AppState::InGame);
+
+        app.add_state::<AppState>()
+            .add_state::<PauseState>()
+            .init_resource::<MatchSettings>()
+            .configure_sets(
+                enter_game.clone(),
+                (
+                    GameSetupSet::Scenario,
+                    GameSetupSet::Resources,
+                    GameSetupSet::Spawn,
+                    GameSetupSet::Place,
+                    GameSetupSet::Restore,
+                )
+                    .chain(),
+            )
+            .add_systems(
+                enter_game,
+                (
+                    apply_deferred
+                        .after(GameSetupSet::Scenario)
+                        .before(GameSetupSet::Resources),
+                    apply_deferred
+                        .after(GameSetupSet::Resources)
+                        .before(GameSetupSet::Spawn),
+                    apply_deferred
+                        .after(GameSetupSet::Spawn)
+                        .before(GameSetupSet::Place),
+                    apply_deferred
+                        .after(GameSetupSet::Place)
+                        .before(GameSetupSet::Restore),
+                ),
+            )
+            .add_systems(OnExit(AppState::InGame), unpause);
+    }
+}
+
+fn unpause(mut next_pause_state: ResMut<NextState<PauseState>>) {
+    next_pause_state.set(PauseState::Running);
+}
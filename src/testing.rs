@@ -0,0 +1,163 @@
+// small boards for tests, played through the same
+// systems that apply commands in the game. a board
+// is written one row of hexes per line, the line is
+// the hex's `y` and the column its `x`:
+//
+//     . B . .
+//     . . s .
+//
+// `.` is an empty tile and letters are units, upper
+// case for the first team and lower case for the second
+
+use std::collections::HashMap;
+
+use bevy::{ecs::system::CommandQueue, prelude::*};
+use hexx::{hex, Hex};
+
+use crate::{
+    board::{
+        components::{HexTile, Team, TileVariant},
+        resources::BoardIndex,
+        systems::sync_board_index,
+    },
+    buildings::systems::despawn_destroyed_buildings,
+    command::{
+        events::{CommandEvent, GameCommand, GameEvent},
+        rules::{CaptureRules, CombatRng, CombatRules},
+        systems::apply_game_commands,
+    },
+    player::resources::{TeamCoins, TurnCounter, TurnOrder},
+    units::{
+        components::{Action, Unit, UnitDefault},
+        systems::{despawn_dead_units, spawn_unit},
+    },
+};
+
+pub const FIRST: Team = Team::Player(0);
+pub const SECOND: Team = Team::Player(1);
+
+pub struct TestBoard {
+    pub app: App,
+}
+
+impl TestBoard {
+    // every unit starts out able to move and attack
+    pub fn parse(board: &str) -> Self {
+        let mut tiles = Vec::new();
+        let mut units = Vec::new();
+        for (y, line) in board.lines().filter(|l| !l.trim().is_empty()).enumerate() {
+            for (x, token) in line.split_whitespace().enumerate() {
+                let coordinate = hex(x as i32, y as i32);
+                tiles.push(HexTile {
+                    coordinate,
+                    variant: TileVariant::Land,
+                    capture_progress: 0,
+                    team: Team::Neutral,
+                    resource: None,
+                });
+
+                if let Some(mut unit) = parse_unit(token, coordinate) {
+                    unit.actions = vec![Action::Move, Action::Attack];
+                    units.push(unit);
+                }
+            }
+        }
+
+        TestBoard::new(tiles, units)
+    }
+
+    pub fn new(tiles: Vec<HexTile>, units: Vec<Unit>) -> Self {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .add_event::<CommandEvent>()
+            .add_event::<GameEvent>()
+            .init_resource::<BoardIndex>()
+            .init_resource::<CaptureRules>()
+            .init_resource::<CombatRules>()
+            .init_resource::<CombatRng>()
+            .insert_resource(TurnOrder::new(vec![FIRST, SECOND]))
+            .insert_resource(TurnCounter(0))
+            .insert_resource(TeamCoins(vec![0, 0]))
+            .add_systems(
+                Update,
+                (
+                    sync_board_index,
+                    apply_game_commands,
+                    apply_deferred,
+                    despawn_dead_units,
+                    despawn_destroyed_buildings,
+                )
+                    .chain(),
+            );
+
+        app.world.spawn_batch(tiles);
+
+        let mut queue = CommandQueue::default();
+        let mut commands = Commands::new(&mut queue, &app.world);
+        for unit in units {
+            spawn_unit(&mut commands, unit);
+        }
+        queue.apply(&mut app.world);
+        app.update();
+
+        TestBoard { app }
+    }
+
+    pub fn command(&mut self, command: GameCommand) {
+        self.app.world.send_event(CommandEvent::local(command));
+        self.app.update();
+    }
+
+    pub fn attack(&mut self, from: Hex, target: Hex) {
+        self.command(GameCommand::Attack { from, target });
+    }
+
+    pub fn move_unit(&mut self, from: Hex, to: Hex) {
+        self.command(GameCommand::Move { from, to });
+    }
+
+    // both teams end their turn
+    pub fn end_round(&mut self) {
+        self.command(GameCommand::EndTurn);
+        self.command(GameCommand::EndTurn);
+    }
+
+    pub fn active_team(&self) -> Team {
+        self.app.world.resource::<TurnOrder>().active()
+    }
+
+    pub fn unit(&mut self, at: Hex) -> Option<Unit> {
+        self.units().into_iter().find(|unit| unit.position == at)
+    }
+
+    pub fn units(&mut self) -> Vec<Unit> {
+        self.units_by_entity().into_values().collect()
+    }
+
+    pub fn units_by_entity(&mut self) -> HashMap<Entity, Unit> {
+        self.app
+            .world
+            .query::<(Entity, &Unit)>()
+            .iter(&self.app.world)
+            .map(|(entity, unit)| (entity, unit.clone()))
+            .collect()
+    }
+}
+
+fn parse_unit(token: &str, position: Hex) -> Option<Unit> {
+    let letter = token.chars().next()?;
+    let kind = match letter.to_ascii_lowercase() {
+        'a' => UnitDefault::Archer,
+        'b' => UnitDefault::BladeDancer,
+        's' => UnitDefault::Scout,
+        'k' => UnitDefault::Knight,
+        'c' => UnitDefault::Catapult,
+        'p' => UnitDefault::Sniper,
+        'n' => UnitDefault::Newt,
+        '.' => return None,
+        _ => panic!("{token} is not a unit"),
+    };
+    let team = if letter.is_uppercase() { FIRST } else { SECOND };
+
+    Some(Unit::new_default(kind, position, team))
+}
@@ -1,7 +1,31 @@
 use bevy::prelude::*;
+use hexx::Hex;
 
 #[derive(Component)]
 pub struct TileText;
 
 #[derive(Component)]
 pub struct CoinText;
+
+// what attacking the hovered enemy would do
+#[derive(Component)]
+pub struct ForecastText;
+
+// everything about the hovered unit, ours or not
+#[derive(Component)]
+pub struct UnitPanel;
+
+#[derive(Component)]
+pub struct UnitPanelText;
+
+// holds the cells, as many rings of
+// them as the hovered unit reaches
+#[derive(Component)]
+pub struct PatternDiagram {
+    pub radius: u32,
+}
+
+// one hex of the move and attack diagram,
+// relative to the unit in the middle
+#[derive(Component)]
+pub struct PatternCell(pub Hex);
@@ -1,8 +1,17 @@
 use bevy::prelude::*;
 
-use self::systems::{
-    generate_player_coin_text, generate_tile_info_text, update_player_coin_text,
-    update_tile_info_text,
+use crate::{
+    state::{game_running, AppState, GameSetupSet},
+    util::despawn_all,
+};
+
+use self::{
+    components::{CoinText, ForecastText, TileText, UnitPanel},
+    systems::{
+        generate_forecast_text, generate_player_coin_text, generate_tile_info_text,
+        generate_unit_panel, update_forecast_text, update_player_coin_text, update_tile_info_text,
+        update_unit_panel,
+    },
 };
 
 mod components;
@@ -12,7 +21,34 @@ pub struct TextPlugin;
 
 impl Plugin for TextPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Startup, (generate_tile_info_text, generate_player_coin_text))
-            .add_systems(Update, (update_tile_info_text, update_player_coin_text));
+        app.add_systems(
+            OnEnter(AppState::InGame),
+            (
+                generate_tile_info_text,
+                generate_player_coin_text,
+                generate_forecast_text,
+                generate_unit_panel,
+            )
+                .in_set(GameSetupSet::Spawn),
+        )
+        .add_systems(
+            OnExit(AppState::InGame),
+            (
+                despawn_all::<TileText>,
+                despawn_all::<CoinText>,
+                despawn_all::<ForecastText>,
+                despawn_all::<UnitPanel>,
+            ),
+        )
+        .add_systems(
+            Update,
+            (
+                update_tile_info_text,
+                update_player_coin_text,
+                update_forecast_text,
+                update_unit_panel,
+            )
+                .run_if(game_running),
+        );
     }
 }
@@ -1,8 +1,26 @@
 use bevy::prelude::*;
+use hexx::{Hex, HexLayout, HexOrientation};
 
-use crate::{board::components::HexTile, player::resources::PlayerCoins, util::cursor_to_hex};
+use crate::{
+    board::{components::HexTile, resources::BoardIndex},
+    buildings::components::Building,
+    clock::resources::{format_clock, TurnClocks},
+    command::rules::CombatRules,
+    player::{
+        income::IncomeBreakdown,
+        resources::{PlayerTeam, TeamCoins, TurnOrder},
+    },
+    state::{MatchSettings, TimeControl},
+    units::{
+        components::{Action, AttackForecast, Order, Unit},
+        resources::SelectedUnit,
+    },
+    util::BoardCursor,
+};
 
-use super::components::{CoinText, TileText};
+use super::components::{
+    CoinText, ForecastText, PatternCell, PatternDiagram, TileText, UnitPanel, UnitPanelText,
+};
 
 pub fn generate_tile_info_text(mut commands: Commands, asset_server: Res<AssetServer>) {
     commands.spawn((
@@ -47,29 +65,100 @@ pub fn generate_tile_info_text(mut commands: Commands, asset_server: Res<AssetSe
                 font_size: 40.0,
                 color: Color::GOLD,
             }),
+            TextSection::new(
+                "\nUnit: ",
+                TextStyle {
+                    font: asset_server.load("fonts/arial.ttf"),
+                    font_size: 40.0,
+                    color: Color::WHITE,
+                },
+            ),
+            TextSection::from_style(TextStyle {
+                font: asset_server.load("fonts/arial.ttf"),
+                font_size: 40.0,
+                color: Color::GOLD,
+            }),
         ]),
         TileText,
     ));
 }
 
 pub fn update_tile_info_text(
-    windows: Query<&Window>,
+    cursor: BoardCursor,
     hexes: Query<&HexTile>,
+    buildings: Query<&Building>,
+    units: Query<&Unit>,
+    board_index: Res<BoardIndex>,
+    combat: (Res<SelectedUnit>, Res<CombatRules>),
     mut tile_text: Query<&mut Text, With<TileText>>,
 ) {
-    let Some(hovered_hex) = cursor_to_hex(windows) else {
+    let (selected_unit, combat_rules) = combat;
+    let Some(hovered_hex) = cursor.hex() else {
         return;
     };
 
     let mut hex_coord = "None".to_string();
     let mut hex_capture = "??".to_string();
     let mut hex_team = "??".to_string();
+    let mut hex_unit = "None".to_string();
 
-    for hex in &hexes {
-        if hex.coordinate == hovered_hex {
-            hex_coord = format!("{:?} {:?}", hex.variant, hex.coordinate);
-            hex_capture = format!("{}", hex.capture_progress);
-            hex_team = format!("{:?}", hex.team);
+    if let Some(hex) = board_index
+        .tile(hovered_hex)
+        .and_then(|entity| hexes.get(entity).ok())
+    {
+        hex_coord = match hex.resource {
+            Some(resource) => format!("{} {:?}", resource.name(), hex.coordinate),
+            None => format!("{:?} {:?}", hex.variant, hex.coordinate),
+        };
+        if let Some(building) = board_index
+            .building(hovered_hex)
+            .and_then(|entity| buildings.get(entity).ok())
+        {
+            hex_coord += &format!(
+                "\n{} {}/{}",
+                building.kind.name(),
+                building.health,
+                building.max_health
+            );
+        }
+        hex_capture = format!("{}", hex.capture_progress);
+        hex_team = format!("{:?}", hex.team);
+    }
+
+    if let Some(unit) = board_index
+        .unit(hovered_hex)
+        .and_then(|entity| units.get(entity).ok())
+    {
+        hex_unit = format!(
+            "{:?} {}/{}\nLevel {} ({} xp)",
+            unit.kind,
+            unit.health,
+            unit.max_health,
+            unit.level(),
+            unit.experience
+        );
+
+        for promotion in &unit.promotions {
+            hex_unit += &format!("\n{}", promotion.name());
+        }
+
+        if unit.pending_promotions() > 0 {
+            hex_unit += "\nPromotion ready";
+        }
+
+        if let Some(attacker) = selected_unit
+            .0
+            .and_then(|entity| units.get(entity).ok())
+            .filter(|attacker| attacker.team != unit.team)
+        {
+            let distance = attacker.position.unsigned_distance_to(unit.position);
+            let (hit, critical, damage) =
+                combat_rules.expected(attacker.damage, &unit.keywords, distance);
+            hex_unit += &format!(
+                "\nExpected damage {damage:.1} (hit {:.0}%, crit {:.0}%)",
+                hit * 100.,
+                critical * 100.
+            );
         }
     }
 
@@ -78,6 +167,7 @@ pub fn update_tile_info_text(
         text.sections[1].value = hex_coord.to_string();
         text.sections[3].value = hex_capture.to_string();
         text.sections[5].value = hex_team.to_string();
+        text.sections[7].value = hex_unit.to_string();
     }
 }
 
@@ -98,17 +188,398 @@ pub fn generate_player_coin_text(mut commands: Commands, asset_server: Res<Asset
                 font_size: 40.0,
                 color: Color::GOLD,
             }),
-        ]),
+            // the time left, empty without a time limit
+            TextSection::from_style(TextStyle {
+                font: asset_server.load("fonts/arial.ttf"),
+                font_size: 30.0,
+                color: Color::WHITE,
+            }),
+            TextSection::new(
+                "\nIncome: ",
+                TextStyle {
+                    font: asset_server.load("fonts/arial.ttf"),
+                    font_size: 30.0,
+                    color: Color::WHITE,
+                },
+            ),
+            TextSection::from_style(TextStyle {
+                font: asset_server.load("fonts/arial.ttf"),
+                font_size: 30.0,
+                color: Color::GOLD,
+            }),
+            // one line per source of income
+            TextSection::from_style(TextStyle {
+                font: asset_server.load("fonts/arial.ttf"),
+                font_size: 24.0,
+                color: Color::GRAY,
+            }),
+        ])
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            right: Val::Px(10.),
+            top: Val::Px(10.),
+            ..default()
+        }),
         CoinText,
     ));
 }
 
 pub fn update_player_coin_text(
-    player_coins: Res<PlayerCoins>,
-    mut coin_text: Query<&mut Text, With<CoinText>>,
+    team_coins: Res<TeamCoins>,
+    player_team: Res<PlayerTeam>,
+    hexes: (Query<&HexTile>, Query<(), Changed<HexTile>>),
+    buildings: (Query<&Building>, Query<(), Changed<Building>>),
+    mut removed_buildings: RemovedComponents<Building>,
+    mut coin_text: Query<(Ref<CoinText>, &mut Text)>,
+    clock: (Res<TurnClocks>, Res<MatchSettings>, Res<TurnOrder>),
 ) {
-    for mut text in &mut coin_text {
+    let (clocks, match_settings, turn_order) = clock;
+    let (hexes, changed_hexes) = hexes;
+    let (buildings, changed_buildings) = buildings;
+    // the breakdown only changes with the tiles and buildings
+    let tiles_changed = player_team.is_changed()
+        || !changed_hexes.is_empty()
+        || !changed_buildings.is_empty()
+        || removed_buildings.iter().count() > 0;
+
+    for (coin_text, mut text) in &mut coin_text {
         // Update the value of the second section
-        text.sections[1].value = format!("{}", player_coins.0);
+        text.sections[1].value = format!("{}", team_coins.get(player_team.0));
+        text.sections[2].value = clock_lines(&clocks, match_settings.time_control, &turn_order);
+
+        if !tiles_changed && !coin_text.is_added() {
+            continue;
+        }
+
+        let income = IncomeBreakdown::for_team(player_team.0, hexes.iter(), buildings.iter());
+
+        text.sections[4].value = format!("+{} per turn", income.total());
+        text.sections[5].value = income
+            .items()
+            .iter()
+            .map(|(label, coins)| format!("\n{label}: +{coins}"))
+            .collect();
+    }
+}
+
+// the active team's time, with a chess clock every team's
+fn clock_lines(clocks: &TurnClocks, control: TimeControl, turn_order: &TurnOrder) -> String {
+    let active = turn_order.active();
+
+    match control {
+        TimeControl::Unlimited => String::new(),
+        TimeControl::PerTurn { .. } => clocks
+            .left(control, active, true)
+            .map(|left| format!("\nTime: {}", format_clock(left)))
+            .unwrap_or_default(),
+        TimeControl::ChessClock { .. } => turn_order
+            .standing()
+            .iter()
+            .filter_map(|team| {
+                let left = format_clock(clocks.left(control, *team, *team == active)?);
+                Some(match *team == active {
+                    true => format!("\n{team:?}: {left} (playing)"),
+                    false => format!("\n{team:?}: {left}"),
+                })
+            })
+            .collect(),
     }
 }
+
+pub fn generate_forecast_text(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.spawn((
+        TextBundle::from_sections([
+            TextSection::new(
+                "Attack\n",
+                TextStyle {
+                    font: asset_server.load("fonts/arial.ttf"),
+                    font_size: 30.0,
+                    color: Color::WHITE,
+                },
+            ),
+            TextSection::from_style(TextStyle {
+                font: asset_server.load("fonts/arial.ttf"),
+                font_size: 24.0,
+                color: Color::GOLD,
+            }),
+        ])
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            // the objectives are below
+            right: Val::Px(10.),
+            top: Val::Percent(40.),
+            ..default()
+        }),
+        ForecastText,
+    ));
+}
+
+// shown while one of our units is selected and an
+// enemy it can attack right now is hovered
+pub fn update_forecast_text(
+    cursor: BoardCursor,
+    selected: (Res<SelectedUnit>, Res<PlayerTeam>, Res<CombatRules>),
+    targets: (Query<&Unit>, Query<&Building>),
+    board_index: Res<BoardIndex>,
+    mut forecast_text: Query<(&mut Text, &mut Visibility), With<ForecastText>>,
+) {
+    let (selected_unit, player_team, combat_rules) = selected;
+    let (units, buildings) = targets;
+
+    let forecast = cursor.hex().and_then(|hovered_hex| {
+        let attacker = selected_unit
+            .0
+            .and_then(|entity| units.get(entity).ok())
+            .filter(|unit| unit.team == player_team.0)
+            .filter(|unit| unit.actions.contains(&Action::Attack))
+            .filter(|unit| unit.relative_attack_hexes().contains(&hovered_hex))?;
+
+        // a unit on the tile is hit before the building
+        match board_index.unit(hovered_hex) {
+            Some(entity) => units
+                .get(entity)
+                .ok()
+                .filter(|unit| unit.team != attacker.team)
+                .map(|unit| attacker.forecast(unit)),
+            None => board_index
+                .building(hovered_hex)
+                .and_then(|entity| buildings.get(entity).ok())
+                .filter(|building| building.team != attacker.team)
+                .map(|building| attacker.forecast(building)),
+        }
+    });
+
+    for (mut text, mut visibility) in &mut forecast_text {
+        let Some(forecast) = forecast else {
+            visibility.set_if_neq(Visibility::Hidden);
+            continue;
+        };
+
+        visibility.set_if_neq(Visibility::Inherited);
+        text.sections[1].value = forecast_lines(&forecast, combat_rules.random);
+    }
+}
+
+fn forecast_lines(forecast: &AttackForecast, random: bool) -> String {
+    let mut lines = format!("Deals {} damage", forecast.damage);
+    if random {
+        lines += " before dice";
+    }
+    if forecast.kills {
+        lines += ", kills";
+    }
+
+    if forecast.retaliation > 0 {
+        lines += &format!("\nStrike back: {} damage", forecast.retaliation);
+        if forecast.attacker_dies {
+            lines += ", dies";
+        }
+    }
+
+    if let Some(hex) = forecast.moves_to {
+        lines += &format!("\nMoves to {:?}", hex);
+    }
+
+    lines += if forecast.keeps_attack {
+        "\nCan attack again"
+    } else {
+        "\nUses up the attack"
+    };
+
+    lines
+}
+
+const DIAGRAM_SIZE: f32 = 140.;
+
+// as big as the hovered unit's longest reach allows, same
+// orientation as the board, y already points down in the ui
+fn pattern_layout(radius: u32) -> (HexLayout, f32) {
+    let hex_size = DIAGRAM_SIZE / (2 * radius + 2) as f32 / 3f32.sqrt();
+    let layout = HexLayout {
+        orientation: HexOrientation::Pointy,
+        origin: Vec2::ZERO,
+        hex_size: Vec2::splat(hex_size),
+        invert_x: false,
+        invert_y: false,
+    };
+
+    (layout, hex_size * 1.6)
+}
+
+fn pattern_radius(unit: &Unit) -> u32 {
+    unit.move_hexes
+        .iter()
+        .chain(&unit.attack_hexes)
+        .map(|hex| hex.ulength())
+        .max()
+        .unwrap_or(0)
+        .max(1)
+}
+
+const MOVE_COLOR: Color = Color::rgb(0.3, 0.6, 1.);
+const ATTACK_COLOR: Color = Color::rgb(0.9, 0.3, 0.3);
+const MOVE_AND_ATTACK_COLOR: Color = Color::rgb(0.7, 0.4, 0.9);
+const EMPTY_CELL_COLOR: Color = Color::rgba(1., 1., 1., 0.1);
+
+pub fn generate_unit_panel(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                left: Val::Percent(35.),
+                bottom: Val::Px(10.),
+                column_gap: Val::Px(10.),
+                padding: UiRect::all(Val::Px(8.)),
+                ..default()
+            },
+            background_color: Color::rgba(0., 0., 0., 0.6).into(),
+            visibility: Visibility::Hidden,
+            ..default()
+        })
+        .insert(UnitPanel)
+        .with_children(|panel| {
+            panel
+                .spawn(TextBundle::from_sections([
+                    TextSection::from_style(TextStyle {
+                        font: asset_server.load("fonts/arial.ttf"),
+                        font_size: 26.0,
+                        color: Color::GOLD,
+                    }),
+                    TextSection::from_style(TextStyle {
+                        font: asset_server.load("fonts/arial.ttf"),
+                        font_size: 20.0,
+                        color: Color::WHITE,
+                    }),
+                ]))
+                .insert(UnitPanelText);
+
+            panel
+                .spawn(NodeBundle {
+                    style: Style {
+                        width: Val::Px(DIAGRAM_SIZE),
+                        height: Val::Px(DIAGRAM_SIZE),
+                        ..default()
+                    },
+                    ..default()
+                })
+                // the cells are spawned for the first unit hovered
+                .insert(PatternDiagram { radius: 0 });
+        });
+}
+
+pub fn update_unit_panel(
+    mut commands: Commands,
+    hover: (BoardCursor, Res<BoardIndex>),
+    units: Query<(&Unit, Option<&Order>)>,
+    mut panels: Query<&mut Visibility, With<UnitPanel>>,
+    mut panel_text: Query<&mut Text, With<UnitPanelText>>,
+    mut diagrams: Query<(Entity, &mut PatternDiagram)>,
+    mut cells: Query<(&PatternCell, &mut BackgroundColor)>,
+) {
+    let (cursor, board_index) = hover;
+    let hovered = cursor
+        .hex()
+        .and_then(|hex| board_index.unit(hex))
+        .and_then(|entity| units.get(entity).ok());
+
+    for mut visibility in &mut panels {
+        visibility.set_if_neq(match hovered {
+            Some(_) => Visibility::Inherited,
+            None => Visibility::Hidden,
+        });
+    }
+
+    let Some((unit, order)) = hovered else {
+        return;
+    };
+
+    for mut text in &mut panel_text {
+        text.sections[0].value = format!("{:?} ({:?})", unit.kind, unit.team);
+        text.sections[1].value = unit_lines(unit, order);
+    }
+
+    let radius = pattern_radius(unit);
+    for (entity, mut diagram) in &mut diagrams {
+        if diagram.radius == radius {
+            continue;
+        }
+
+        // a longer or shorter reach than the
+        // last unit's, the cells are resized
+        diagram.radius = radius;
+        let (layout, cell_size) = pattern_layout(radius);
+        commands
+            .entity(entity)
+            .despawn_descendants()
+            .with_children(|diagram| {
+                for hex in Hex::ZERO.range(radius) {
+                    let center =
+                        layout.hex_to_world_pos(hex) + Vec2::splat((DIAGRAM_SIZE - cell_size) / 2.);
+                    diagram
+                        .spawn(NodeBundle {
+                            style: Style {
+                                position_type: PositionType::Absolute,
+                                left: Val::Px(center.x),
+                                top: Val::Px(center.y),
+                                width: Val::Px(cell_size),
+                                height: Val::Px(cell_size),
+                                ..default()
+                            },
+                            background_color: cell_color(unit, hex).into(),
+                            ..default()
+                        })
+                        .insert(PatternCell(hex));
+                }
+            });
+    }
+
+    for (cell, mut color) in &mut cells {
+        color.0 = cell_color(unit, cell.0);
+    }
+}
+
+fn cell_color(unit: &Unit, hex: Hex) -> Color {
+    let moves = unit.move_hexes.contains(&hex);
+    let attacks = unit.attack_hexes.contains(&hex);
+    match (moves, attacks) {
+        _ if hex == Hex::ZERO => Color::WHITE,
+        (true, true) => MOVE_AND_ATTACK_COLOR,
+        (true, false) => MOVE_COLOR,
+        (false, true) => ATTACK_COLOR,
+        (false, false) => EMPTY_CELL_COLOR,
+    }
+}
+
+fn unit_lines(unit: &Unit, order: Option<&Order>) -> String {
+    let mut lines = format!(
+        "\nHealth {}/{}, damage {}",
+        unit.health, unit.max_health, unit.damage
+    );
+
+    for keyword in &unit.keywords {
+        lines += &format!("\n{}: {}", keyword.name(), keyword.description());
+    }
+
+    let actions = unit
+        .actions
+        .iter()
+        .map(|action| format!("{action:?}"))
+        .collect::<Vec<_>>();
+    lines += &match actions.is_empty() {
+        true => "\nNo actions left".to_string(),
+        false => format!("\nCan still: {}", actions.join(", ")),
+    };
+
+    if let Some(turns) = unit.waiting_turns() {
+        lines += &format!("\nMoves again in {turns} turns");
+    }
+
+    match order {
+        Some(Order::MoveTo(destination)) => lines += &format!("\nHeading to {destination:?}"),
+        Some(Order::Fortify) => lines += "\nFortified",
+        None => {}
+    }
+
+    lines += "\nBlue moves, red attacks, purple both";
+    lines
+}
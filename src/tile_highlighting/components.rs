@@ -1,6 +1,11 @@
 use bevy::prelude::*;
 
-use crate::units::components::Action;
+#[derive(Component)]
+pub struct TilePurposeSprite;
+
+#[derive(Component)]
+pub struct ThreatSprite;
 
+// the planned path of one of our units with a MoveTo order
 #[derive(Component)]
-pub struct TilePurposeSprite(pub Action);
+pub struct OrderArrow;
@@ -1,27 +1,45 @@
 use bevy::prelude::*;
 
-use self::systems::{
-    color_tile_purpose_sprites, despawn_tile_purpose_sprites, highlight_hovered_hex,
-    highlight_unit_hex, remove_tile_highlights, spawn_tile_purpose_sprites,
+use crate::{
+    state::{game_running, AppState},
+    util::despawn_all,
+};
+
+use self::{
+    components::{OrderArrow, ThreatSprite, TilePurposeSprite},
+    resources::ThreatOverlay,
+    systems::{
+        setup_threat_shades, toggle_threat_overlay, update_order_arrows, update_threat_overlay,
+        update_tile_highlights, update_tile_purpose_sprites,
+    },
 };
 
 mod components;
-mod systems;
+pub mod resources;
+pub mod systems;
 pub struct TileHighlighting;
 
 impl Plugin for TileHighlighting {
     fn build(&self, app: &mut App) {
-        app.add_systems(
-            Update,
-            (
-                remove_tile_highlights,
-                highlight_unit_hex,
-                highlight_hovered_hex,
+        app.init_resource::<ThreatOverlay>()
+            .add_systems(Startup, setup_threat_shades)
+            .add_systems(
+                OnExit(AppState::InGame),
+                (
+                    despawn_all::<TilePurposeSprite>,
+                    despawn_all::<ThreatSprite>,
+                    despawn_all::<OrderArrow>,
+                ),
             )
-                .chain(),
-        )
-        .add_systems(PreUpdate, despawn_tile_purpose_sprites)
-        .add_systems(Update, spawn_tile_purpose_sprites)
-        .add_systems(PostUpdate, color_tile_purpose_sprites);
+            .add_systems(
+                Update,
+                (
+                    update_tile_highlights,
+                    update_tile_purpose_sprites,
+                    update_order_arrows,
+                    (toggle_threat_overlay, update_threat_overlay).chain(),
+                )
+                    .run_if(game_running),
+            );
     }
 }
@@ -0,0 +1,135 @@
+use std::collections::{HashMap, HashSet};
+
+use bevy::prelude::*;
+use hexx::Hex;
+
+use crate::units::components::Unit;
+
+// how many shades of the overlay there are, from a
+// single weak threat up to the most threatened hex
+pub const THREAT_SHADES: usize = 4;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ThreatSide {
+    Enemy,
+    Own,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum ThreatMeasure {
+    #[default]
+    Units,
+    Damage,
+}
+
+// shades every hex the other teams (or our own)
+// units could attack by the next turn
+#[derive(Resource, Default)]
+pub struct ThreatOverlay {
+    pub side: Option<ThreatSide>,
+    pub measure: ThreatMeasure,
+}
+
+impl ThreatOverlay {
+    // off, then the enemies, then our own
+    pub fn next_side(&mut self) {
+        self.side = match self.side {
+            None => Some(ThreatSide::Enemy),
+            Some(ThreatSide::Enemy) => Some(ThreatSide::Own),
+            Some(ThreatSide::Own) => None,
+        };
+    }
+}
+
+#[derive(Resource)]
+pub struct ThreatShades {
+    pub mesh: Handle<Mesh>,
+    // weakest first, THREAT_SHADES of each
+    pub enemy: Vec<Handle<ColorMaterial>>,
+    pub own: Vec<Handle<ColorMaterial>>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct Threat {
+    pub units: i32,
+    pub damage: i32,
+}
+
+impl Threat {
+    pub fn measure(&self, measure: ThreatMeasure) -> i32 {
+        match measure {
+            ThreatMeasure::Units => self.units,
+            ThreatMeasure::Damage => self.damage,
+        }
+    }
+}
+
+// every hex the units could attack after one more move,
+// `can_stand` tells the tiles they could move to
+pub fn threat_map<'a>(
+    units: impl IntoIterator<Item = &'a Unit>,
+    can_stand: impl Fn(Hex) -> bool,
+) -> HashMap<Hex, Threat> {
+    let mut threats = HashMap::<Hex, Threat>::new();
+
+    for unit in units {
+        let standing = unit
+            .relative_move_hexes()
+            .into_iter()
+            .filter(|hex| can_stand(*hex))
+            .chain([unit.position]);
+
+        // a unit counts once per hex, however it gets there
+        let covered = standing
+            .flat_map(|at| unit.attack_hexes.iter().map(move |hex| *hex + at))
+            .collect::<HashSet<_>>();
+
+        for hex in covered {
+            let threat = threats.entry(hex).or_default();
+            threat.units += 1;
+            threat.damage += unit.damage;
+        }
+    }
+
+    threats
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{board::components::Team, units::components::UnitDefault};
+
+    #[test]
+    fn threats_add_up_over_every_reachable_tile() {
+        let scout = Unit::new_default(UnitDefault::Scout, Hex::ZERO, Team::Player(1));
+        let knight = Unit::new_default(UnitDefault::Knight, Hex::new(2, 0), Team::Player(1));
+
+        let standing_still = threat_map([&scout, &knight], |_| false);
+        assert_eq!(
+            standing_still.get(&Hex::new(1, 0)),
+            Some(&Threat {
+                units: 2,
+                damage: 3
+            })
+        );
+        assert_eq!(
+            standing_still.get(&Hex::new(-1, 0)),
+            Some(&Threat {
+                units: 1,
+                damage: 1
+            })
+        );
+        assert_eq!(standing_still.get(&Hex::new(-3, 0)), None);
+
+        // scouts walk two hexes and hit the next
+        let moving = threat_map([&scout], |_| true);
+        assert_eq!(
+            moving.get(&Hex::new(-3, 0)),
+            Some(&Threat {
+                units: 1,
+                damage: 1
+            })
+        );
+        assert_eq!(moving.get(&Hex::new(-4, 0)), None);
+    }
+}
@@ -1,111 +1,139 @@
-use bevy::prelude::*;
+use bevy::{prelude::*, sprite::MaterialMesh2dBundle};
 use hexx::Hex;
 
 use crate::{
     board::{
-        components::{HexTile, Team},
-        resources::HexColors,
-        HEX_RADIUS, HEX_SIZE, HEX_LAYOUT,
+        components::HexTile,
+        resources::{BoardIndex, HexColors},
+        HEX_SIZE, HEX_LAYOUT,
     },
+    buildings::components::Building,
+    player::resources::PlayerTeam,
     units::{
-        components::{Action, Unit},
+        components::{Action, Order, Unit},
         resources::SelectedUnit,
-    }, util::cursor_to_hex,
+        systems::walkable,
+    },
+    util::BoardCursor,
 };
 
-use super::components::TilePurposeSprite;
-
-pub fn remove_tile_highlights(
-    mut hexes: Query<(&HexTile, &mut Handle<ColorMaterial>)>,
-    colors: Res<HexColors>,
-) {
-    for (hex, mut color_mat) in &mut hexes {
-        *color_mat = hex.base_color(&colors);
-    }
-}
-
-pub fn highlight_hovered_hex(
-    windows: Query<&Window>,
-    mut hexes: Query<(&HexTile, &mut Handle<ColorMaterial>)>,
-    colors: Res<HexColors>,
-) {
-    let Some(hovered_hex) = cursor_to_hex(windows) else {
-        return;
-    };
-
-    for (hex, mut color_mat) in &mut hexes {
-        if hex.coordinate != hovered_hex {
-            continue;
-        }
-
-        *color_mat = hex.strong_highlight(&colors);
-        break;
-    }
-}
+use super::{
+    components::{OrderArrow, ThreatSprite, TilePurposeSprite},
+    resources::{
+        threat_map, ThreatMeasure, ThreatOverlay, ThreatShades, ThreatSide, THREAT_SHADES,
+    },
+};
 
-pub fn highlight_unit_hex(
+// tile materials are only touched when the hovered
+// hex, the selection, a unit or a tile changed
+pub fn update_tile_highlights(
+    cursor: BoardCursor,
+    mut last_hovered_hex: Local<Option<Hex>>,
     selected_unit: Res<SelectedUnit>,
-    units: Query<&Unit>,
+    units: (Query<&Unit>, Query<(), Changed<Unit>>, RemovedComponents<Unit>),
     mut hexes: Query<(&HexTile, &mut Handle<ColorMaterial>)>,
+    changed_hexes: Query<(), Changed<HexTile>>,
     colors: Res<HexColors>,
 ) {
-    let Some(selected_entity) = selected_unit.0 else {
-        return;
-    };
+    let (units, changed_units, mut removed_units) = units;
+    let hovered_hex = cursor.hex();
+    let hover_changed = *last_hovered_hex != hovered_hex;
+    *last_hovered_hex = hovered_hex;
 
-    let Ok(unit) = units.get(selected_entity) else {
+    let units_changed = removed_units.iter().count() > 0 || !changed_units.is_empty();
+
+    if !hover_changed
+        && !units_changed
+        && !selected_unit.is_changed()
+        && !colors.is_changed()
+        && changed_hexes.is_empty()
+    {
         return;
-    };
+    }
 
     let mut strong_highlights = Vec::new();
     let mut weak_highlights = Vec::new();
 
-    if unit.actions.contains(&Action::Attack) {
-        strong_highlights.append(&mut unit.relative_attack_hexes());
-    } else {
-        weak_highlights.append(&mut unit.relative_attack_hexes());
-    };
+    if let Some(unit) = selected_unit.0.and_then(|entity| units.get(entity).ok()) {
+        if unit.actions.contains(&Action::Attack) {
+            strong_highlights.append(&mut unit.relative_attack_hexes());
+        } else {
+            weak_highlights.append(&mut unit.relative_attack_hexes());
+        };
 
-    if unit.actions.contains(&Action::Move) {
-        strong_highlights.append(&mut unit.relative_move_hexes());
-    } else {
-        weak_highlights.append(&mut unit.relative_move_hexes());
-    };
+        if unit.actions.contains(&Action::Move) {
+            strong_highlights.append(&mut unit.relative_move_hexes());
+        } else {
+            weak_highlights.append(&mut unit.relative_move_hexes());
+        };
+    }
 
     for (hex, mut color_mat) in &mut hexes {
-        if strong_highlights.contains(&hex.coordinate) {
-            *color_mat = hex.strong_highlight(&colors);
-            continue;
-        }
+        let material = if Some(hex.coordinate) == hovered_hex
+            || strong_highlights.contains(&hex.coordinate)
+        {
+            hex.strong_highlight(&colors)
+        } else if weak_highlights.contains(&hex.coordinate) {
+            hex.weak_highlight(&colors)
+        } else {
+            hex.base_color(&colors)
+        };
 
-        if weak_highlights.contains(&hex.coordinate) {
-            *color_mat = hex.weak_highlight(&colors);
-            continue;
-        }
+        color_mat.set_if_neq(material);
     }
 }
 
-pub fn spawn_tile_purpose_sprites(
+// purpose sprites are only rebuilt when the
+// selection or the selected unit changed
+pub fn update_tile_purpose_sprites(
     mut commands: Commands,
     asset_server: Res<AssetServer>,
+    colors: Res<HexColors>,
     selected_unit: Res<SelectedUnit>,
-    units: Query<&Unit>,
+    units: Query<Ref<Unit>>,
+    board_index: Res<BoardIndex>,
+    tile_purpose_sprites: Query<Entity, With<TilePurposeSprite>>,
 ) {
-    let Some(selected_entity) = selected_unit.0 else {
+    let selected = selected_unit.0.and_then(|entity| units.get(entity).ok());
+
+    // a despawned selection is still selected,
+    // so its sprites have to go
+    let selected_removed =
+        selected_unit.0.is_some() && selected.is_none() && !tile_purpose_sprites.is_empty();
+
+    if !selected_unit.is_changed()
+        && !colors.is_changed()
+        && !selected_removed
+        && !selected.as_ref().is_some_and(|unit| unit.is_changed())
+    {
+        return;
+    }
+
+    for entity in &tile_purpose_sprites {
+        commands.entity(entity).despawn();
+    }
+
+    let Some(unit) = selected else {
         return;
     };
 
-    let Ok(unit) = units.get(selected_entity) else {
+    let Some(team_colors) = colors.team(unit.team) else {
         return;
     };
 
-    let valid_hex_tiles = Hex::ZERO.range(HEX_RADIUS as u32).collect::<Vec<Hex>>();
+    let action_color = |action| {
+        if unit.actions.contains(&action) {
+            team_colors.unused_action_color
+        } else {
+            team_colors.used_action_color
+        }
+    };
 
     let mut both = unit.relative_move_hexes();
     both.retain(|cube| unit.relative_attack_hexes().contains(cube));
 
     for hex in unit.relative_move_hexes() {
-        if !valid_hex_tiles.contains(&hex) {
+        if !board_index.contains(hex) {
             continue;
         }
 
@@ -128,14 +156,18 @@ pub fn spawn_tile_purpose_sprites(
         commands
             .spawn(SpriteBundle {
                 transform,
+                sprite: Sprite {
+                    color: action_color(Action::Move),
+                    ..default()
+                },
                 texture: asset_server.load("sprites/move.png".to_string()),
                 ..default()
             })
-            .insert(TilePurposeSprite(Action::Move));
+            .insert(TilePurposeSprite);
     }
 
-    for hex in unit.relative_attack_hexes () {
-        if !valid_hex_tiles.contains(&hex) {
+    for hex in unit.relative_attack_hexes() {
+        if !board_index.contains(hex) {
             continue;
         }
 
@@ -158,53 +190,194 @@ pub fn spawn_tile_purpose_sprites(
         commands
             .spawn(SpriteBundle {
                 transform,
+                sprite: Sprite {
+                    color: action_color(Action::Attack),
+                    ..default()
+                },
                 texture: asset_server.load("sprites/attack.png".to_string()),
                 ..default()
             })
-            .insert(TilePurposeSprite(Action::Attack));
+            .insert(TilePurposeSprite);
     }
 }
 
-pub fn despawn_tile_purpose_sprites(
+pub fn setup_threat_shades(
     mut commands: Commands,
-    tile_purpose_sprites: Query<Entity, With<TilePurposeSprite>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
 ) {
-    for entity in &tile_purpose_sprites {
-        commands.entity(entity).despawn();
+    let mut shades = |r: f32, g: f32, b: f32| {
+        (1..=THREAT_SHADES)
+            .map(|level| {
+                let alpha = 0.15 + 0.45 * level as f32 / THREAT_SHADES as f32;
+                materials.add(ColorMaterial::from(Color::rgba(r, g, b, alpha)))
+            })
+            .collect::<Vec<_>>()
+    };
+
+    let enemy = shades(0.9, 0.1, 0.1);
+    let own = shades(0.1, 0.4, 0.9);
+    commands.insert_resource(ThreatShades {
+        mesh: meshes.add(shape::RegularPolygon::new(HEX_SIZE, 6).into()),
+        enemy,
+        own,
+    });
+}
+
+// O cycles between off, enemy threats and our
+// own coverage, I switches counting units or damage
+pub fn toggle_threat_overlay(keys: Res<Input<KeyCode>>, mut overlay: ResMut<ThreatOverlay>) {
+    if keys.just_released(KeyCode::O) {
+        overlay.next_side();
+    }
+
+    if keys.just_released(KeyCode::I) {
+        overlay.measure = match overlay.measure {
+            ThreatMeasure::Units => ThreatMeasure::Damage,
+            ThreatMeasure::Damage => ThreatMeasure::Units,
+        };
     }
 }
 
-pub fn color_tile_purpose_sprites(
-    mut sprites: Query<(&TilePurposeSprite, &mut Sprite)>,
-    colors: Res<HexColors>,
-    selected_unit: Res<SelectedUnit>,
-    units: Query<&Unit>,
+pub fn update_threat_overlay(
+    mut commands: Commands,
+    overlay: (Res<ThreatOverlay>, Res<ThreatShades>),
+    player_team: Res<PlayerTeam>,
+    board: (Res<BoardIndex>, Query<&Building>),
+    units: (Query<&Unit>, Query<(), Changed<Unit>>),
+    mut removed_units: RemovedComponents<Unit>,
+    threat_sprites: Query<Entity, With<ThreatSprite>>,
 ) {
-    let Some(selected_entity) = selected_unit.0 else {
+    let (overlay, shades) = overlay;
+    let (board_index, buildings) = board;
+    let (units, changed_units) = units;
+    let units_changed = removed_units.iter().count() > 0 || !changed_units.is_empty();
+
+    if !units_changed && !overlay.is_changed() && !player_team.is_changed() {
         return;
-    };
+    }
 
-    let Ok(unit) = units.get(selected_entity) else {
+    for entity in &threat_sprites {
+        commands.entity(entity).despawn();
+    }
+
+    let Some(side) = overlay.side else {
         return;
     };
 
-    let unused_color = match unit.team {
-        Team::Ally => colors.ally_unused_action_color,
-        Team::Enemy => colors.enemy_unused_action_color,
-        _ => colors.ally_unused_action_color,
+    let (covering, materials) = match side {
+        ThreatSide::Enemy => (
+            units.iter().filter(|unit| unit.team != player_team.0).collect::<Vec<_>>(),
+            &shades.enemy,
+        ),
+        ThreatSide::Own => (
+            units.iter().filter(|unit| unit.team == player_team.0).collect::<Vec<_>>(),
+            &shades.own,
+        ),
     };
 
-    let used_color = match unit.team {
-        Team::Ally => colors.ally_used_action_color,
-        Team::Enemy => colors.enemy_used_action_color,
-        _ => colors.ally_used_action_color,
-    };
+    let can_stand = |hex| walkable(&board_index, &buildings, hex);
 
-    for (tile_purpose_sprite, mut sprite) in &mut sprites {
-        if unit.actions.contains(&tile_purpose_sprite.0) {
-            sprite.color = unused_color;
-        } else {
-            sprite.color = used_color;
+    let threats = threat_map(covering.iter().copied(), can_stand);
+    let strongest = threats
+        .values()
+        .map(|threat| threat.measure(overlay.measure))
+        .max()
+        .unwrap_or(0)
+        .max(1);
+
+    for (hex, threat) in threats {
+        if !board_index.contains(hex) {
+            continue;
+        }
+
+        let strength = threat.measure(overlay.measure) as f32 / strongest as f32;
+        let shade = ((strength * THREAT_SHADES as f32).ceil() as usize).clamp(1, THREAT_SHADES);
+
+        commands
+            .spawn(MaterialMesh2dBundle {
+                mesh: shades.mesh.clone().into(),
+                material: materials[shade - 1].clone(),
+                transform: Transform::from_translation(
+                    HEX_LAYOUT.hex_to_world_pos(hex).extend(1.2),
+                ),
+                ..default()
+            })
+            .insert(ThreatSprite);
+    }
+}
+
+// a line along every move still planned and a
+// diamond on the destination, for our own units
+pub fn update_order_arrows(
+    mut commands: Commands,
+    player_team: Res<PlayerTeam>,
+    units: Query<(&Unit, Option<&Order>)>,
+    changed: (Query<(), Changed<Unit>>, Query<(), Changed<Order>>),
+    mut removed: (RemovedComponents<Unit>, RemovedComponents<Order>),
+    board: (Res<BoardIndex>, Query<&Building>),
+    arrows: Query<Entity, With<OrderArrow>>,
+) {
+    let (board_index, buildings) = board;
+    let changed = removed.0.iter().count() > 0
+        || removed.1.iter().count() > 0
+        || !changed.0.is_empty()
+        || !changed.1.is_empty();
+
+    if !changed && !player_team.is_changed() {
+        return;
+    }
+
+    for entity in &arrows {
+        commands.entity(entity).despawn();
+    }
+
+    let color = Color::rgba(1., 1., 1., 0.7);
+    for (unit, order) in &units {
+        let Some(Order::MoveTo(destination)) = order.copied() else {
+            continue;
+        };
+
+        if unit.team != player_team.0 {
+            continue;
+        }
+
+        let open = |hex| walkable(&board_index, &buildings, hex);
+        let Some(path) = unit.path_to(destination, open) else {
+            continue;
+        };
+
+        let mut from = HEX_LAYOUT.hex_to_world_pos(unit.position);
+        for hex in path {
+            let to = HEX_LAYOUT.hex_to_world_pos(hex);
+            let along = to - from;
+
+            commands
+                .spawn(SpriteBundle {
+                    sprite: Sprite {
+                        color,
+                        custom_size: Some(Vec2::new(along.length(), HEX_SIZE / 10.)),
+                        ..default()
+                    },
+                    transform: Transform::from_translation(((from + to) / 2.).extend(2.5))
+                        .with_rotation(Quat::from_rotation_z(along.y.atan2(along.x))),
+                    ..default()
+                })
+                .insert(OrderArrow);
+            from = to;
         }
+
+        commands
+            .spawn(SpriteBundle {
+                sprite: Sprite {
+                    color,
+                    custom_size: Some(Vec2::splat(HEX_SIZE / 3.)),
+                    ..default()
+                },
+                transform: Transform::from_translation(from.extend(2.5))
+                    .with_rotation(Quat::from_rotation_z(std::f32::consts::FRAC_PI_4)),
+                ..default()
+            })
+            .insert(OrderArrow);
     }
 }
@@ -0,0 +1,8 @@
+use bevy::prelude::*;
+
+#[derive(Component)]
+pub struct TutorialText;
+
+// outline around the hexes of the current step
+#[derive(Component)]
+pub struct TutorialHighlight;
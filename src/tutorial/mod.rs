@@ -0,0 +1,70 @@
+use bevy::prelude::*;
+
+use crate::{
+    command::CommandSet,
+    scenario::objectives::ScenarioOutcome,
+    state::{game_running, AppState, GameSetupSet},
+    util::despawn_all,
+};
+
+use self::{
+    components::{TutorialHighlight, TutorialText},
+    resources::TutorialProgress,
+    systems::{
+        advance_tutorial, draw_tutorial_highlights, end_tutorial, explain_keywords,
+        filter_tutorial_commands, start_tutorial, tutorial_scenario, update_tutorial_text,
+    },
+};
+
+pub mod components;
+pub mod resources;
+pub mod steps;
+pub mod systems;
+
+// the scenario started from the main menu
+pub const TUTORIAL_SCENARIO: &str = "Tutorial";
+
+// walks the player through the steps of a
+// scenario, allowing only what is being taught
+pub struct TutorialPlugin;
+
+impl Plugin for TutorialPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            OnEnter(AppState::InGame),
+            start_tutorial
+                .run_if(tutorial_scenario)
+                .in_set(GameSetupSet::Spawn),
+        )
+        .add_systems(
+            OnExit(AppState::InGame),
+            (
+                despawn_all::<TutorialText>,
+                despawn_all::<TutorialHighlight>,
+                end_tutorial,
+            ),
+        )
+        .add_systems(
+            Update,
+            (
+                filter_tutorial_commands
+                    .after(CommandSet::Issue)
+                    .before(CommandSet::Apply)
+                    .run_if(in_state(AppState::InGame)),
+                advance_tutorial
+                    .after(CommandSet::Apply)
+                    .run_if(in_state(AppState::InGame))
+                    .run_if(not(resource_exists::<ScenarioOutcome>())),
+                (
+                    explain_keywords,
+                    update_tutorial_text,
+                    draw_tutorial_highlights,
+                )
+                    .chain()
+                    .after(advance_tutorial)
+                    .run_if(game_running),
+            )
+                .run_if(resource_exists::<TutorialProgress>()),
+        );
+    }
+}
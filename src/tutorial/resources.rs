@@ -0,0 +1,19 @@
+use bevy::prelude::*;
+
+// where the player is in the tutorial of the scenario
+#[derive(Resource, Default)]
+pub struct TutorialProgress {
+    pub step: usize,
+    // keywords are explained once, the first
+    // time a unit with them comes up
+    pub explained: Vec<&'static str>,
+    // explanations shown with the current step
+    pub notes: Vec<String>,
+}
+
+impl TutorialProgress {
+    pub fn advance(&mut self) {
+        self.step += 1;
+        self.notes.clear();
+    }
+}
@@ -0,0 +1,111 @@
+use hexx::Hex;
+use serde::{Deserialize, Serialize};
+
+use crate::command::events::GameCommand;
+
+// what the player has to do to finish a step
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TutorialGoal {
+    // press enter after reading
+    Continue,
+    // select the unit standing here
+    Select(Hex),
+    Deselect,
+    PlaceCapital,
+    Move { to: Hex },
+    Attack { target: Hex },
+    Recruit,
+    Build,
+    EndTurn,
+    // end turns until the tile is ours
+    Capture(Hex),
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct TutorialStep {
+    pub text: String,
+    // outlined on the board, and where the
+    // step's commands may go when given
+    #[serde(default)]
+    pub highlight: Vec<Hex>,
+    pub goal: TutorialGoal,
+}
+
+impl TutorialStep {
+    // commands of the taught team that
+    // don't belong to the step are dropped
+    pub fn allows(&self, command: &GameCommand) -> bool {
+        let highlighted = |hex: &Hex| self.highlight.is_empty() || self.highlight.contains(hex);
+
+        match (self.goal, command) {
+            (_, GameCommand::Promote { .. }) => true,
+            (TutorialGoal::PlaceCapital, GameCommand::PlaceCapital { hex }) => highlighted(hex),
+            (TutorialGoal::Move { to }, GameCommand::Move { to: hex, .. }) => *hex == to,
+            (TutorialGoal::Attack { target }, GameCommand::Attack { target: hex, .. }) => {
+                *hex == target
+            }
+            (TutorialGoal::Recruit, GameCommand::Recruit { at, .. }) => highlighted(at),
+            (TutorialGoal::Build, GameCommand::Build { at, .. }) => highlighted(at),
+            (TutorialGoal::EndTurn | TutorialGoal::Capture(_), GameCommand::EndTurn) => true,
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use hexx::hex;
+
+    use crate::{buildings::components::BuildingKind, units::components::UnitDefault};
+
+    use super::*;
+
+    fn step(goal: TutorialGoal, highlight: Vec<Hex>) -> TutorialStep {
+        TutorialStep {
+            text: String::new(),
+            highlight,
+            goal,
+        }
+    }
+
+    #[test]
+    fn steps_only_allow_what_they_teach() {
+        let moving = step(TutorialGoal::Move { to: hex(0, 1) }, vec![hex(0, 1)]);
+        assert!(moving.allows(&GameCommand::Move {
+            from: hex(-1, 1),
+            to: hex(0, 1)
+        }));
+        assert!(!moving.allows(&GameCommand::Move {
+            from: hex(-1, 1),
+            to: hex(-1, 2)
+        }));
+        assert!(!moving.allows(&GameCommand::EndTurn));
+
+        let reading = step(TutorialGoal::Continue, vec![]);
+        assert!(!reading.allows(&GameCommand::EndTurn));
+
+        let capturing = step(TutorialGoal::Capture(hex(0, 1)), vec![hex(0, 1)]);
+        assert!(capturing.allows(&GameCommand::EndTurn));
+        assert!(!capturing.allows(&GameCommand::Move {
+            from: hex(0, 1),
+            to: hex(1, 1)
+        }));
+    }
+
+    #[test]
+    fn highlights_limit_where_commands_go() {
+        let building = step(TutorialGoal::Build, vec![hex(-3, 2)]);
+        let build = |at| GameCommand::Build {
+            at,
+            kind: BuildingKind::Wall,
+        };
+        assert!(building.allows(&build(hex(-3, 2))));
+        assert!(!building.allows(&build(hex(-1, 2))));
+
+        let recruiting = step(TutorialGoal::Recruit, vec![]);
+        assert!(recruiting.allows(&GameCommand::Recruit {
+            at: hex(4, 0),
+            kind: UnitDefault::Scout
+        }));
+    }
+}
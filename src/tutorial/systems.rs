@@ -0,0 +1,275 @@
+use std::collections::HashSet;
+
+use bevy::{ecs::event::ManualEventReader, prelude::*, sprite::MaterialMesh2dBundle};
+
+use crate::{
+    board::{
+        components::{HexTile, TileVariant},
+        outline::{outline_mesh, region_outlines},
+        resources::BoardIndex,
+        HEX_GAP,
+    },
+    command::events::{CommandEvent, CommandSource, GameCommand},
+    player::resources::TurnOrder,
+    scenario::{
+        objectives::ScenarioOutcome,
+        resources::{Profile, Scenario},
+        PROFILE_PATH, SCENARIO_TEAM,
+    },
+    units::{components::Unit, resources::SelectedUnit},
+};
+
+use super::{
+    components::{TutorialHighlight, TutorialText},
+    resources::TutorialProgress,
+    steps::TutorialGoal,
+};
+
+pub fn tutorial_scenario(scenario: Option<Res<Scenario>>) -> bool {
+    scenario.is_some_and(|scenario| !scenario.tutorial.is_empty())
+}
+
+pub fn start_tutorial(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(TutorialProgress::default());
+
+    commands.spawn((
+        TextBundle::from_section(
+            "",
+            TextStyle {
+                font: asset_server.load("fonts/arial.ttf"),
+                font_size: 24.0,
+                color: Color::WHITE,
+            },
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            left: Val::Percent(35.),
+            top: Val::Px(10.),
+            max_width: Val::Percent(30.),
+            ..default()
+        }),
+        TutorialText,
+    ));
+}
+
+pub fn end_tutorial(mut commands: Commands) {
+    commands.remove_resource::<TutorialProgress>();
+}
+
+// runs between issuing and applying commands, so
+// nothing outside of the current step happens
+pub fn filter_tutorial_commands(
+    scenario: Res<Scenario>,
+    progress: Res<TutorialProgress>,
+    turn_order: Res<TurnOrder>,
+    mut events: ResMut<Events<CommandEvent>>,
+    mut reader: Local<ManualEventReader<CommandEvent>>,
+) {
+    let Some(step) = scenario.tutorial.get(progress.step) else {
+        return;
+    };
+
+    let allowed = |event: &CommandEvent| {
+        event.source == CommandSource::Remote
+            || turn_order.active() != SCENARIO_TEAM
+            || step.allows(&event.command)
+    };
+
+    let issued = reader.iter(&events).copied().collect::<Vec<_>>();
+    if issued.iter().all(allowed) {
+        return;
+    }
+
+    // older events were read by everything
+    // after applying them last frame
+    events.drain().for_each(drop);
+    for event in issued.into_iter().filter(allowed) {
+        events.send(event);
+    }
+    reader.iter(&events).for_each(drop);
+}
+
+// checked against the state after commands were applied
+pub fn advance_tutorial(
+    mut commands: Commands,
+    scenario: Res<Scenario>,
+    mut progress: ResMut<TutorialProgress>,
+    mut game_commands: EventReader<CommandEvent>,
+    keys: Res<Input<KeyCode>>,
+    game: (
+        Res<SelectedUnit>,
+        Res<BoardIndex>,
+        Query<&Unit>,
+        Query<&HexTile>,
+    ),
+    mut profile: ResMut<Profile>,
+) {
+    let (selected_unit, board_index, units, hexes) = game;
+    let applied = game_commands
+        .iter()
+        .map(|event| event.command)
+        .collect::<Vec<_>>();
+
+    let Some(step) = scenario.tutorial.get(progress.step) else {
+        return;
+    };
+
+    let selected = selected_unit.0.and_then(|entity| units.get(entity).ok());
+    let ours = |hex| {
+        units
+            .iter()
+            .any(|unit| unit.position == hex && unit.team == SCENARIO_TEAM)
+    };
+
+    let done = match step.goal {
+        TutorialGoal::Continue => keys.just_released(KeyCode::Return),
+        TutorialGoal::Select(hex) => selected.is_some_and(|unit| unit.position == hex),
+        TutorialGoal::Deselect => selected.is_none(),
+        TutorialGoal::PlaceCapital => hexes.iter().any(|tile| {
+            tile.team == SCENARIO_TEAM && tile.variant == TileVariant::Capital
+        }),
+        TutorialGoal::Move { to } => ours(to),
+        TutorialGoal::Attack { target } => applied
+            .iter()
+            .any(|command| matches!(command, GameCommand::Attack { target: hex, .. } if *hex == target)),
+        // recruits and buildings are indexed as soon
+        // as they are spawned, before the queries see them
+        TutorialGoal::Recruit => applied.iter().any(|command| {
+            matches!(command, GameCommand::Recruit { at, .. } if board_index.unit(*at).is_some())
+        }),
+        TutorialGoal::Build => applied.iter().any(|command| {
+            matches!(command, GameCommand::Build { at, .. } if board_index.building(*at).is_some())
+        }),
+        TutorialGoal::EndTurn => applied.contains(&GameCommand::EndTurn),
+        TutorialGoal::Capture(hex) => hexes
+            .iter()
+            .any(|tile| tile.coordinate == hex && tile.team == SCENARIO_TEAM),
+    };
+
+    if !done {
+        return;
+    }
+
+    progress.advance();
+    if progress.step < scenario.tutorial.len() {
+        return;
+    }
+
+    commands.insert_resource(ScenarioOutcome::Victory);
+    profile.record_victory(&scenario.name, None, &[]);
+    if let Err(error) = profile.write(PROFILE_PATH) {
+        warn!("could not save the profile to {PROFILE_PATH}: {error}");
+    }
+}
+
+// keywords of the selected unit and the highlighted ones
+pub fn explain_keywords(
+    scenario: Res<Scenario>,
+    mut progress: ResMut<TutorialProgress>,
+    selected_unit: Res<SelectedUnit>,
+    units: Query<&Unit>,
+) {
+    let highlight = scenario
+        .tutorial
+        .get(progress.step)
+        .map_or(&[][..], |step| &step.highlight[..]);
+
+    let shown = selected_unit
+        .0
+        .and_then(|entity| units.get(entity).ok())
+        .into_iter()
+        .chain(
+            units
+                .iter()
+                .filter(|unit| highlight.contains(&unit.position)),
+        );
+
+    let mut notes = Vec::new();
+    for unit in shown {
+        for keyword in &unit.keywords {
+            let name = keyword.name();
+            if progress.explained.contains(&name) || notes.iter().any(|(n, _)| *n == name) {
+                continue;
+            }
+            notes.push((name, format!("{name}: {}", keyword.description())));
+        }
+    }
+
+    // only touched when there is something
+    // new, the highlights redraw on change
+    if notes.is_empty() {
+        return;
+    }
+
+    for (name, note) in notes {
+        progress.explained.push(name);
+        progress.notes.push(note);
+    }
+}
+
+pub fn update_tutorial_text(
+    scenario: Res<Scenario>,
+    progress: Res<TutorialProgress>,
+    mut tutorial_text: Query<&mut Text, With<TutorialText>>,
+) {
+    let mut value = match scenario.tutorial.get(progress.step) {
+        Some(step) => format!(
+            "({}/{}) {}",
+            progress.step + 1,
+            scenario.tutorial.len(),
+            step.text
+        ),
+        None => "Tutorial complete!".to_string(),
+    };
+    for note in &progress.notes {
+        value += &format!("\n{note}");
+    }
+
+    for mut text in &mut tutorial_text {
+        if text.sections[0].value != value {
+            text.sections[0].value = value.clone();
+        }
+    }
+}
+
+pub fn draw_tutorial_highlights(
+    mut commands: Commands,
+    scenario: Res<Scenario>,
+    progress: Res<TutorialProgress>,
+    highlights: Query<Entity, With<TutorialHighlight>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut material: Local<Option<Handle<ColorMaterial>>>,
+) {
+    if !progress.is_changed() {
+        return;
+    }
+
+    for entity in &highlights {
+        commands.entity(entity).despawn();
+    }
+
+    let Some(step) = scenario.tutorial.get(progress.step) else {
+        return;
+    };
+    if step.highlight.is_empty() {
+        return;
+    }
+
+    let material = material
+        .get_or_insert_with(|| materials.add(ColorMaterial::from(Color::GOLD)))
+        .clone();
+    let region = step.highlight.iter().copied().collect::<HashSet<_>>();
+
+    commands.spawn((
+        MaterialMesh2dBundle {
+            mesh: meshes
+                .add(outline_mesh(&region_outlines(&region), HEX_GAP * 2.))
+                .into(),
+            material,
+            transform: Transform::from_xyz(0., 0., 4.),
+            ..default()
+        },
+        TutorialHighlight,
+    ));
+}
@@ -1,8 +1,17 @@
+use std::collections::{HashMap, VecDeque};
+
 use bevy::prelude::*;
 use hexx::Hex;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    board::{components::Team, HEX_LAYOUT},
+    buildings::WATCHTOWER_RANGE,
+};
 
-use crate::board::{components::Team, HEX_LAYOUT};
+use super::{DAMAGE_EXPERIENCE, KILL_EXPERIENCE, LEVEL_THRESHOLDS};
 
+#[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Debug, Serialize, Deserialize)]
 pub enum UnitDefault {
     Archer,
     BladeDancer,
@@ -25,9 +34,22 @@ impl UnitDefault {
             UnitDefault::Newt => "sprites/frog.png".to_string(),
         }
     }
+
+    // what recruiting one costs
+    pub fn cost(&self) -> i32 {
+        match self {
+            UnitDefault::Archer => 4,
+            UnitDefault::BladeDancer => 5,
+            UnitDefault::Scout => 3,
+            UnitDefault::Knight => 6,
+            UnitDefault::Catapult => 7,
+            UnitDefault::Sniper => 8,
+            UnitDefault::Newt => 6,
+        }
+    }
 }
 
-#[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Debug)]
+#[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Debug, Serialize, Deserialize)]
 pub enum Keyword {
     // amount of damage reduced
     // for every hit (like repulsion armor)
@@ -51,14 +73,108 @@ pub enum Keyword {
     Despised,
 }
 
-#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+impl Keyword {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Keyword::Armor(_) => "Armor",
+            Keyword::Regeneration(_) => "Regeneration",
+            Keyword::StrikeBack => "Strike Back",
+            Keyword::Nimble => "Nimble",
+            Keyword::Executioner => "Executioner",
+            Keyword::Slow { .. } => "Slow",
+            Keyword::Despised => "Despised",
+        }
+    }
+
+    pub fn description(&self) -> String {
+        match self {
+            Keyword::Armor(amount) => format!("takes {amount} less damage from every hit"),
+            Keyword::Regeneration(amount) => format!("heals {amount} at the start of every turn"),
+            Keyword::StrikeBack => "hits back whenever it survives an attack".to_string(),
+            Keyword::Nimble => "takes the place of the units it kills".to_string(),
+            Keyword::Executioner => "killing doesn't use up its attack".to_string(),
+            Keyword::Slow { max_countdown, .. } => {
+                format!("after moving it waits {max_countdown} turns to act again")
+            }
+            Keyword::Despised => "attacking it doesn't use up the attack".to_string(),
+        }
+    }
+}
+
+// damage left after armor
+pub fn armored_damage(keywords: &[Keyword], damage: i32) -> i32 {
+    let armor = keywords.iter().find_map(|k| match k {
+        Keyword::Armor(amount) => Some(amount),
+        _ => None,
+    });
+
+    (damage - armor.unwrap_or(&0)).max(0)
+}
+
+// anything a unit can attack
+pub trait Target {
+    fn position(&self) -> Hex;
+    fn health(&self) -> i32;
+    // what it strikes back with
+    fn damage(&self) -> i32;
+    fn keywords(&self) -> &[Keyword];
+    // returns true when it was killed
+    fn take_damage(&mut self, damage: i32) -> bool;
+}
+
+// what an attack would do, shown before it is made
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AttackForecast {
+    // after the opponent's armor
+    pub damage: i32,
+    pub kills: bool,
+    // taken from a StrikeBack
+    pub retaliation: i32,
+    pub attacker_dies: bool,
+    // where a Nimble attacker ends up
+    pub moves_to: Option<Hex>,
+    pub keeps_attack: bool,
+}
+
+// what a unit can pick once it levels up
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum Promotion {
+    Damage,
+    Health,
+    // another point of Armor
+    Armor,
+    // one hex further out than
+    // it could attack before
+    Range,
+}
+
+impl Promotion {
+    pub const ALL: [Promotion; 4] = [
+        Promotion::Damage,
+        Promotion::Health,
+        Promotion::Armor,
+        Promotion::Range,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Promotion::Damage => "+1 damage",
+            Promotion::Health => "+2 health",
+            Promotion::Armor => "+1 armor",
+            Promotion::Range => "+1 range",
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug, Serialize, Deserialize)]
 pub enum Action {
     Move,
     Attack,
 }
 
-#[derive(Component, Clone)]
+#[derive(Component, Clone, PartialEq, Debug, Serialize, Deserialize)]
 pub struct Unit {
+    pub kind: UnitDefault,
     pub position: Hex,
     pub team: Team,
     pub max_health: i32,
@@ -72,6 +188,16 @@ pub struct Unit {
     // tiles relative to current
     // that can be attacked
     pub attack_hexes: Vec<Hex>,
+    #[serde(default)]
+    pub experience: i32,
+    // already applied to the stats above,
+    // kept to show what the unit picked
+    #[serde(default)]
+    pub promotions: Vec<Promotion>,
+    // the watchtower it mans, kept up to
+    // date by the command systems
+    #[serde(default)]
+    pub watchtower: Option<Hex>,
 }
 
 impl Unit {
@@ -80,6 +206,7 @@ impl Unit {
 
         match default {
             UnitDefault::Archer => Unit {
+                kind: default,
                 position,
                 team,
                 max_health: 1,
@@ -89,8 +216,12 @@ impl Unit {
                 actions,
                 move_hexes: Hex::NEIGHBORS_COORDS.to_vec(),
                 attack_hexes: Hex::ZERO.ring(2).collect::<Vec<Hex>>(),
+                experience: 0,
+                promotions: Vec::new(),
+                watchtower: None,
             },
             UnitDefault::BladeDancer => Unit {
+                kind: default,
                 position,
                 team,
                 max_health: 2,
@@ -100,8 +231,12 @@ impl Unit {
                 actions,
                 move_hexes: Hex::NEIGHBORS_COORDS.to_vec(),
                 attack_hexes: Hex::DIAGONAL_COORDS.to_vec(),
+                experience: 0,
+                promotions: Vec::new(),
+                watchtower: None,
             },
             UnitDefault::Scout => Unit {
+                kind: default,
                 position,
                 team,
                 max_health: 1,
@@ -111,8 +246,12 @@ impl Unit {
                 actions,
                 move_hexes: Hex::ZERO.range(2).collect::<Vec<Hex>>(),
                 attack_hexes: Hex::NEIGHBORS_COORDS.to_vec(),
+                experience: 0,
+                promotions: Vec::new(),
+                watchtower: None,
             },
             UnitDefault::Knight => Unit {
+                kind: default,
                 position,
                 team,
                 max_health: 4,
@@ -122,8 +261,12 @@ impl Unit {
                 actions,
                 move_hexes: [Hex::NEIGHBORS_COORDS, Hex::DIAGONAL_COORDS].concat(),
                 attack_hexes: Hex::NEIGHBORS_COORDS.to_vec(),
+                experience: 0,
+                promotions: Vec::new(),
+                watchtower: None,
             },
             UnitDefault::Catapult => Unit {
+                kind: default,
                 position,
                 team,
                 max_health: 2,
@@ -133,8 +276,12 @@ impl Unit {
                 actions,
                 move_hexes: Hex::NEIGHBORS_COORDS.to_vec(),
                 attack_hexes: Hex::ZERO.ring(3).collect::<Vec<Hex>>(),
+                experience: 0,
+                promotions: Vec::new(),
+                watchtower: None,
             },
             UnitDefault::Sniper => Unit {
+                kind: default,
                 position,
                 team,
                 max_health: 1,
@@ -147,8 +294,12 @@ impl Unit {
                 actions,
                 move_hexes: Hex::NEIGHBORS_COORDS.to_vec(),
                 attack_hexes: Hex::NEIGHBORS_COORDS.map(|h| h * 5).to_vec(),
+                experience: 0,
+                promotions: Vec::new(),
+                watchtower: None,
             },
             UnitDefault::Newt => Unit {
+                kind: default,
                 position,
                 team,
                 max_health: 10,
@@ -162,6 +313,9 @@ impl Unit {
                 actions,
                 move_hexes: Hex::NEIGHBORS_COORDS.to_vec(),
                 attack_hexes: Hex::NEIGHBORS_COORDS.to_vec(),
+                experience: 0,
+                promotions: Vec::new(),
+                watchtower: None,
             },
         }
     }
@@ -175,6 +329,23 @@ impl Unit {
         true
     }
 
+    pub fn heal(&mut self, amount: i32) {
+        self.health = (self.health + amount).min(self.max_health);
+    }
+
+    // new turns a Slow unit that moved
+    // has to wait before it acts again
+    pub fn waiting_turns(&self) -> Option<i32> {
+        if self.actions.contains(&Action::Move) {
+            return None;
+        }
+
+        self.keywords.iter().find_map(|k| match k {
+            Keyword::Slow { countdown, .. } => Some(countdown + 1),
+            _ => None,
+        })
+    }
+
     pub fn new_turn(&mut self) {
         let regen = self.keywords.iter().find_map(|k| match k {
             Keyword::Regeneration(amount) => Some(amount),
@@ -210,43 +381,322 @@ impl Unit {
         }
     }
 
-    fn take_damage(&mut self, damage: i32) -> bool {
-        let armor = self.keywords.iter().find_map(|k| match k {
-            Keyword::Armor(amount) => Some(amount),
-            _ => None,
-        });
-        let armored_damage = (damage - armor.unwrap_or(&0)).max(0);
-        self.health -= armored_damage;
-        println!("{}", self.health);
+    // returns the keywords of either side that changed the outcome
+    pub fn attack(
+        &mut self,
+        my_transform: &mut Transform,
+        opponent: &mut impl Target,
+    ) -> Vec<Keyword> {
+        self.attack_with(my_transform, opponent, self.damage)
+    }
 
-        self.health <= 0
+    // runs the attack on copies, so the
+    // forecast can't disagree with it
+    pub fn forecast(&self, opponent: &(impl Target + Clone)) -> AttackForecast {
+        let mut attacker = self.clone();
+        let mut defender = opponent.clone();
+        attacker.attack(&mut Transform::default(), &mut defender);
+
+        AttackForecast {
+            damage: opponent.health() - defender.health(),
+            kills: defender.health() <= 0,
+            retaliation: self.health - attacker.health,
+            attacker_dies: attacker.health <= 0,
+            moves_to: (attacker.position != self.position).then_some(attacker.position),
+            keeps_attack: attacker.actions.contains(&Action::Attack),
+        }
     }
 
-    pub fn attack(&mut self, my_transform: &mut Transform, opponent: &mut Unit) {
-        let killed = opponent.take_damage(self.damage);
+    // `damage` is what the attack rolled, before the opponent's armor
+    pub fn attack_with(
+        &mut self,
+        my_transform: &mut Transform,
+        opponent: &mut impl Target,
+        damage: i32,
+    ) -> Vec<Keyword> {
+        let mut triggered = Vec::new();
 
-        if !((killed && self.keywords.contains(&Keyword::Executioner))
-            || opponent.keywords.contains(&Keyword::Despised))
-        {
+        let health_before = opponent.health();
+        let killed = opponent.take_damage(damage);
+
+        let damage_dealt = health_before.max(0) - opponent.health().max(0);
+        self.experience += damage_dealt * DAMAGE_EXPERIENCE;
+        if killed {
+            self.experience += KILL_EXPERIENCE;
+        }
+
+        if killed && self.keywords.contains(&Keyword::Executioner) {
+            triggered.push(Keyword::Executioner);
+        } else if opponent.keywords().contains(&Keyword::Despised) {
+            triggered.push(Keyword::Despised);
+        } else {
             self.remove_action(Action::Attack);
         }
 
-        if !killed && opponent.keywords.contains(&Keyword::StrikeBack) {
-            self.take_damage(opponent.damage);
+        if !killed && opponent.keywords().contains(&Keyword::StrikeBack) {
+            triggered.push(Keyword::StrikeBack);
+            self.take_damage(opponent.damage());
         }
 
         if self.keywords.contains(&Keyword::Nimble) && killed {
-            self.position = opponent.position;
+            triggered.push(Keyword::Nimble);
+            self.position = opponent.position();
 
             my_transform.translation = HEX_LAYOUT.hex_to_world_pos(self.position).extend(1.);
         }
+
+        triggered
+    }
+
+    pub fn level(&self) -> usize {
+        LEVEL_THRESHOLDS
+            .iter()
+            .filter(|threshold| self.experience >= **threshold)
+            .count()
+    }
+
+    // levels reached that no promotion was picked for yet
+    pub fn pending_promotions(&self) -> usize {
+        self.level().saturating_sub(self.promotions.len())
+    }
+
+    pub fn promote(&mut self, promotion: Promotion) -> bool {
+        if self.pending_promotions() == 0 {
+            return false;
+        }
+
+        match promotion {
+            Promotion::Damage => self.damage += 1,
+            Promotion::Health => {
+                self.max_health += 2;
+                self.health += 2;
+            }
+            Promotion::Armor => {
+                let armor = self.keywords.iter_mut().find_map(|k| match k {
+                    Keyword::Armor(amount) => Some(amount),
+                    _ => None,
+                });
+
+                match armor {
+                    Some(amount) => *amount += 1,
+                    None => self.keywords.push(Keyword::Armor(1)),
+                }
+            }
+            Promotion::Range => {
+                // every hex just outside the
+                // ones it can already reach
+                let extended = self
+                    .attack_hexes
+                    .iter()
+                    .flat_map(|h| {
+                        h.all_neighbors()
+                            .into_iter()
+                            .filter(move |n| n.ulength() > h.ulength())
+                    })
+                    .collect::<Vec<_>>();
+
+                for hex in extended {
+                    if !self.attack_hexes.contains(&hex) {
+                        self.attack_hexes.push(hex);
+                    }
+                }
+            }
+        }
+
+        self.promotions.push(promotion);
+        true
     }
 
     pub fn relative_attack_hexes(&self) -> Vec<Hex> {
-        self.attack_hexes.iter().map(|h| *h + self.position).collect::<Vec<Hex>>()
+        let mut hexes = self
+            .attack_hexes
+            .iter()
+            .map(|h| *h + self.position)
+            .collect::<Vec<Hex>>();
+
+        // a manned watchtower adds everything in its range
+        if let Some(tower) = self.watchtower {
+            let reach = tower
+                .range(WATCHTOWER_RANGE)
+                .filter(|hex| *hex != self.position && !hexes.contains(hex))
+                .collect::<Vec<_>>();
+            hexes.extend(reach);
+        }
+
+        hexes
     }
 
     pub fn relative_move_hexes(&self) -> Vec<Hex> {
         self.move_hexes.iter().map(|h| *h + self.position).collect::<Vec<Hex>>()
     }
+
+    // the fewest moves to the destination, one hex a
+    // turn, `open` has to bound the search (the board)
+    pub fn path_to(&self, destination: Hex, open: impl Fn(Hex) -> bool) -> Option<Vec<Hex>> {
+        let mut came_from = HashMap::from([(self.position, self.position)]);
+        let mut frontier = VecDeque::from([self.position]);
+
+        while let Some(current) = frontier.pop_front() {
+            if current == destination {
+                let mut path = Vec::new();
+                let mut at = current;
+                while at != self.position {
+                    path.push(at);
+                    at = came_from[&at];
+                }
+                path.reverse();
+                return Some(path);
+            }
+
+            for step in &self.move_hexes {
+                let next = current + *step;
+                if open(next) && !came_from.contains_key(&next) {
+                    came_from.insert(next, current);
+                    frontier.push_back(next);
+                }
+            }
+        }
+
+        None
+    }
+}
+
+impl Target for Unit {
+    fn position(&self) -> Hex {
+        self.position
+    }
+
+    fn health(&self) -> i32 {
+        self.health
+    }
+
+    fn damage(&self) -> i32 {
+        self.damage
+    }
+
+    fn keywords(&self) -> &[Keyword] {
+        &self.keywords
+    }
+
+    fn take_damage(&mut self, damage: i32) -> bool {
+        self.health -= armored_damage(&self.keywords, damage);
+
+        self.health <= 0
+    }
+}
+
+// the buttons to pick a promotion, shown
+// while a unit with one pending is selected
+#[derive(Component)]
+pub struct PromotionMenu;
+
+#[derive(Component, Clone, Copy)]
+pub struct PromotionButton(pub Promotion);
+
+// the filled part of the bar under a unit
+#[derive(Component)]
+pub struct HealthBar;
+
+// lit while the unit still has the action
+#[derive(Component)]
+pub struct ActionPip(pub Action);
+
+// how long a Slow unit still waits
+#[derive(Component)]
+pub struct WaitCounter;
+
+// still has something to do this turn, units told to
+// wait or already on their way only need to attack
+pub fn needs_orders(unit: &Unit, order: Option<&Order>) -> bool {
+    match order {
+        Some(Order::Fortify) => false,
+        Some(Order::MoveTo(_)) => unit.actions.contains(&Action::Attack),
+        None => !unit.actions.is_empty(),
+    }
+}
+
+// standing orders for our own units, local to this player
+// and carried out with the same commands a click sends
+#[derive(Component, Clone, Copy, PartialEq, Debug)]
+pub enum Order {
+    // a move a turn until it gets there or is blocked
+    MoveTo(Hex),
+    // needs no attention until ordered otherwise
+    Fortify,
+}
+
+#[cfg(test)]
+mod tests {
+    use hexx::hex;
+
+    use super::*;
+
+    #[test]
+    fn kills_earn_a_promotion() {
+        let mut catapult = Unit::new_default(UnitDefault::Catapult, hex(0, 0), Team::Player(0));
+        catapult.actions = vec![Action::Attack];
+        let mut archer = Unit::new_default(UnitDefault::Archer, hex(3, 0), Team::Player(1));
+
+        catapult.attack(&mut Transform::default(), &mut archer);
+
+        // only the archer's one health counts as damage dealt
+        assert_eq!(catapult.experience, DAMAGE_EXPERIENCE + KILL_EXPERIENCE);
+        assert_eq!(catapult.pending_promotions(), 1);
+
+        assert!(catapult.promote(Promotion::Range));
+        assert!(!catapult.promote(Promotion::Damage));
+        assert!(catapult.attack_hexes.contains(&hex(4, 0)));
+        assert!(!catapult.attack_hexes.contains(&hex(2, 0)));
+        assert_eq!(catapult.promotions, [Promotion::Range]);
+    }
+
+    #[test]
+    fn armor_promotions_stack() {
+        let mut knight = Unit::new_default(UnitDefault::Knight, hex(0, 0), Team::Player(0));
+        knight.experience = LEVEL_THRESHOLDS[1];
+
+        assert!(knight.promote(Promotion::Armor));
+        assert!(knight.promote(Promotion::Health));
+
+        assert_eq!(knight.keywords, [Keyword::Armor(2)]);
+        assert_eq!((knight.health, knight.max_health), (6, 6));
+    }
+
+    #[test]
+    fn slow_units_count_down_their_wait() {
+        let mut sniper = Unit::new_default(UnitDefault::Sniper, hex(0, 0), Team::Player(0));
+        sniper.actions = vec![Action::Move, Action::Attack];
+        assert_eq!(sniper.waiting_turns(), None);
+
+        sniper.remove_action(Action::Move);
+        let mut waits = Vec::new();
+        for _ in 0..3 {
+            waits.push(sniper.waiting_turns());
+            sniper.new_turn();
+        }
+
+        assert_eq!(waits, [Some(3), Some(2), Some(1)]);
+        assert_eq!(sniper.waiting_turns(), None);
+    }
+
+    #[test]
+    fn paths_take_a_move_a_turn_around_blocked_tiles() {
+        let scout = Unit::new_default(UnitDefault::Scout, hex(0, 0), Team::Player(0));
+        let board = |h: Hex| h.unsigned_distance_to(Hex::ZERO) <= 6;
+
+        let path = scout.path_to(hex(6, 0), board).unwrap();
+        assert_eq!(path.len(), 3);
+        assert_eq!(path.last(), Some(&hex(6, 0)));
+        let mut from = scout.position;
+        for step in &path {
+            assert!(scout.move_hexes.contains(&(*step - from)));
+            from = *step;
+        }
+
+        assert_eq!(scout.path_to(hex(0, 0), board), Some(Vec::new()));
+
+        // walled in by everything within two hexes
+        let walled = |h: Hex| board(h) && h.unsigned_distance_to(Hex::ZERO) > 2;
+        assert_eq!(scout.path_to(hex(6, 0), walled), None);
+    }
 }
@@ -1,30 +1,67 @@
 use bevy::prelude::*;
 
+use crate::{
+    animation::systems::animations_finished,
+    command::CommandSet,
+    player::systems::player_turn,
+    state::{game_running, game_undecided, AppState},
+    util::despawn_all,
+};
+
 use self::{
+    components::{PromotionMenu, Unit},
     resources::SelectedUnit,
     systems::{
-        check_for_unit_movement, check_for_unit_selection, color_units, despawn_dead_units,
-        test_spawn_unit,
+        attach_unit_sprites, check_for_unit_movement, check_for_unit_selection, choose_promotion,
+        color_units, despawn_dead_units, deselect_unit, follow_orders, give_orders,
+        recruit_on_hovered_tile, select_next_unit, update_promotion_menu, update_unit_overlays,
     },
 };
 
 pub mod components;
 pub mod resources;
-mod systems;
+pub mod systems;
+
+// experience for every point of damage dealt and for a kill
+pub const DAMAGE_EXPERIENCE: i32 = 1;
+pub const KILL_EXPERIENCE: i32 = 3;
+// experience each level needs, every level is worth a promotion
+pub const LEVEL_THRESHOLDS: [i32; 3] = [4, 10, 18];
 
 pub struct UnitsPlugin;
 
 impl Plugin for UnitsPlugin {
     fn build(&self, app: &mut App) {
         app.insert_resource(SelectedUnit(None))
-            .add_systems(PostStartup, test_spawn_unit)
+            .add_systems(
+                OnExit(AppState::InGame),
+                (despawn_all::<Unit>, despawn_all::<PromotionMenu>, deselect_unit),
+            )
             .add_systems(
                 Update,
                 (
-                    despawn_dead_units,
-                    check_for_unit_movement.before(check_for_unit_selection),
-                    check_for_unit_selection,
-                    color_units,
+                    despawn_dead_units
+                        .after(CommandSet::Apply)
+                        .run_if(in_state(AppState::InGame)),
+                    (
+                        check_for_unit_movement,
+                        recruit_on_hovered_tile,
+                        choose_promotion,
+                        give_orders,
+                        follow_orders,
+                    )
+                        .run_if(game_running.and_then(player_turn).and_then(animations_finished))
+                        .run_if(game_undecided)
+                        .in_set(CommandSet::Issue),
+                    (
+                        check_for_unit_selection.after(CommandSet::Apply),
+                        select_next_unit.after(CommandSet::Apply),
+                        update_promotion_menu.after(CommandSet::Apply),
+                        attach_unit_sprites,
+                        update_unit_overlays.after(CommandSet::Apply),
+                        color_units,
+                    )
+                        .run_if(game_running),
                 ),
             );
     }
@@ -1,67 +1,214 @@
-use bevy::prelude::*;
-use hexx::{hex, Hex};
+use std::collections::HashSet;
 
-const UNIT_SPRITE_SIZE: f32 = HEX_SIZE / 110.;
+use bevy::{prelude::*, sprite::Anchor};
+use hexx::Hex;
 
-use crate::{board::{components::Team, resources::HexColors, HEX_SIZE, HEX_LAYOUT}, util::cursor_to_hex};
+pub const UNIT_SPRITE_SIZE: f32 = HEX_SIZE / 110.;
+const BAR_WIDTH: f32 = HEX_SIZE * 0.8;
+const BAR_HEIGHT: f32 = HEX_SIZE / 12.;
+const PIP_SIZE: f32 = HEX_SIZE / 8.;
+
+const RECRUIT_KEYS: [(KeyCode, UnitDefault); 7] = [
+    (KeyCode::Key1, UnitDefault::Archer),
+    (KeyCode::Key2, UnitDefault::BladeDancer),
+    (KeyCode::Key3, UnitDefault::Scout),
+    (KeyCode::Key4, UnitDefault::Knight),
+    (KeyCode::Key5, UnitDefault::Catapult),
+    (KeyCode::Key6, UnitDefault::Sniper),
+    (KeyCode::Key7, UnitDefault::Newt),
+];
+
+use crate::{
+    board::{
+        components::Emblem,
+        resources::{BoardIndex, HexColors},
+        systems::emblem_bundle,
+        HEX_SIZE, HEX_LAYOUT,
+    },
+    buildings::components::{Building, BuildingKind},
+    command::events::{CommandEvent, GameCommand},
+    menu::systems::{BUTTON_COLOR, FONT},
+    player::resources::{PlayerTeam, TurnCounter},
+    util::BoardCursor,
+};
 
 use super::{
-    components::{Action, Unit, UnitDefault},
+    components::{
+        needs_orders, Action, ActionPip, HealthBar, Order, Promotion, PromotionButton,
+        PromotionMenu, Unit, UnitDefault, WaitCounter,
+    },
     resources::SelectedUnit,
 };
 
-pub fn test_spawn_unit(mut commands: Commands, asset_server: Res<AssetServer>) {
-    spawn_unit(
-        &mut commands,
-        &asset_server,
-        hex(-2, 4),
-        UnitDefault::Knight,
-        Team::Ally,
-    );
-    spawn_unit(
-        &mut commands,
-        &asset_server,
-        hex(-2, 1),
-        UnitDefault::Newt,
-        Team::Enemy,
-    );
-    spawn_unit(
-        &mut commands,
-        &asset_server,
-        hex(-3, 0),
-        UnitDefault::Archer,
-        Team::Enemy,
-    );
-}
-
-fn spawn_unit(
-    commands: &mut Commands,
-    asset_server: &Res<AssetServer>,
-    hex_pos: Hex,
-    unit: UnitDefault,
-    team: Team,
-) {
-    let pixel_pos = HEX_LAYOUT.hex_to_world_pos(hex_pos);
+// sprites are attached by attach_unit_sprites,
+// so units can also be spawned without assets
+pub fn spawn_unit(commands: &mut Commands, unit: Unit) -> Entity {
+    let pixel_pos = HEX_LAYOUT.hex_to_world_pos(unit.position);
     commands
-        .spawn(SpriteBundle {
+        .spawn(Transform::from_translation(pixel_pos.extend(1.)))
+        .insert(unit)
+        .id()
+}
+
+// the emblem sits in the unit's top right corner,
+// undoing the sprite's scale so it keeps its size
+pub fn attach_unit_sprites(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    colors: Res<HexColors>,
+    units: Query<(Entity, &Unit), Added<Unit>>,
+) {
+    for (entity, unit) in &units {
+        let pixel_pos = HEX_LAYOUT.hex_to_world_pos(unit.position);
+        commands.entity(entity).insert(SpriteBundle {
             transform: Transform {
                 translation: pixel_pos.extend(1.),
                 scale: Vec3::splat(UNIT_SPRITE_SIZE),
                 ..Default::default()
             },
-            texture: asset_server.load(unit.sprite_location()),
+            texture: asset_server.load(unit.kind.sprite_location()),
+            ..default()
+        });
+
+        commands
+            .entity(entity)
+            .with_children(|parent| spawn_unit_overlays(parent, &asset_server, unit));
+
+        let corner = Vec3::new(HEX_SIZE / 2., HEX_SIZE / 2., 1.) / UNIT_SPRITE_SIZE;
+        let Some(mut emblem) =
+            emblem_bundle(unit.team, HEX_SIZE / 7., corner, &mut meshes, &colors)
+        else {
+            continue;
+        };
+        emblem.transform.scale = Vec3::splat(1. / UNIT_SPRITE_SIZE);
+
+        commands.entity(entity).with_children(|parent| {
+            parent.spawn(emblem).insert(Emblem(unit.team));
+        });
+    }
+}
+
+// health bar below the unit, action pips in the top left
+// and the turns a Slow unit waits in the bottom right
+fn spawn_unit_overlays(parent: &mut ChildBuilder, asset_server: &AssetServer, unit: &Unit) {
+    // positions are in the unit's scaled space
+    let at = |x: f32, y: f32| {
+        Transform::from_translation(Vec3::new(x, y, 1.) / UNIT_SPRITE_SIZE)
+            .with_scale(Vec3::splat(1. / UNIT_SPRITE_SIZE))
+    };
+
+    parent.spawn(SpriteBundle {
+        sprite: Sprite {
+            color: Color::BLACK,
+            custom_size: Some(Vec2::new(BAR_WIDTH, BAR_HEIGHT)),
+            ..default()
+        },
+        transform: at(0., -HEX_SIZE / 2.),
+        ..default()
+    });
+    // in front of the background, filled from the left
+    let mut fill = at(-BAR_WIDTH / 2., -HEX_SIZE / 2.);
+    fill.translation.z += 0.1;
+    parent
+        .spawn(SpriteBundle {
+            sprite: health_bar(unit),
+            transform: fill,
+            ..default()
+        })
+        .insert(HealthBar);
+
+    for (i, action) in [Action::Move, Action::Attack].into_iter().enumerate() {
+        parent
+            .spawn(SpriteBundle {
+                sprite: Sprite {
+                    color: pip_color(unit, action),
+                    custom_size: Some(Vec2::splat(PIP_SIZE)),
+                    ..default()
+                },
+                transform: at(-HEX_SIZE / 2. + i as f32 * PIP_SIZE * 1.5, HEX_SIZE / 2.),
+                ..default()
+            })
+            .insert(ActionPip(action));
+    }
+
+    let (wait, visibility) = wait_counter(unit);
+    parent
+        .spawn(Text2dBundle {
+            text: Text::from_section(
+                wait,
+                TextStyle {
+                    font: asset_server.load(FONT),
+                    font_size: HEX_SIZE / 3.,
+                    color: Color::GOLD,
+                },
+            ),
+            transform: at(HEX_SIZE / 2., -HEX_SIZE / 3.),
+            visibility,
             ..default()
         })
-        .insert(Unit::new_default(unit, hex_pos, team));
+        .insert(WaitCounter);
+}
+
+fn health_bar(unit: &Unit) -> Sprite {
+    let ratio = (unit.health as f32 / unit.max_health as f32).clamp(0., 1.);
+
+    Sprite {
+        color: Color::rgb(1. - ratio, ratio, 0.2),
+        custom_size: Some(Vec2::new(BAR_WIDTH * ratio, BAR_HEIGHT)),
+        anchor: Anchor::CenterLeft,
+        ..default()
+    }
+}
+
+fn pip_color(unit: &Unit, action: Action) -> Color {
+    match (unit.actions.contains(&action), action) {
+        (false, _) => Color::DARK_GRAY,
+        (true, Action::Move) => Color::CYAN,
+        (true, Action::Attack) => Color::ORANGE_RED,
+    }
+}
+
+fn wait_counter(unit: &Unit) -> (String, Visibility) {
+    match unit.waiting_turns() {
+        Some(turns) => (turns.to_string(), Visibility::Inherited),
+        None => (String::new(), Visibility::Hidden),
+    }
+}
+
+// the overlays follow every change to the unit
+pub fn update_unit_overlays(
+    units: Query<(&Unit, &Children), Changed<Unit>>,
+    mut bars: Query<&mut Sprite, With<HealthBar>>,
+    mut pips: Query<(&ActionPip, &mut Sprite), Without<HealthBar>>,
+    mut counters: Query<(&mut Text, &mut Visibility), With<WaitCounter>>,
+) {
+    for (unit, children) in &units {
+        for child in children {
+            if let Ok(mut sprite) = bars.get_mut(*child) {
+                *sprite = health_bar(unit);
+            }
+
+            if let Ok((pip, mut sprite)) = pips.get_mut(*child) {
+                sprite.color = pip_color(unit, pip.0);
+            }
+
+            if let Ok((mut text, mut visibility)) = counters.get_mut(*child) {
+                let (wait, wait_visibility) = wait_counter(unit);
+                text.sections[0].value = wait;
+                *visibility = wait_visibility;
+            }
+        }
+    }
 }
 
 pub fn check_for_unit_selection(
-    windows: Query<&Window>,
+    cursor: BoardCursor,
     buttons: Res<Input<MouseButton>>,
     mut selected_unit: ResMut<SelectedUnit>,
-    units: Query<(&Unit, Entity)>,
+    board_index: Res<BoardIndex>,
 ) {
-    let Some(hovered_hex) = cursor_to_hex(windows) else {
+    let Some(hovered_hex) = cursor.hex() else {
         return;
     };
 
@@ -74,15 +221,46 @@ pub fn check_for_unit_selection(
         return;
     }
 
-    for (unit, entity) in &units {
-        if unit.position != hovered_hex {
-            continue;
-        }
+    selected_unit.0 = board_index.unit(hovered_hex);
+}
 
-        selected_unit.0 = Some(entity);
+// Tab selects the next of our units with something
+// left to do and centers the camera on it
+pub fn select_next_unit(
+    keys: Res<Input<KeyCode>>,
+    player_team: Res<PlayerTeam>,
+    mut selected_unit: ResMut<SelectedUnit>,
+    units: Query<(Entity, &Unit, Option<&Order>)>,
+    mut cameras: Query<&mut Transform, With<Camera>>,
+) {
+    if !keys.just_released(KeyCode::Tab) {
         return;
     }
 
+    let mut candidates = units
+        .iter()
+        .filter(|(_, unit, order)| unit.team == player_team.0 && needs_orders(unit, *order))
+        .map(|(entity, unit, _)| (entity, unit.position))
+        .collect::<Vec<_>>();
+    candidates.sort_by_key(|(entity, _)| *entity);
+
+    let next = selected_unit
+        .0
+        .and_then(|selected| candidates.iter().position(|(entity, _)| *entity == selected))
+        .map_or(0, |index| index + 1);
+    let Some((entity, position)) = candidates.get(next).or(candidates.first()).copied() else {
+        return;
+    };
+
+    selected_unit.0 = Some(entity);
+    let center = HEX_LAYOUT.hex_to_world_pos(position);
+    for mut transform in &mut cameras {
+        transform.translation.x = center.x;
+        transform.translation.y = center.y;
+    }
+}
+
+pub fn deselect_unit(mut selected_unit: ResMut<SelectedUnit>) {
     selected_unit.0 = None;
 }
 
@@ -95,18 +273,28 @@ pub fn despawn_dead_units(mut commands: Commands, units: Query<(Entity, &Unit)>)
 }
 
 pub fn check_for_unit_movement(
-    windows: Query<&Window>,
+    cursor: BoardCursor,
     mut buttons: ResMut<Input<MouseButton>>,
     selected_unit: Res<SelectedUnit>,
-    mut units: Query<(&mut Unit, &mut Transform, Entity)>,
+    player_team: Res<PlayerTeam>,
+    targets: (Query<&Unit>, Query<&Building>),
+    board_index: Res<BoardIndex>,
+    mut game_commands: EventWriter<CommandEvent>,
 ) {
+    let (units, buildings) = targets;
+    let building = |hex| {
+        board_index
+            .building(hex)
+            .and_then(|entity| buildings.get(entity).ok())
+    };
+
     // make sure we left clicked
     if !buttons.just_released(MouseButton::Left) {
         return;
     }
 
     // make sure we are hovering a hex
-    let Some(hovered_hex) = cursor_to_hex(windows) else {
+    let Some(hovered_hex) = cursor.hex() else {
         return;
     };
 
@@ -116,70 +304,296 @@ pub fn check_for_unit_movement(
     };
 
     // make sure the entity is a unit
-    let Ok((unit, _unit_transform, _unit_id)) = units.get(selected_entity) else {
+    let Ok(unit) = units.get(selected_entity) else {
         return;
     };
 
-    // make sure the entity is an ally
-    if unit.team != Team::Ally {
+    // make sure the entity is ours
+    if unit.team != player_team.0 {
         return;
     }
 
     if unit.relative_attack_hexes().contains(&hovered_hex) && unit.actions.contains(&Action::Attack) {
-        let pixel_pos = HEX_LAYOUT.hex_to_world_pos(hovered_hex);
+        let enemy_on_tile = board_index
+            .unit(hovered_hex)
+            .and_then(|entity| units.get(entity).ok())
+            .is_some_and(|enemy_unit| enemy_unit.team != unit.team);
 
-        let mut enemy_entity = None;
-        for (enemy_unit, transform, entity) in &units {
-            if enemy_unit.team == Team::Ally || transform.translation != pixel_pos.extend(1.) {
-                continue;
-            }
+        // a unit on the tile is hit before the building
+        let enemy_building = board_index.unit(hovered_hex).is_none()
+            && building(hovered_hex).is_some_and(|building| building.team != unit.team);
 
-            enemy_entity = Some(entity);
-            break;
+        if enemy_on_tile || enemy_building {
+            game_commands.send(CommandEvent::local(GameCommand::Attack {
+                from: unit.position,
+                target: hovered_hex,
+            }));
+            buttons.clear_just_released(MouseButton::Left);
+            return;
         }
+    }
 
-        if let Some(enemy_entity) = enemy_entity {
-            let entities = units.get_many_mut([selected_entity, enemy_entity]);
-            if let Ok(
-                [(mut attacker_unit, mut attacker_transform, _attacker_entity), (mut defender_unit, _defender_transform, _defender_entity)],
-            ) = entities
-            {
-                attacker_unit.attack(&mut attacker_transform, &mut defender_unit);
-                buttons.clear_just_released(MouseButton::Left);
-                return;
-            }
+    if unit.relative_move_hexes().contains(&hovered_hex) && unit.actions.contains(&Action::Move) {
+        // check if tile is occupied
+        if board_index.unit(hovered_hex).is_some() {
+            return;
         }
+
+        if building(hovered_hex).is_some_and(|building| building.kind == BuildingKind::Wall) {
+            return;
+        }
+
+        game_commands.send(CommandEvent::local(GameCommand::Move {
+            from: unit.position,
+            to: hovered_hex,
+        }));
     }
+}
 
-    let unit = units.get(selected_entity).unwrap().0;
+// what a move command accepts besides the unit's pattern
+pub fn walkable(board_index: &BoardIndex, buildings: &Query<&Building>, hex: Hex) -> bool {
+    board_index.contains(hex)
+        && board_index.unit(hex).is_none()
+        && board_index
+            .building(hex)
+            .and_then(|entity| buildings.get(entity).ok())
+            .is_none_or(|building| building.kind != BuildingKind::Wall)
+}
 
-    if unit.relative_move_hexes().contains(&hovered_hex) && unit.actions.contains(&Action::Move) {
-        let new_position = HEX_LAYOUT.hex_to_world_pos(hovered_hex).extend(1.);
+// with one of our units selected, G sends it to the hovered
+// hex (its own hex cancels) and F fortifies it or wakes it up
+pub fn give_orders(
+    mut commands: Commands,
+    keys: Res<Input<KeyCode>>,
+    cursor: BoardCursor,
+    selected: (Res<SelectedUnit>, Res<PlayerTeam>),
+    units: Query<(&Unit, Option<&Order>)>,
+    board: (Res<BoardIndex>, Query<&Building>),
+) {
+    let (selected_unit, player_team) = selected;
+    let (board_index, buildings) = board;
 
-        // check if tile is occupied
-        for (_unit, transform, _entity) in &units {
-            if transform.translation != new_position {
-                continue;
-            }
+    let Some((entity, (unit, order))) = selected_unit
+        .0
+        .and_then(|entity| units.get(entity).ok().map(|unit| (entity, unit)))
+        .filter(|(_, (unit, _))| unit.team == player_team.0)
+    else {
+        return;
+    };
 
-            // tile is occupied
-            return;
+    if keys.just_released(KeyCode::F) {
+        match order {
+            Some(Order::Fortify) => commands.entity(entity).remove::<Order>(),
+            _ => commands.entity(entity).insert(Order::Fortify),
+        };
+    }
+
+    if !keys.just_released(KeyCode::G) {
+        return;
+    }
+
+    let Some(hovered_hex) = cursor.hex() else {
+        return;
+    };
+
+    if hovered_hex == unit.position {
+        commands.entity(entity).remove::<Order>();
+        return;
+    }
+
+    let open = |hex| walkable(&board_index, &buildings, hex);
+    if unit.path_to(hovered_hex, open).is_some() {
+        commands.entity(entity).insert(Order::MoveTo(hovered_hex));
+    }
+}
+
+// moves one unit a frame, so every path is planned on the
+// board the last move left behind, and the moves play in turn
+pub fn follow_orders(
+    mut commands: Commands,
+    turn_counter: Res<TurnCounter>,
+    mut advanced: Local<(i32, HashSet<Entity>)>,
+    player_team: Res<PlayerTeam>,
+    units: Query<(Entity, &Unit, &Order)>,
+    board: (Res<BoardIndex>, Query<&Building>),
+    mut game_commands: EventWriter<CommandEvent>,
+) {
+    let (board_index, buildings) = board;
+
+    // a unit that couldn't move tries again next turn
+    if advanced.0 != turn_counter.0 {
+        *advanced = (turn_counter.0, HashSet::new());
+    }
+
+    for (entity, unit, order) in &units {
+        let Order::MoveTo(destination) = *order else {
+            continue;
+        };
+
+        if unit.team != player_team.0 || advanced.1.contains(&entity) {
+            continue;
+        }
+
+        if unit.position == destination {
+            commands.entity(entity).remove::<Order>();
+            continue;
+        }
+
+        if !unit.actions.contains(&Action::Move) {
+            continue;
+        }
+
+        let open = |hex| walkable(&board_index, &buildings, hex);
+        let Some(next) = unit.path_to(destination, open).and_then(|path| path.first().copied())
+        else {
+            info!("{:?} at {:?} is blocked on its way", unit.kind, unit.position);
+            commands.entity(entity).remove::<Order>();
+            continue;
+        };
+
+        advanced.1.insert(entity);
+        game_commands.send(CommandEvent::local(GameCommand::Move {
+            from: unit.position,
+            to: next,
+        }));
+        return;
+    }
+}
+
+// hover the capital or a barracks and press
+// a number, in the order of UnitDefault
+pub fn recruit_on_hovered_tile(
+    keys: Res<Input<KeyCode>>,
+    cursor: BoardCursor,
+    mut game_commands: EventWriter<CommandEvent>,
+) {
+    let Some((_, kind)) = RECRUIT_KEYS.iter().find(|(key, _)| keys.just_released(*key)) else {
+        return;
+    };
+
+    let Some(hovered_hex) = cursor.hex() else {
+        return;
+    };
+
+    game_commands.send(CommandEvent::local(GameCommand::Recruit {
+        at: hovered_hex,
+        kind: *kind,
+    }));
+}
+
+// spawned while one of our units with a promotion
+// pending is selected and removed once there is none
+pub fn update_promotion_menu(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    selected_unit: Res<SelectedUnit>,
+    player_team: Res<PlayerTeam>,
+    units: Query<&Unit>,
+    menus: Query<Entity, With<PromotionMenu>>,
+) {
+    let promotable = selected_unit
+        .0
+        .and_then(|entity| units.get(entity).ok())
+        .is_some_and(|unit| unit.team == player_team.0 && unit.pending_promotions() > 0);
+
+    let shown = menus.iter().next().is_some();
+    if promotable == shown {
+        return;
+    }
+
+    if !promotable {
+        for entity in &menus {
+            commands.entity(entity).despawn_recursive();
         }
+        return;
+    }
+
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                left: Val::Px(10.),
+                bottom: Val::Px(10.),
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(6.),
+                ..default()
+            },
+            ..default()
+        })
+        .insert(PromotionMenu)
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                "Promote",
+                TextStyle {
+                    font: asset_server.load(FONT),
+                    font_size: 30.,
+                    color: Color::GOLD,
+                },
+            ));
 
-        let (mut unit, mut unit_transform, _unit_id) = units.get_mut(selected_entity).unwrap();
+            for promotion in Promotion::ALL {
+                parent
+                    .spawn(ButtonBundle {
+                        style: Style {
+                            padding: UiRect::axes(Val::Px(12.), Val::Px(6.)),
+                            ..default()
+                        },
+                        background_color: BUTTON_COLOR.into(),
+                        ..default()
+                    })
+                    .insert(PromotionButton(promotion))
+                    .with_children(|button| {
+                        button.spawn(TextBundle::from_section(
+                            promotion.name(),
+                            TextStyle {
+                                font: asset_server.load(FONT),
+                                font_size: 24.,
+                                color: Color::WHITE,
+                            },
+                        ));
+                    });
+            }
+        });
+}
 
-        unit_transform.translation = new_position;
-        unit.position = hovered_hex;
-        unit.remove_action(Action::Move);
+pub fn choose_promotion(
+    buttons: Query<(&Interaction, &PromotionButton), Changed<Interaction>>,
+    selected_unit: Res<SelectedUnit>,
+    units: Query<&Unit>,
+    mut game_commands: EventWriter<CommandEvent>,
+) {
+    let Some(unit) = selected_unit.0.and_then(|entity| units.get(entity).ok()) else {
+        return;
+    };
+
+    for (interaction, button) in &buttons {
+        if *interaction == Interaction::Pressed {
+            game_commands.send(CommandEvent::local(GameCommand::Promote {
+                at: unit.position,
+                promotion: button.0,
+            }));
+        }
     }
 }
 
-pub fn color_units(mut units: Query<(&Unit, &mut Sprite)>, colors: Res<HexColors>) {
+pub fn color_units(
+    mut units: Query<(&Unit, &mut Sprite)>,
+    mut emblems: Query<(&Emblem, &mut Handle<ColorMaterial>)>,
+    colors: Res<HexColors>,
+) {
     for (unit, mut sprite) in &mut units {
-        sprite.color = match unit.team {
-            Team::Ally => colors.ally_sprite,
-            Team::Enemy => colors.enemy_sprite,
-            _ => colors.ally_sprite,
+        if let Some(team_colors) = colors.team(unit.team) {
+            sprite.color = team_colors.sprite;
+        }
+    }
+
+    if !colors.is_changed() {
+        return;
+    }
+
+    for (emblem, mut material) in &mut emblems {
+        if let Some(team_colors) = colors.team(emblem.0) {
+            material.set_if_neq(team_colors.emblem.clone());
         }
     }
 }
@@ -1,26 +1,38 @@
-use bevy::prelude::*;
+use bevy::{ecs::system::SystemParam, prelude::*};
 use hexx::Hex;
 
-use crate::board::{HEX_RADIUS, HEX_LAYOUT};
+use crate::board::{resources::BoardIndex, HEX_LAYOUT};
 
+// the hex under the cursor, if it's on the board
+// and not covered by a button. goes through the
+// camera so it still works when the camera is
+// zoomed out to fit a larger board
+#[derive(SystemParam)]
+pub struct BoardCursor<'w, 's> {
+    windows: Query<'w, 's, &'static Window>,
+    cameras: Query<'w, 's, (&'static Camera, &'static GlobalTransform)>,
+    board_index: Res<'w, BoardIndex>,
+    buttons: Query<'w, 's, &'static Interaction>,
+}
 
-pub fn cursor_to_hex(windows: Query<&Window>) -> Option<Hex> {
-    let Ok(primary) = windows.get_single() else {
-        return None;
-    };
+impl BoardCursor<'_, '_> {
+    pub fn hex(&self) -> Option<Hex> {
+        if self.buttons.iter().any(|interaction| *interaction != Interaction::None) {
+            return None;
+        }
 
-    let Some(cursor_pos) = primary.cursor_position() else {
-        return None;
-    };
+        let primary = self.windows.get_single().ok()?;
+        let (camera, camera_transform) = self.cameras.get_single().ok()?;
 
-    let x = cursor_pos.x - primary.resolution.width() / 2.;
-    let y = (cursor_pos.y - primary.resolution.height() / 2.) * -1.;
+        let cursor_pos = camera.viewport_to_world_2d(camera_transform, primary.cursor_position()?)?;
+        let cursors_hex_pos = HEX_LAYOUT.world_pos_to_hex(cursor_pos);
 
-    let cursors_hex_pos = HEX_LAYOUT.world_pos_to_hex(Vec2::new(x, y));
+        self.board_index.contains(cursors_hex_pos).then_some(cursors_hex_pos)
+    }
+}
 
-    if Hex::ZERO.range(HEX_RADIUS as u32).collect::<Vec<Hex>>().contains(&cursors_hex_pos) {
-        return Some(cursors_hex_pos);
-    } else {
-        return None
-    };
-}
\ No newline at end of file
+pub fn despawn_all<T: Component>(mut commands: Commands, entities: Query<Entity, With<T>>) {
+    for entity in &entities {
+        commands.entity(entity).despawn_recursive();
+    }
+}